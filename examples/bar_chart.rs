@@ -0,0 +1,25 @@
+use std::error::Error;
+
+use helio::chart::{BarChartBuilder, BarSort};
+use helio::core::Scene;
+use helio::svg_backend::{self, SVGContext};
+use helio::data::{read_file, ReadOptions};
+use helio::transform::Aggregate;
+
+fn main() -> Result<(), Box<dyn Error>> {
+
+    let table = read_file("examples/data/cars.csv", &ReadOptions::default())?;
+    let means = table.group_by(&["cylinders"])?
+        .aggregate(&[Aggregate::mean("economy (mpg)")])?;
+
+    let chart = BarChartBuilder::default()
+        .category(means.column("cylinders")?.to_strings())
+        .value(means.column("mean_economy (mpg)")?.to_f64())
+        .sort(BarSort::Category)
+        .labels(true)
+        .build()?;
+
+    let scene: Scene<SVGContext> = chart.scene()?;
+    svg_backend::save("chart.svg", &scene);
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use std::error::Error;
+
+use helio::core::Scene;
+use helio::chart::{ChartBuilder, EncodingBuilder, FieldDef, MarkType};
+use helio::svg_backend;
+use helio::data::{read_file, ReadOptions};
+
+fn main() -> Result<(), Box<dyn Error>> {
+
+    let table = read_file("examples/data/cars.csv", &ReadOptions::default())?;
+
+    // Scales, axes and the legend are inferred from the types of the fields
+    let encoding = EncodingBuilder::default()
+        .x(FieldDef::quantitative("weight (lb)"))
+        .y(FieldDef::quantitative("displacement (cc)"))
+        .color(FieldDef::ordinal("cylinders"))
+        .size(FieldDef::quantitative("year"))
+        .tooltip(vec![FieldDef::nominal("name"), FieldDef::quantitative("economy (mpg)")])
+        .build()?;
+
+    let chart = ChartBuilder::default()
+        .data(table)
+        .mark(MarkType::Circle)
+        .encoding(encoding)
+        .title(Some("Cars".to_string()))
+        .width(800_u32)
+        .height(600_u32)
+        .build()?;
+
+    let scene: Scene<svg_backend::SVGContext> = chart.scene()?;
+    svg_backend::save("chart.svg", &scene);
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use std::error::Error;
+use std::rc::Rc;
+
+use helio::core::{Canvas, Segment, SegmentBuilder, StyleBuilder, Scene};
+use helio::chart::HistogramBuilder;
+use helio::transform::{BinParamsBuilder, Thresholds};
+use helio::svg_backend;
+use helio::color::{WHITE, BLACK, PALETTE_CATEGORY10};
+use helio::data::{read_file, ReadOptions};
+
+fn main() -> Result<(), Box<dyn Error>> {
+
+    let width = 800;
+    let height = 400;
+    let margin = (50.0, 70.0, 50.0, 70.0); // Top, Right, Bottom, Left
+
+    let x_column = "weight (lb)";
+
+    let table = read_file("examples/data/cars.csv", &ReadOptions::default())?;
+    let x_data: Vec<f64> = table.column(x_column)?.to_f64();
+
+    let canvas = Canvas {
+        width,
+        height,
+        background: WHITE
+    };
+    let mut scene = Scene::new(canvas);
+
+    let bar_style = Rc::new(StyleBuilder::default()
+        .fill(Some(PALETTE_CATEGORY10[0].into()))
+        .build()?);
+
+    let histogram = HistogramBuilder::default()
+        .data(x_data)
+        .bins(BinParamsBuilder::default().thresholds(Thresholds::Count(20)).build()?)
+        .x_range([0.0 + margin.3, width as f64 - margin.1])
+        .y_range([height as f64 - margin.0, 0.0 + margin.2])
+        .style(bar_style)
+        .build()?;
+    let layout = histogram.layout();
+
+    for rect in layout.rects {
+        scene.add(Box::new(rect));
+    }
+
+    let axis_style = Rc::new(StyleBuilder::default()
+        .stroke(Some(BLACK.into()))
+        .stroke_width(1.0)
+        .build()?);
+
+    let axis_bottom: Segment = SegmentBuilder::default()
+        .x(layout.x_scale.range[0])
+        .x2(layout.x_scale.range[1])
+        .y(layout.y_scale.range[0])
+        .y2(layout.y_scale.range[0])
+        .style(axis_style.clone())
+        .build()?;
+    scene.add(Box::new(axis_bottom));
+
+    svg_backend::save("chart.svg", &scene);
+    Ok(())
+}
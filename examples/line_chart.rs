@@ -1,18 +1,18 @@
 
-use csv;
 
 
 
 use std::error::Error;
 use std::rc::Rc;
-use chrono::{Utc, NaiveDate, DateTime as ChDateTime};
-use itertools::Itertools;
 
-use helio::core::{Color, Canvas, Line, StyleBuilder, Scene, TextBuilder, TextAlign};
+use helio::core::{Color, Canvas, Line, LineBuilder, Style, StyleBuilder, Scene, Text, TextBuilder, TextAlign};
+use helio::chart::{Bounds, Candidate, LabelLayoutBuilder};
 use helio::scale::{OrdinalScale, OrdinalScaleBuilder, DateTime, TimeScale, TimeScaleBuilder, LinearScale, LinearScaleBuilder};
 use helio::svg_backend;
 use helio::color;
 use helio::utils::{Extend};
+use helio::data::{read_file, ReadOptionsBuilder};
+use helio::transform::Downsample;
 
 fn main() -> Result<(), Box<dyn Error>> {
 
@@ -25,32 +25,20 @@ fn main() -> Result<(), Box<dyn Error>> {
     let c_column = "symbol";
 
     // Data to use
-    let mut x_data: Vec<DateTime> = Vec::new();
-    let mut y_data: Vec<f64> = Vec::new();
-    let mut c_data: Vec<String> = Vec::new();
-    
-    let mut rdr = csv::Reader::from_path("examples/data/stocks.csv")?;
-    println!("{:?}", rdr.headers()?);
-
-    let x_data_idx = rdr.headers()?.iter().position(|x| x == x_column)
-        .ok_or(format!("'{}' not in the dataset", x_column) )?;
-    let y_data_idx = rdr.headers()?.iter().position(|x| x == y_column)
-        .ok_or(format!("'{}' not in the dataset", y_column) )?;
-    let c_data_idx = rdr.headers()?.iter().position(|x| x == c_column)
-        .ok_or(format!("'{}' not in the dataset", c_column) )?;
-
-    for result in rdr.records() {
-        let record = result?;
-        let date = NaiveDate::parse_from_str(record.get(x_data_idx).unwrap(), "%b %e %Y")?.and_hms(0,0,0);
-        let datetime = ChDateTime::<Utc>::from_utc(date, Utc);
-        x_data.push(datetime.into());
-        y_data.push(record.get(y_data_idx).unwrap().parse()?);
-        c_data.push(record.get(c_data_idx).unwrap().parse()?);
-    }
+    let options = ReadOptionsBuilder::default()
+        .date_formats(vec!["%b %e %Y".to_string()])
+        .build()?;
+    let table = read_file("examples/data/stocks.csv", &options)?;
+    println!("{:?}", table.names());
+
+    let x_data: Vec<DateTime> = table.column(x_column)?.as_datetime()
+        .ok_or(format!("'{}' is not a date", x_column))?.to_vec();
+    let y_data: Vec<f64> = table.column(y_column)?.to_f64();
+    let c_data: Vec<String> = table.column(c_column)?.to_strings();
 
     let canvas = Canvas {
-        width: width,
-        height: height,
+        width,
+        height,
         background: color::WHITE
     };
 
@@ -71,31 +59,41 @@ fn main() -> Result<(), Box<dyn Error>> {
         .build()?;
 
     let mut scene = Scene::new(canvas);
-    // for r in x_scale.call(&x_data).iter().zip(y_scale.call(&y_data).iter()) {
 
     let x = x_scale.call(&x_data);
     let y = y_scale.call(&y_data);
 
-    for category in c_data.iter().unique() {
+    let groups = table.group_by(&[c_column])?;
+    let mut end_labels = vec![];
+    for rows in groups.groups() {
+        let category = &c_data[rows[0]];
+        let last = *rows.last().unwrap();
+        end_labels.push(Text { x: x[last], y: y[last], text: category.clone(), ..Text::default() });
         let style = StyleBuilder::default()
-            .stroke(Some(c_scale.call(&[category.clone()])[0]))
+            .stroke(Some(c_scale.call(std::slice::from_ref(category))[0].into()))
             .stroke_width(2)
             .build()?;
 
-        let rows = c_data.iter()
-            .enumerate()
-            .filter(move |&(_i, c)| *c == *category)
-            .map(|(i, _c)| i);
-
-        let mut line = Line{x: vec![], y: vec![], style: Rc::new(style)};
-        for row in rows {
-            line.add_x(x[row]);
-            line.add_y(y[row]);
-        }
-
+        let line: Line = LineBuilder::default()
+            .x(rows.iter().map(|&row| x[row]).collect::<Vec<f64>>())
+            .y(rows.iter().map(|&row| y[row]).collect::<Vec<f64>>())
+            .style(Rc::new(style))
+            .downsample(Some(Downsample::Lttb))
+            .build()?;
         scene.add(Box::new(line));
     }
 
+    // Symbols at the end of their lines, moved apart when they are too close
+    let layout = LabelLayoutBuilder::default()
+        .candidates(Candidate::right(6.0, 6.0, 4))
+        .bounds(Some(Bounds::new(0.0, 0.0, width as f64, height as f64)))
+        .leader_distance(Some(8.0))
+        .build()?;
+    let leader_style = Style { stroke: Some(color::GRAY.into()), dash_array: vec![2.0, 2.0], ..Style::default() };
+    let (leaders, texts) = layout.marks(&end_labels, Rc::new(leader_style));
+    leaders.into_iter().for_each(|s| scene.add(Box::new(s)));
+    texts.into_iter().for_each(|t| scene.add(Box::new(t)));
+
     let title = TextBuilder::default()
         .x(width / 2)
         .y(40)
@@ -1,14 +1,13 @@
-#[macro_use] extern crate helio;
-use csv;
-
 use std::error::Error;
 use std::rc::Rc;
 
-use helio::core::{Color, Canvas, Circle, CircleBuilder, Segment, SegmentBuilder,StyleBuilder, Scene};
-use helio::scale::{LinearScale, LinearScaleBuilder, OrdinalScale, OrdinalScaleBuilder, PowScale, PowScaleBuilder};
+use helio::core::{Color, Canvas, CircleBatch, CircleBatchBuilder, Segment, SegmentBuilder,StyleBuilder, Scene};
+use helio::scale::{LinearScale, LinearScaleBuilder, OrdinalScale, PowScale, PowScaleBuilder};
 use helio::svg_backend;
-use helio::color::{WHITE, BLACK, PALETTE_CATEGORY10};
+use helio::color::{WHITE, BLACK, GRAY, PALETTE_CATEGORY10};
+use helio::transform::{RegressionMethod, RegressionParamsBuilder, regression};
 use helio::utils::Extend;
+use helio::data::{read_file, ReadOptions};
 
 fn main() -> Result<(), Box<dyn Error>> {
 
@@ -22,34 +21,17 @@ fn main() -> Result<(), Box<dyn Error>> {
     let c_column = "cylinders";
 
     // Data to use
-    let mut x_data: Vec<f64> = Vec::new();
-    let mut y_data: Vec<f64> = Vec::new();
-    let mut r_data: Vec<f64> = Vec::new();
-    let mut c_data: Vec<String> = Vec::new();
-    
-    let mut rdr = csv::Reader::from_path("examples/data/cars.csv")?;
-    println!("{:?}", rdr.headers()?);
-
-    let x_data_idx = rdr.headers()?.iter().position(|x| x == x_column)
-        .ok_or(format!("'{}' not in the dataset", x_column) )?;
-    let y_data_idx = rdr.headers()?.iter().position(|x| x == y_column)
-        .ok_or(format!("'{}' not in the dataset", y_column) )?;
-    let r_data_idx = rdr.headers()?.iter().position(|x| x == r_column)
-        .ok_or(format!("'{}' not in the dataset", r_column) )?;
-    let c_data_idx = rdr.headers()?.iter().position(|x| x == c_column)
-        .ok_or(format!("'{}' not in the dataset", c_column) )?;
-
-    for result in rdr.records() {
-        let record = result?;
-        x_data.push(record.get(x_data_idx).unwrap().parse()?);
-        y_data.push(record.get(y_data_idx).unwrap().parse()?);
-        r_data.push(record.get(r_data_idx).unwrap().parse()?);
-        c_data.push(record.get(c_data_idx).unwrap().parse()?);
-    }
+    let table = read_file("examples/data/cars.csv", &ReadOptions::default())?;
+    println!("{:?}", table.names());
+
+    let x_data: Vec<f64> = table.column(x_column)?.to_f64();
+    let y_data: Vec<f64> = table.column(y_column)?.to_f64();
+    let r_data: Vec<f64> = table.column(r_column)?.to_f64();
+    let c_data: Vec<String> = table.column(c_column)?.to_strings();
 
     let canvas = Canvas {
-        width: width,
-        height: height,
+        width,
+        height,
         background: WHITE
     };
 
@@ -69,37 +51,42 @@ fn main() -> Result<(), Box<dyn Error>> {
         .exponent(2)
         .build()?;
 
-    let c_scale: OrdinalScale<String, Color> = OrdinalScaleBuilder::default()
-        .domain(vec!['6'.to_string(),'8'.to_string()])
-        .range(PALETTE_CATEGORY10.to_vec())
-        .unknown(Color::from_hex("#ccc")?)
-        .build()?;
+    let c_scale: OrdinalScale<String, Color> = OrdinalScale::from_data(&c_data, PALETTE_CATEGORY10.to_vec());
 
     let mut scene = Scene::new(canvas);
     
-    for row in izip!(x_scale.call(&x_data), 
-                     y_scale.call(&y_data),
-                     r_scale.call(&r_data),
-                     c_scale.call(&c_data)) {
-
-        let (x, y, r, c) = row;
-           
-        let style = StyleBuilder::default()
-            .fill(Some(c))
-            .build()?;
-
-        let circle : Circle = CircleBuilder::default()
-            .x(x)
-            .y(y)
-            .radius(r)
-            .style(style)
-            .build()?;
-
-        scene.add(Box::new(circle));
+    let fill: Vec<Option<Color>> = c_scale.call(&c_data).into_iter().map(Some).collect();
+    let circles: CircleBatch = CircleBatchBuilder::default()
+        .x(x_scale.call(&x_data))
+        .y(y_scale.call(&y_data))
+        .radius(r_scale.call(&r_data))
+        .fill(fill)
+        .build()?;
+
+    scene.add(Box::new(circles));
+
+    let params = RegressionParamsBuilder::default()
+        .method(RegressionMethod::Linear)
+        .confidence(Some(0.95))
+        .build()?;
+    if let Some(fit) = regression(&x_data, &y_data, &params) {
+        println!("R² = {:.3}", fit.r_squared);
+        let band_style = Rc::new(StyleBuilder::default()
+            .fill(Some(Color { a: 0.2, ..GRAY }.into()))
+            .build()?);
+        let trend_style = Rc::new(StyleBuilder::default()
+            .fill(None)
+            .stroke(Some(BLACK.into()))
+            .stroke_width(2.0)
+            .build()?);
+        if let Some(band) = fit.area(&x_scale, &y_scale, band_style) {
+            scene.add(Box::new(band));
+        }
+        scene.add(Box::new(fit.line(&x_scale, &y_scale, trend_style)));
     }
 
     let axis_style = Rc::new(StyleBuilder::default()
-        .stroke(Some(BLACK))
+        .stroke(Some(BLACK.into()))
         .stroke_width(1.0)
         .build()?);
 
@@ -11,7 +11,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     let blue = Color{r:0, g:0, b:255, a:0.5};
     let style = Rc::new(
         StyleBuilder::default()
-            .fill(Some(blue))
+            .fill(Some(blue.into()))
             .build()?
     );
     let canvas = Canvas {
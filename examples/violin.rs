@@ -0,0 +1,45 @@
+use std::error::Error;
+use std::rc::Rc;
+
+use helio::core::{Canvas, StyleBuilder, Scene};
+use helio::chart::ViolinBuilder;
+use helio::svg_backend;
+use helio::color::{WHITE, PALETTE_CATEGORY10};
+use helio::data::{read_file, ReadOptions};
+
+fn main() -> Result<(), Box<dyn Error>> {
+
+    let width = 800;
+    let height = 400;
+    let margin = (50.0, 70.0, 50.0, 70.0); // Top, Right, Bottom, Left
+
+    let table = read_file("examples/data/cars.csv", &ReadOptions::default())?
+        .sort_by("cylinders", true)?;
+    let category: Vec<String> = table.column("cylinders")?.to_strings();
+    let value: Vec<f64> = table.column("economy (mpg)")?.to_f64();
+
+    let canvas = Canvas {
+        width,
+        height,
+        background: WHITE
+    };
+    let mut scene = Scene::new(canvas);
+
+    let violin = ViolinBuilder::default()
+        .category(category)
+        .value(value)
+        .x_range([0.0 + margin.3, width as f64 - margin.1])
+        .y_range([height as f64 - margin.0, 0.0 + margin.2])
+        .build()?;
+    let layout = violin.layout()?;
+
+    for (i, mut area) in layout.areas.into_iter().enumerate() {
+        area.style = Rc::new(StyleBuilder::default()
+            .fill(Some(PALETTE_CATEGORY10[i % PALETTE_CATEGORY10.len()].into()))
+            .build()?);
+        scene.add(Box::new(area));
+    }
+
+    svg_backend::save("chart.svg", &scene);
+    Ok(())
+}
@@ -0,0 +1,298 @@
+//!
+//! Renders charts from the command line, like
+//!
+//!     helio examples/data/stocks.csv -m line -x date -y price -c symbol --date-format "%b %e %Y" -o stocks.svg
+//!     cat spec.json | helio --spec - --theme dark > chart.svg
+//!
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::process;
+
+use helio::chart::{Chart, ChartBuilder, Encoding, FieldDef, FieldType, MarkType, Theme};
+use helio::core::Scene;
+use helio::data::{read_csv, ReadOptions, ReadOptionsBuilder, Table};
+use helio::svg_backend::{self, SVGContext};
+
+const USAGE: &str = "\
+Usage: helio [OPTIONS] [DATA]
+
+Renders a chart of DATA, a CSV file or - for stdin (the default)
+
+Chart:
+  -m, --mark <MARK>         point, circle, square, bar, line or area, scatter is circle [default: point]
+  -x <FIELD>                Field on the horizontal axis
+  -y <FIELD>                Field on the vertical axis
+  -c, --color <FIELD>
+  -s, --size <FIELD>
+      --shape <FIELD>
+      --opacity <FIELD>
+  -t, --tooltip <FIELD>     Can be repeated
+      --spec <FILE>         Vega-Lite JSON spec instead of the flags above, - for stdin.
+                            Its transforms and encoding apply to DATA when both are given,
+                            then the spec needs no data
+
+  Fields are column names, optionally typed like price:Q, date:T, symbol:N or year:O.
+  Otherwise numbers are quantitative, dates temporal and the rest nominal
+
+Data:
+      --date-format <FORMAT>  Format of the dates like \"%b %e %Y\", can be repeated
+      --delimiter <CHAR>      [default: ,]
+
+Output:
+  -o, --output <FILE>       - for stdout [default: -]
+  -f, --format <FORMAT>     svg or png [default: the extension of the output, else svg]
+  -W, --width <PIXELS>
+  -H, --height <PIXELS>
+      --theme <THEME>       light or dark
+      --title <TEXT>
+  -h, --help
+";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format { Svg, Png }
+
+#[derive(Debug, Default, PartialEq)]
+struct Args {
+    data: Option<String>,
+    spec: Option<String>,
+    mark: Option<MarkType>,
+    x: Option<String>,
+    y: Option<String>,
+    color: Option<String>,
+    size: Option<String>,
+    shape: Option<String>,
+    opacity: Option<String>,
+    tooltip: Vec<String>,
+    date_formats: Vec<String>,
+    delimiter: Option<u8>,
+    output: Option<String>,
+    format: Option<Format>,
+    width: Option<u32>,
+    height: Option<u32>,
+    theme: Option<Theme>,
+    title: Option<String>,
+    help: bool,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            parsed.help = true;
+            continue;
+        }
+        if !arg.starts_with('-') || arg == "-" {
+            if parsed.data.replace(arg).is_some() { return Err("only one DATA can be given".to_string()) };
+            continue;
+        }
+        // Both --flag value and --flag=value
+        let (flag, inline) = match arg.find('=') {
+            Some(i) if arg.starts_with("--") => (arg[..i].to_string(), Some(arg[i + 1..].to_string())),
+            _ => (arg.clone(), None),
+        };
+        let value = match inline.or_else(|| args.next()) {
+            Some(value) => value,
+            None => return Err(format!("{} needs a value", flag)),
+        };
+        let number = |value: &str| value.parse::<u32>().map_err(|_| format!("{} should be a number of pixels", flag));
+        match flag.as_str() {
+            "-m" | "--mark" => parsed.mark = Some(if value == "scatter" { MarkType::Circle } else { value.parse()? }),
+            "-x" => parsed.x = Some(value),
+            "-y" => parsed.y = Some(value),
+            "-c" | "--color" => parsed.color = Some(value),
+            "-s" | "--size" => parsed.size = Some(value),
+            "--shape" => parsed.shape = Some(value),
+            "--opacity" => parsed.opacity = Some(value),
+            "-t" | "--tooltip" => parsed.tooltip.push(value),
+            "--spec" => parsed.spec = Some(value),
+            "--date-format" => parsed.date_formats.push(value),
+            "--delimiter" => match value.as_bytes() {
+                [delimiter] => parsed.delimiter = Some(*delimiter),
+                _ if value == "\\t" => parsed.delimiter = Some(b'\t'),
+                _ => return Err(format!("'{}' should be a single character", value)),
+            },
+            "-o" | "--output" => parsed.output = Some(value),
+            "-f" | "--format" => parsed.format = Some(format(&value)?),
+            "-W" | "--width" => parsed.width = Some(number(&value)?),
+            "-H" | "--height" => parsed.height = Some(number(&value)?),
+            "--theme" => parsed.theme = Some(match value.as_str() {
+                "light" => Theme::light(),
+                "dark" => Theme::dark(),
+                _ => return Err(format!("'{}' should be light or dark", value)),
+            }),
+            "--title" => parsed.title = Some(value),
+            _ => return Err(format!("unknown option {}", flag)),
+        }
+    }
+    if parsed.spec.as_deref() == Some("-") && parsed.data.as_deref() == Some("-") {
+        return Err("--spec and DATA can't both be read from stdin".to_string());
+    }
+    Ok(parsed)
+}
+
+fn format(name: &str) -> Result<Format, String> {
+    match name.to_lowercase().as_str() {
+        "svg" => Ok(Format::Svg),
+        "png" => Ok(Format::Png),
+        _ => Err(format!("'{}' should be svg or png", name)),
+    }
+}
+
+/// A column name with an optional Vega-Lite type shorthand
+fn field(table: &Table, text: &str) -> Result<FieldDef, String> {
+    let typed = text.rfind(':').and_then(|i| {
+        let field_type = match &text[i + 1..] {
+            "Q" | "quantitative" => FieldType::Quantitative,
+            "T" | "temporal" => FieldType::Temporal,
+            "N" | "nominal" => FieldType::Nominal,
+            "O" | "ordinal" => FieldType::Ordinal,
+            _ => return None,
+        };
+        Some(FieldDef::new(&text[..i], field_type))
+    });
+    match typed {
+        Some(def) => Ok(def),
+        None => FieldDef::infer(table, text),
+    }
+}
+
+/// Reads `path`, stdin when it is -
+fn read_input(path: &str) -> Result<Box<dyn Read>, String> {
+    if path == "-" { return Ok(Box::new(io::stdin())) };
+    File::open(path).map(|f| Box::new(f) as Box<dyn Read>).map_err(|e| format!("{}: {}", path, e))
+}
+
+fn read_data(args: &Args, path: &str) -> Result<Table, String> {
+    let mut builder = ReadOptionsBuilder::default();
+    builder.date_formats(args.date_formats.clone());
+    if let Some(delimiter) = args.delimiter { builder.delimiter(delimiter); };
+    let options: ReadOptions = builder.build()?;
+    read_csv(read_input(path)?, &options).map_err(|e| e.to_string())
+}
+
+fn chart(args: &Args) -> Result<Chart, String> {
+    let mut chart = match &args.spec {
+        Some(path) => {
+            let mut json = String::new();
+            read_input(path)?.read_to_string(&mut json).map_err(|e| format!("{}: {}", path, e))?;
+            match &args.data {
+                Some(data) => Chart::from_vega_lite_with_data(&json, read_data(args, data)?),
+                None => Chart::from_vega_lite(&json),
+            }.map_err(|e| e.to_string())?
+        },
+        None => {
+            let table = read_data(args, args.data.as_deref().unwrap_or("-"))?;
+            let def = |text: &Option<String>| text.as_ref().map(|t| field(&table, t)).transpose();
+            let encoding = Encoding {
+                x: def(&args.x)?,
+                y: def(&args.y)?,
+                color: def(&args.color)?,
+                size: def(&args.size)?,
+                shape: def(&args.shape)?,
+                opacity: def(&args.opacity)?,
+                tooltip: args.tooltip.iter().map(|t| field(&table, t)).collect::<Result<_, _>>()?,
+            };
+            ChartBuilder::default()
+                .data(table)
+                .mark(args.mark.unwrap_or(MarkType::Point))
+                .encoding(encoding)
+                .build()?
+        },
+    };
+    if let Some(width) = args.width { chart.width = width };
+    if let Some(height) = args.height { chart.height = height };
+    if let Some(theme) = &args.theme { chart.theme = theme.clone() };
+    if let Some(title) = &args.title { chart.title = Some(title.clone()) };
+    Ok(chart)
+}
+
+#[cfg(feature = "png")]
+fn png(scene: &Scene<SVGContext>) -> Result<Vec<u8>, String> {
+    svg_backend::to_png(scene, 1.0)
+}
+
+#[cfg(not(feature = "png"))]
+fn png(_scene: &Scene<SVGContext>) -> Result<Vec<u8>, String> {
+    Err("helio was built without PNG support, rebuild it with --features png".to_string())
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let args = parse_args(std::env::args().skip(1))?;
+    if args.help {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+    let output = args.output.as_deref().unwrap_or("-");
+    let format = match args.format {
+        Some(format) => format,
+        None => output.rsplit('.').next().filter(|_| output.contains('.')).and_then(|e| self::format(e).ok()).unwrap_or(Format::Svg),
+    };
+
+    let scene: Scene<SVGContext> = chart(&args)?.scene()?;
+    // Encoded before creating the output, so a failure leaves no empty file behind
+    let png = if format == Format::Png { Some(png(&scene)?) } else { None };
+    let target: Box<dyn Write> = if output == "-" {
+        Box::new(io::stdout())
+    } else {
+        Box::new(File::create(output).map_err(|e| format!("{}: {}", output, e))?)
+    };
+    let mut target = BufWriter::new(target);
+    match png {
+        Some(bytes) => target.write_all(&bytes),
+        None => svg_backend::write(&mut target, &scene),
+    }
+    .and_then(|_| target.flush())
+    .map_err(|e| format!("{}: {}", output, e))?;
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("helio: {}\n\nTry helio --help", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(text: &str) -> Result<Args, String> {
+        parse_args(text.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn args_work() {
+        let parsed = args("data.csv -m scatter -x a:O --color=b -t c -t d -W 300 --theme dark -o out.png").unwrap();
+        assert_eq!(parsed.data.as_deref(), Some("data.csv"));
+        assert_eq!(parsed.mark, Some(MarkType::Circle));
+        assert_eq!(parsed.x.as_deref(), Some("a:O"));
+        assert_eq!(parsed.color.as_deref(), Some("b"));
+        assert_eq!(parsed.tooltip, ["c", "d"]);
+        assert_eq!(parsed.width, Some(300));
+        assert_eq!(parsed.theme, Some(Theme::dark()));
+
+        assert!(args("-m pie").is_err());
+        assert!(args("-x").is_err());
+        assert!(args("--unknown 1").is_err());
+        assert!(args("a.csv b.csv").is_err());
+        assert!(args("--spec - -").is_err());
+        assert!(args("--spec - data.csv").is_ok());
+    }
+
+    #[test]
+    fn chart_works() {
+        let mut parsed = args("examples/data/stocks.csv -m line -x date -y price -c symbol:O -H 300 --title Stocks").unwrap();
+        parsed.date_formats.push("%b %e %Y".to_string());
+        let chart = chart(&parsed).unwrap();
+        assert_eq!(chart.mark, MarkType::Line);
+        assert_eq!(chart.encoding.x, Some(FieldDef::temporal("date")));
+        assert_eq!(chart.encoding.y, Some(FieldDef::quantitative("price")));
+        assert_eq!(chart.encoding.color, Some(FieldDef::ordinal("symbol")));
+        assert_eq!((chart.width, chart.height), (800, 300));
+        assert_eq!(chart.title.as_deref(), Some("Stocks"));
+        assert!(chart.scene::<SVGContext>().is_ok());
+    }
+}
@@ -0,0 +1,151 @@
+use std::rc::Rc;
+
+use chrono::{TimeZone, Utc};
+
+use crate::chart::label::{Bounds, thin};
+use crate::core::Style;
+use crate::font::{Font, measure_text};
+use crate::mark::{Segment, Text, TextAlign, TextBaseline};
+use crate::scale::DateTime;
+use crate::scale::time_ticks::TimeUnit;
+
+/// Side of the plot where the axis is drawn, the ticks point outwards
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AxisOrient { Top, Right, Bottom, Left }
+
+/// Line of an axis with its ticks and labels, all in pixels
+#[derive(Debug, Clone, PartialEq)]
+pub struct Axis {
+    pub orient: AxisOrient,
+    /// y of horizontal axes and x of vertical ones
+    pub offset: f64,
+    /// Extent of the line along the axis
+    pub range: [f64; 2],
+    /// Position and label of each tick
+    pub ticks: Vec<(f64, String)>,
+    pub tick_size: f64,
+    /// Font of the labels
+    pub font: Font,
+    /// Keeps only every other label, every third and so on when they would overlap. Off by default
+    pub thin_labels: bool,
+}
+
+/// Text of a numeric tick with the decimals needed for ticks `step` apart
+pub fn format_number(value: f64, step: f64) -> String {
+    let decimals = if step > 0.0 && step.is_finite() { (-step.log10().floor()).max(0.0) as usize } else { 0 };
+    let text = format!("{:.*}", decimals, value);
+    // Avoids "-0"
+    if text.trim_start_matches('-').chars().all(|c| c == '0' || c == '.') { text.trim_start_matches('-').to_string() } else { text }
+}
+
+/// Text of a date tick, as precise as ticks one `unit` apart need
+pub fn format_date(value: &DateTime, unit: TimeUnit) -> String {
+    let format = match unit {
+        TimeUnit::Millisecond => "%H:%M:%S%.3f",
+        TimeUnit::Second => "%H:%M:%S",
+        TimeUnit::Minute | TimeUnit::Hour => "%H:%M",
+        TimeUnit::Day | TimeUnit::Week => "%b %d",
+        TimeUnit::Month => "%b %Y",
+        TimeUnit::Year => "%Y",
+    };
+    match Utc.timestamp_millis_opt(value.timestamp_millis()).single() {
+        Some(date) => date.format(format).to_string(),
+        None => String::new(),
+    }
+}
+
+impl Axis {
+    pub fn new(orient: AxisOrient, offset: f64, range: [f64; 2], ticks: Vec<(f64, String)>) -> Axis {
+        Axis { orient, offset, range, ticks, tick_size: 6.0, font: Font::default(), thin_labels: false }
+    }
+
+    /// Distance from the line to the far side of the labels, the room the axis needs in a margin
+    pub fn size(&self) -> f64 {
+        let (_, ascent, descent) = measure_text("", &self.font);
+        let widest = self.ticks.iter().map(|(_, label)| measure_text(label, &self.font).0).fold(0.0, f64::max);
+        let across = match self.orient {
+            AxisOrient::Top | AxisOrient::Bottom => ascent + descent,
+            AxisOrient::Left | AxisOrient::Right => widest,
+        };
+        self.tick_size + 3.0 + across
+    }
+
+    /// The line and the ticks, and the labels filled with the stroke of `style`
+    pub fn marks(&self, style: Rc<Style>) -> (Vec<Segment>, Vec<Text>) {
+        let label_style = Rc::new(Style { fill: style.stroke.clone().or(Style::default().fill), ..Style::default() });
+        let horizontal = self.orient == AxisOrient::Top || self.orient == AxisOrient::Bottom;
+        let sign = if self.orient == AxisOrient::Top || self.orient == AxisOrient::Left { -1.0 } else { 1.0 };
+        let tick_end = self.offset + sign * self.tick_size;
+        let segment = |along: [f64; 2], across: [f64; 2]| if horizontal {
+            Segment { x: along[0], y: across[0], x2: along[1], y2: across[1], style: style.clone() }
+        } else {
+            Segment { x: across[0], y: along[0], x2: across[1], y2: along[1], style: style.clone() }
+        };
+
+        let mut segments = vec![segment(self.range, [self.offset, self.offset])];
+        let mut labels = vec![];
+        for (position, label) in &self.ticks {
+            segments.push(segment([*position, *position], [self.offset, tick_end]));
+            // Labels are placed past the ticks, facing them
+            let (x, y, align, baseline) = match self.orient {
+                AxisOrient::Bottom => (*position, tick_end + 3.0, TextAlign::Center, TextBaseline::Top),
+                AxisOrient::Top => (*position, tick_end - 3.0, TextAlign::Center, TextBaseline::Bottom),
+                AxisOrient::Left => (tick_end - 3.0, *position, TextAlign::End, TextBaseline::Middle),
+                AxisOrient::Right => (tick_end + 3.0, *position, TextAlign::Start, TextBaseline::Middle),
+            };
+            let font = self.font.clone();
+            labels.push(Text { x, y, text: label.clone(), align, baseline, font, style: label_style.clone(), ..Text::default() });
+        }
+        if self.thin_labels {
+            let boxes: Vec<Bounds> = labels.iter().map(Bounds::of_text).collect();
+            let mut kept = thin(&boxes, 4.0).into_iter();
+            labels.retain(|_| kept.next().unwrap_or(true));
+        }
+        (segments, labels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_works() {
+        assert_eq!(format_number(20.0, 10.0), "20");
+        assert_eq!(format_number(0.30000000000000004, 0.1), "0.3");
+        assert_eq!(format_number(0.25, 0.05), "0.25");
+        assert_eq!(format_number(-0.0001, 0.1), "0.0");
+
+        let date: DateTime = Utc.with_ymd_and_hms(2011, 3, 5, 14, 30, 0).unwrap().into();
+        assert_eq!(format_date(&date, TimeUnit::Year), "2011");
+        assert_eq!(format_date(&date, TimeUnit::Month), "Mar 2011");
+        assert_eq!(format_date(&date, TimeUnit::Day), "Mar 05");
+        assert_eq!(format_date(&date, TimeUnit::Hour), "14:30");
+    }
+
+    #[test]
+    fn marks_work() {
+        let axis = Axis::new(AxisOrient::Left, 50.0, [300.0, 0.0], vec![(300.0, "0".into()), (0.0, "10".into())]);
+        let (segments, labels) = axis.marks(Rc::new(Style::default()));
+        assert_eq!(segments.len(), 3);
+        assert_eq!((segments[1].x, segments[1].x2, segments[1].y), (50.0, 44.0, 300.0));
+        assert_eq!(labels[1].text, "10");
+        assert_eq!(labels[1].x, 41.0);
+        // Two digits of 10px Helvetica past the tick and its padding
+        assert_eq!(labels[1].baseline, TextBaseline::Middle);
+        assert!((axis.size() - (6.0 + 3.0 + 11.12)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn thin_works() {
+        let ticks: Vec<(f64, String)> = (0..10).map(|i| (i as f64 * 20.0, format!("{}", 1000 + i))).collect();
+        let mut axis = Axis::new(AxisOrient::Bottom, 100.0, [0.0, 180.0], ticks);
+        assert_eq!(axis.marks(Rc::new(Style::default())).1.len(), 10);
+
+        // Four digits are 22px wide
+        axis.thin_labels = true;
+        let (segments, labels) = axis.marks(Rc::new(Style::default()));
+        assert_eq!(segments.len(), 11);
+        assert_eq!(labels.iter().map(|l| l.text.as_str()).collect::<Vec<_>>(), ["1000", "1002", "1004", "1006", "1008"]);
+    }
+}
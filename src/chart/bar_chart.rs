@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::chart::{Axis, AxisOrient, Legend, MAX_TICKS, format_number};
+use crate::color::{Color, BLACK, WHITE, PALETTE_CATEGORY10};
+use crate::core::{Canvas, Glyph, Scene, Style};
+use crate::mark::{Rect, Segment, Text, TextAlign, TextBaseline};
+use crate::scale::{BandScale, BandScaleBuilder, LinearScale, Scale, ticks};
+use crate::transform::{StackOffset, StackOrder, stack_series};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Orientation {
+    /// Categories along x and values along y
+    Vertical,
+    /// Categories along y and values along x
+    Horizontal,
+}
+
+/// How the bars of the series of a category are laid out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BarLayout {
+    /// Side by side
+    Grouped,
+    /// One over another, negative values below zero
+    Stacked,
+}
+
+/// Order of the categories
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BarSort {
+    /// First-seen order
+    Input,
+    /// By the total of the category
+    Ascending,
+    Descending,
+    /// By the name of the category
+    Category,
+}
+
+/// Bars of a value for each category, optionally split in series
+#[derive(Debug, Builder)]
+#[builder(setter(into), build_fn(validate = "Self::validate"))]
+pub struct BarChart {
+    pub category: Vec<String>,
+    pub value: Vec<f64>,
+    /// Key of the series of each row, values with the same category and series are summed
+    #[builder(default)]
+    pub series: Option<Vec<String>>,
+    #[builder(default = "BarLayout::Grouped")]
+    pub layout: BarLayout,
+    #[builder(default = "Orientation::Vertical")]
+    pub orientation: Orientation,
+    #[builder(default = "BarSort::Input")]
+    pub sort: BarSort,
+    /// Writes the value of each bar next to it
+    #[builder(default)]
+    pub labels: bool,
+    #[builder(default = "800")]
+    pub width: u32,
+    #[builder(default = "400")]
+    pub height: u32,
+    /// Top, Right, Bottom, Left
+    #[builder(default = "(30.0, 120.0, 40.0, 60.0)")]
+    pub margin: (f64, f64, f64, f64),
+    /// Inner and outer padding of the category bands
+    #[builder(default = "0.1")]
+    pub padding: f64,
+    /// Colors of the series, cycled. Can't be empty
+    #[builder(default = "PALETTE_CATEGORY10.to_vec()")]
+    pub colors: Vec<Color>,
+    #[builder(default = "WHITE")]
+    pub background: Color,
+}
+
+/// Category and series keys in first-seen order, and the sum of the values of each series (rows) and category (columns)
+fn pivot(category: &[String], series: &[String], value: &[f64]) -> (Vec<String>, Vec<String>, Vec<Vec<f64>>) {
+    let mut categories: Vec<String> = vec![];
+    let mut keys: Vec<String> = vec![];
+    let mut category_index: HashMap<&str, usize> = HashMap::new();
+    let mut key_index: HashMap<&str, usize> = HashMap::new();
+    let mut cells = vec![];
+    for ((c, s), &v) in category.iter().zip(series).zip(value) {
+        let j = *category_index.entry(c).or_insert_with(|| { categories.push(c.clone()); categories.len() - 1 });
+        let i = *key_index.entry(s).or_insert_with(|| { keys.push(s.clone()); keys.len() - 1 });
+        cells.push((i, j, v));
+    }
+    let mut matrix = vec![vec![f64::NAN; categories.len()]; keys.len()];
+    for (i, j, v) in cells {
+        if v.is_nan() { continue };
+        matrix[i][j] = if matrix[i][j].is_nan() { v } else { matrix[i][j] + v };
+    }
+    (categories, keys, matrix)
+}
+
+impl BarChartBuilder {
+    fn validate(&self) -> Result<(), String> {
+        match &self.colors {
+            Some(colors) if colors.is_empty() => Err("colors can't be empty".into()),
+            _ => Ok(())
+        }
+    }
+}
+
+impl BarChart {
+    /// Scene with the bars, the axes, the labels and a legend when there are series
+    pub fn scene<T: 'static>(&self) -> Result<Scene<T>, String> where
+        Rect: Glyph<Context = T>,
+        Segment: Glyph<Context = T>,
+        Text: Glyph<Context = T>
+    {
+        if self.colors.is_empty() { return Err("colors can't be empty".into()) };
+        let no_series = vec![String::new(); self.category.len()];
+        let series = self.series.as_ref().unwrap_or(&no_series);
+        let (mut categories, keys, mut matrix) = pivot(&self.category, series, &self.value);
+
+        // Sorts the columns of the matrix along with the categories
+        let totals: Vec<f64> = (0..categories.len())
+            .map(|j| matrix.iter().map(|s| s[j]).filter(|v| !v.is_nan()).sum())
+            .collect();
+        let mut order: Vec<usize> = (0..categories.len()).collect();
+        match self.sort {
+            BarSort::Input => {},
+            BarSort::Ascending => order.sort_by(|&a, &b| totals[a].total_cmp(&totals[b])),
+            BarSort::Descending => order.sort_by(|&a, &b| totals[b].total_cmp(&totals[a])),
+            BarSort::Category => order.sort_by(|&a, &b| categories[a].cmp(&categories[b])),
+        }
+        categories = order.iter().map(|&j| categories[j].clone()).collect();
+        matrix = matrix.iter().map(|s| order.iter().map(|&j| s[j]).collect()).collect();
+
+        // [v0, v1] of each bar
+        let intervals: Vec<Vec<[f64; 2]>> = match self.layout {
+            BarLayout::Stacked => stack_series(&matrix, StackOrder::Input, StackOffset::Diverging),
+            BarLayout::Grouped => matrix.iter()
+                .map(|s| s.iter().map(|&v| if v.is_nan() { [0.0, 0.0] } else { [0.0, v] }).collect())
+                .collect(),
+        };
+        let (lo, hi) = intervals.iter().flatten()
+            .fold((0.0_f64, 0.0_f64), |(lo, hi), &[v0, v1]| (lo.min(v0).min(v1), hi.max(v0).max(v1)));
+
+        let (width, height, margin) = (self.width as f64, self.height as f64, self.margin);
+        let x_range = [margin.3, width - margin.1];
+        let y_range = [height - margin.2, margin.0];
+        let vertical = self.orientation == Orientation::Vertical;
+        let (band_range, value_range) = if vertical { (x_range, y_range) } else { ([y_range[1], y_range[0]], x_range) };
+
+        let band_scale: BandScale<String> = BandScaleBuilder::default()
+            .domain(categories.clone())
+            .range(band_range)
+            .padding(self.padding)
+            .build()?;
+        // About a tick every 50 pixels
+        let tick_count = ((value_range[1] - value_range[0]).abs() / 50.0).max(2.0).min(MAX_TICKS as f64) as usize;
+        let value_scale = LinearScale { domain: [lo, if hi > lo { hi } else { lo + 1.0 }], range: value_range, clamp: false, round: false }
+            .nice(tick_count);
+        let grouped = self.layout == BarLayout::Grouped && keys.len() > 1;
+        let inner_scale = BandScale::new((0..keys.len()).collect::<Vec<usize>>(), [0.0, band_scale.bandwidth()]);
+        let bar_width = if grouped { inner_scale.bandwidth() } else { band_scale.bandwidth() };
+
+        let mut scene = Scene::new(Canvas { width: self.width, height: self.height, background: self.background });
+        let mut labels = vec![];
+        for (i, series) in intervals.iter().enumerate() {
+            let style = Rc::new(Style { fill: Some(self.colors[i % self.colors.len()].into()), stroke: None, stroke_width: 0.0, ..Style::default() });
+            for (j, &[v0, v1]) in series.iter().enumerate() {
+                if matrix[i][j].is_nan() { continue };
+                let band = band_scale.call_one(&categories[j]) + if grouped { inner_scale.call_one(&i) } else { 0.0 };
+                let (p0, p1) = (value_scale.call_one(&v0), value_scale.call_one(&v1));
+                let (start, length) = (p0.min(p1), (p1 - p0).abs());
+                let rect = if vertical {
+                    Rect { x: band, y: start, width: bar_width, height: length, style: style.clone() }
+                } else {
+                    Rect { x: start, y: band, width: length, height: bar_width, style: style.clone() }
+                };
+                scene.add(Box::new(rect));
+
+                if self.labels {
+                    let text = format_number(matrix[i][j], ticks::tick_step(lo, hi, tick_count).abs() / 10.0);
+                    let center = band + bar_width / 2.0;
+                    // Stacked labels go inside their segment, the others past the end of the bar
+                    let (along, inside) = if self.layout == BarLayout::Stacked { ((p0 + p1) / 2.0, true) } else { (p1, false) };
+                    let sign = if (p1 >= p0) == vertical { 1.0 } else { -1.0 };
+                    let style = Rc::new(Style::default());
+                    let label = if vertical {
+                        // Outside labels face the end of the bar
+                        let (y, baseline) = if inside { (along, TextBaseline::Middle) }
+                            else if sign > 0.0 { (along + 4.0, TextBaseline::Top) }
+                            else { (along - 4.0, TextBaseline::Bottom) };
+                        Text { x: center, y, text, align: TextAlign::Center, baseline, style, ..Text::default() }
+                    } else if inside {
+                        Text { x: along, y: center, text, align: TextAlign::Center, baseline: TextBaseline::Middle, style, ..Text::default() }
+                    } else {
+                        let align = if sign > 0.0 { TextAlign::End } else { TextAlign::Start };
+                        Text { x: along - sign * 4.0, y: center, text, align, baseline: TextBaseline::Middle, style, ..Text::default() }
+                    };
+                    labels.push(label);
+                }
+            }
+        }
+        for label in labels {
+            scene.add(Box::new(label));
+        }
+
+        let axis_style = Rc::new(Style { fill: None, stroke: Some(BLACK.into()), stroke_width: 1.0, ..Style::default() });
+        let value_ticks: Vec<(f64, String)> = {
+            let [d0, d1] = value_scale.domain;
+            let step = ticks::tick_step(d0, d1, tick_count).abs();
+            value_scale.ticks(tick_count).into_iter().map(|v| (value_scale.call_one(&v), format_number(v, step))).collect()
+        };
+        let band_ticks: Vec<(f64, String)> = categories.iter()
+            .map(|c| (band_scale.call_one(c) + band_scale.bandwidth() / 2.0, c.clone()))
+            .collect();
+        let zero = value_scale.call_one(&0.0);
+        let (category_axis, value_axis) = if vertical {
+            (Axis::new(AxisOrient::Bottom, zero, x_range, band_ticks), Axis::new(AxisOrient::Left, margin.3, y_range, value_ticks))
+        } else {
+            (Axis::new(AxisOrient::Left, zero, y_range, band_ticks), Axis::new(AxisOrient::Bottom, height - margin.2, x_range, value_ticks))
+        };
+        for axis in &[category_axis, value_axis] {
+            let (segments, texts) = axis.marks(axis_style.clone());
+            segments.into_iter().for_each(|s| scene.add(Box::new(s)));
+            texts.into_iter().for_each(|t| scene.add(Box::new(t)));
+        }
+
+        if self.series.is_some() {
+            let items = keys.iter().enumerate().map(|(i, k)| (k.clone(), self.colors[i % self.colors.len()])).collect();
+            let (swatches, texts) = Legend::new(width - margin.1 + 20.0, margin.0, items).marks();
+            swatches.into_iter().for_each(|s| scene.add(Box::new(s)));
+            texts.into_iter().for_each(|t| scene.add(Box::new(t)));
+        }
+        Ok(scene)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg_backend::SVGContext;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn pivot_works() {
+        let (categories, keys, matrix) = pivot(&strings(&["a", "b", "a", "a"]), &strings(&["x", "x", "y", "x"]), &[1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(categories, ["a", "b"]);
+        assert_eq!(keys, ["x", "y"]);
+        assert_eq!(matrix[0], [5.0, 2.0]);
+        assert_eq!(matrix[1][0], 3.0);
+        assert!(matrix[1][1].is_nan());
+    }
+
+    #[test]
+    fn scene_works() {
+        let chart = BarChartBuilder::default()
+            .category(strings(&["a", "b", "c"]))
+            .value(vec![3.0, 10.0, 5.0])
+            .sort(BarSort::Descending)
+            .width(400_u32)
+            .height(300_u32)
+            .margin((0.0, 0.0, 100.0, 100.0))
+            .padding(0.0)
+            .labels(true)
+            .build().unwrap();
+        let scene: Scene<SVGContext> = chart.scene().unwrap();
+        // 3 bars, 3 labels, 2 axis lines with 3 and 6 ticks and their labels
+        assert_eq!(scene.glyphs.len(), 3 + 3 + (1 + 3 + 3) + (1 + 6 + 6));
+
+        // The ticks are capped on huge charts
+        let huge = BarChart { height: u32::MAX, ..chart };
+        let scene: Scene<SVGContext> = huge.scene().unwrap();
+        assert!(scene.glyphs.len() <= 3 + 3 + (1 + 3 + 3) + (1 + 2 * (MAX_TICKS + 1)));
+        let chart = BarChart { height: 300, ..huge };
+
+        // No colors to cycle
+        let no_colors = BarChart { colors: vec![], ..chart };
+        assert!(no_colors.scene::<SVGContext>().is_err());
+        assert!(BarChartBuilder::default().category(strings(&["a"])).value(vec![1.0]).colors(vec![]).build().is_err());
+
+        let stacked = BarChartBuilder::default()
+            .category(strings(&["a", "a", "b"]))
+            .value(vec![1.0, 2.0, 3.0])
+            .series(Some(strings(&["x", "y", "x"])))
+            .layout(BarLayout::Stacked)
+            .orientation(Orientation::Horizontal)
+            .build().unwrap();
+        let scene: Scene<SVGContext> = stacked.scene().unwrap();
+        // 3 bars, 2 axes (0 to 3 every 0.2 along 620 pixels) and 2 legend items
+        assert_eq!(scene.glyphs.len(), 3 + (1 + 2 + 2) + (1 + 16 + 16) + 4);
+    }
+}
@@ -0,0 +1,86 @@
+use std::rc::Rc;
+
+use crate::core::Style;
+use crate::mark::Rect;
+use crate::scale::{LinearScale, Scale};
+use crate::transform::{Bin, BinParams, bin};
+
+/// Distribution of a numeric variable as bars over bins
+#[derive(Debug, Builder)]
+#[builder(setter(into))]
+pub struct Histogram {
+    pub data: Vec<f64>,
+    #[builder(default)]
+    pub bins: BinParams,
+    /// Pixels where the bins are laid out
+    pub x_range: [f64; 2],
+    /// Pixels for zero and for the highest count, usually from bottom to top
+    pub y_range: [f64; 2],
+    /// Pixels between adjacent bars
+    #[builder(default = "1.0")]
+    pub spacing: f64,
+    #[builder(default)]
+    pub style: Rc<Style>,
+}
+
+/// Everything needed to draw a histogram and its axes
+#[derive(Debug)]
+pub struct HistogramLayout {
+    pub bins: Vec<Bin>,
+    pub x_scale: LinearScale,
+    pub y_scale: LinearScale,
+    pub rects: Vec<Rect>,
+}
+
+impl Histogram {
+    pub fn layout(&self) -> HistogramLayout {
+        let bins = bin(&self.data, &self.bins);
+        let x_domain = match (bins.first(), bins.last()) {
+            (Some(first), Some(last)) => [first.start, last.end],
+            _ => [0.0, 1.0],
+        };
+        let max_count = bins.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+        let x_scale = LinearScale { domain: x_domain, range: self.x_range, clamp: false, round: false };
+        let y_scale = LinearScale { domain: [0.0, max_count as f64], range: self.y_range, clamp: false, round: false };
+
+        let base = y_scale.call_one(&0.0);
+        let rects = bins.iter()
+            .map(|b| {
+                let x0 = x_scale.call_one(&b.start);
+                let x1 = x_scale.call_one(&b.end);
+                let top = y_scale.call_one(&(b.count as f64));
+                Rect {
+                    x: x0.min(x1) + self.spacing / 2.0,
+                    y: base.min(top),
+                    width: ((x1 - x0).abs() - self.spacing).max(0.0),
+                    height: (base - top).abs(),
+                    style: self.style.clone(),
+                }
+            })
+            .collect();
+
+        HistogramLayout { bins, x_scale, y_scale, rects }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::{BinParamsBuilder, Thresholds};
+
+    #[test]
+    fn layout_works() {
+        let histogram = HistogramBuilder::default()
+            .data(vec![0.0, 1.0, 1.5, 3.0])
+            .bins(BinParamsBuilder::default().thresholds(Thresholds::Step(1.0)).build().unwrap())
+            .x_range([0.0, 300.0])
+            .y_range([100.0, 0.0])
+            .spacing(0.0)
+            .build().unwrap();
+        let layout = histogram.layout();
+        assert_eq!(layout.bins.len(), 3);
+        assert_eq!(layout.y_scale.domain, [0.0, 2.0]);
+        let rects: Vec<(f64, f64, f64, f64)> = layout.rects.iter().map(|r| (r.x, r.y, r.width, r.height)).collect();
+        assert_eq!(rects, [(0.0, 50.0, 100.0, 50.0), (100.0, 0.0, 100.0, 100.0), (200.0, 50.0, 100.0, 50.0)]);
+    }
+}
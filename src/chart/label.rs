@@ -0,0 +1,410 @@
+//!
+//! Placement of labels next to the points they describe, without overlaps.
+//! Each label is tried at a list of candidate positions around its anchor, either greedily in order
+//! or by simulated annealing. Labels that can't be placed anywhere are hidden.
+//!
+
+use std::rc::Rc;
+
+use crate::core::Style;
+use crate::font::measure_text;
+use crate::mark::{Segment, Text, TextAlign, TextBaseline};
+
+/// Axis aligned box, from its top left corner
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Bounds {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Bounds {
+        Bounds { x, y, width, height }
+    }
+
+    /// Box around the lines of `text` as measured with its font, ignoring its rotation
+    pub fn of_text(text: &Text) -> Bounds {
+        let lines = text.lines();
+        let width = lines.iter().map(|line| measure_text(line, &text.font).0).fold(0.0, f64::max);
+        let (_, ascent, descent) = measure_text("", &text.font);
+        let height = ascent + descent + (lines.len() - 1) as f64 * text.line_spacing();
+        let x = text.x + text.dx - match text.align {
+            TextAlign::Start => 0.0,
+            TextAlign::Center => width / 2.0,
+            TextAlign::End => width,
+        };
+        let y = text.y + text.dy - match text.baseline {
+            TextBaseline::Top | TextBaseline::Hanging => 0.0,
+            TextBaseline::Alphabetic => ascent,
+            TextBaseline::Middle => height / 2.0,
+            TextBaseline::Bottom => height,
+        };
+        Bounds { x, y, width, height }
+    }
+
+    /// Grown by `padding` on every side
+    pub fn padded(&self, padding: f64) -> Bounds {
+        Bounds { x: self.x - padding, y: self.y - padding, width: self.width + 2.0 * padding, height: self.height + 2.0 * padding }
+    }
+
+    /// Area shared with `other`
+    pub fn overlap(&self, other: &Bounds) -> f64 {
+        let width = (self.x + self.width).min(other.x + other.width) - self.x.max(other.x);
+        let height = (self.y + self.height).min(other.y + other.height) - self.y.max(other.y);
+        width.max(0.0) * height.max(0.0)
+    }
+
+    pub fn contains(&self, other: &Bounds) -> bool {
+        other.x >= self.x && other.y >= self.y && other.x + other.width <= self.x + self.width && other.y + other.height <= self.y + self.height
+    }
+
+    /// Area outside of `other`
+    pub fn outside(&self, other: &Bounds) -> f64 {
+        if other.contains(self) { 0.0 } else { self.width * self.height - self.overlap(other) }
+    }
+
+    /// Point of the box closest to (x, y)
+    pub fn closest(&self, x: f64, y: f64) -> (f64, f64) {
+        (x.max(self.x).min(self.x + self.width), y.max(self.y).min(self.y + self.height))
+    }
+}
+
+/// A position of a label relative to its anchor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candidate {
+    pub dx: f64,
+    pub dy: f64,
+    pub align: TextAlign,
+    pub baseline: TextBaseline,
+}
+
+impl Candidate {
+    /// The eight positions `distance` away from the anchor, right and the right corners first, then above and below
+    pub fn around(distance: f64) -> Vec<Candidate> {
+        let d = distance / 2f64.sqrt();
+        let candidate = |dx: f64, dy: f64, align: TextAlign, baseline: TextBaseline| Candidate { dx, dy, align, baseline };
+        vec![
+            candidate(distance, 0.0, TextAlign::Start, TextBaseline::Middle),
+            candidate(d, -d, TextAlign::Start, TextBaseline::Bottom),
+            candidate(d, d, TextAlign::Start, TextBaseline::Top),
+            candidate(0.0, -distance, TextAlign::Center, TextBaseline::Bottom),
+            candidate(0.0, distance, TextAlign::Center, TextBaseline::Top),
+            candidate(-distance, 0.0, TextAlign::End, TextBaseline::Middle),
+            candidate(-d, -d, TextAlign::End, TextBaseline::Bottom),
+            candidate(-d, d, TextAlign::End, TextBaseline::Top),
+        ]
+    }
+
+    /// Positions to the right of the anchor, then shifted up and down by up to `count` times `step`, like for the ends of lines
+    pub fn right(distance: f64, step: f64, count: usize) -> Vec<Candidate> {
+        (0..=2 * count as i64)
+            .map(|i| if i % 2 == 0 { i / 2 } else { -(i + 1) / 2 })
+            .map(|i| Candidate { dx: distance, dy: i as f64 * step, align: TextAlign::Start, baseline: TextBaseline::Middle })
+            .collect()
+    }
+
+    fn apply(&self, text: &Text) -> Text {
+        Text { x: text.x + self.dx, y: text.y + self.dy, align: self.align, baseline: self.baseline, ..text.clone() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Placement {
+    /// Each label in order takes its first free candidate
+    Greedy,
+    /// Starts from the greedy placement and moves random labels, accepting worse placements less often as it cools down.
+    /// Slower but it finds room for more labels when they are crowded
+    Annealing { iterations: usize, seed: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Builder)]
+#[builder(setter(into))]
+pub struct LabelLayout {
+    /// Positions tried for every label, the preferred first
+    #[builder(default = "Candidate::around(4.0)")]
+    pub candidates: Vec<Candidate>,
+    /// Area the labels must stay in
+    #[builder(default)]
+    pub bounds: Option<Bounds>,
+    /// Boxes the labels must not cover, like the marks they describe
+    #[builder(default)]
+    pub obstacles: Vec<Bounds>,
+    /// Free space kept around each label
+    #[builder(default = "1.0")]
+    pub padding: f64,
+    #[builder(default = "Placement::Greedy")]
+    pub placement: Placement,
+    /// Labels placed farther than this from their anchor are joined to it by a line
+    #[builder(default)]
+    pub leader_distance: Option<f64>,
+    /// Labels that can't be placed without overlaps are hidden, otherwise they take their first candidate
+    #[builder(default = "true")]
+    pub hide_overlapping: bool,
+}
+
+impl Default for LabelLayout {
+    fn default() -> LabelLayout {
+        LabelLayoutBuilder::default().build().unwrap()
+    }
+}
+
+/// Candidate of every label, None when it is hidden
+type Assignment = Vec<Option<usize>>;
+
+impl LabelLayout {
+    /// Each label moved from its anchor, the (x, y) of the text, to the chosen candidate. None for the hidden ones.
+    /// Earlier labels have priority over later ones
+    pub fn place(&self, labels: &[Text]) -> Vec<Option<Text>> {
+        if self.candidates.is_empty() { return vec![None; labels.len()] };
+        // Padded box of every label at every candidate
+        let boxes: Vec<Vec<Bounds>> = labels.iter()
+            .map(|label| self.candidates.iter().map(|c| Bounds::of_text(&c.apply(label)).padded(self.padding)).collect())
+            .collect();
+        let mut assignment = self.greedy(&boxes);
+        if let Placement::Annealing { iterations, seed } = self.placement {
+            self.anneal(&boxes, &mut assignment, iterations, seed);
+            self.remove_overlaps(&boxes, &mut assignment);
+        }
+        labels.iter().zip(assignment)
+            .map(|(label, candidate)| candidate.map(|c| self.candidates[c].apply(label)))
+            .collect()
+    }
+
+    /// The leader lines and the placed labels, like `place` without the hidden ones
+    pub fn marks(&self, labels: &[Text], leader_style: Rc<Style>) -> (Vec<Segment>, Vec<Text>) {
+        let mut leaders = vec![];
+        let mut texts = vec![];
+        for (label, placed) in labels.iter().zip(self.place(labels)) {
+            let placed = match placed { Some(placed) => placed, None => continue };
+            let (dx, dy) = (placed.x - label.x, placed.y - label.y);
+            if self.leader_distance.is_some_and(|distance| dx.hypot(dy) > distance) {
+                let (x2, y2) = Bounds::of_text(&placed).closest(label.x, label.y);
+                leaders.push(Segment { x: label.x, y: label.y, x2, y2, style: leader_style.clone() });
+            }
+            texts.push(placed);
+        }
+        (leaders, texts)
+    }
+
+    /// Whether `b` is out of bounds or over an obstacle
+    fn blocked(&self, b: &Bounds) -> bool {
+        self.bounds.is_some_and(|bounds| !bounds.contains(b)) || self.obstacles.iter().any(|o| b.overlap(o) > 0.0)
+    }
+
+    fn greedy(&self, boxes: &[Vec<Bounds>]) -> Assignment {
+        let mut placed: Vec<Bounds> = vec![];
+        boxes.iter().map(|candidates| {
+            let free = candidates.iter().position(|b| !self.blocked(b) && placed.iter().all(|p| b.overlap(p) == 0.0));
+            let chosen = match free {
+                None if self.hide_overlapping => return None,
+                free => free.unwrap_or(0),
+            };
+            placed.push(candidates[chosen]);
+            Some(chosen)
+        }).collect()
+    }
+
+    /// Cost of label `i` at `candidate` given where the others are. Hiding a label costs its area, and a
+    /// less preferred candidate a little. Any overlap or area out of bounds costs more than hiding the largest label
+    fn cost(&self, boxes: &[Vec<Bounds>], assignment: &[Option<usize>], i: usize, candidate: Option<usize>, conflict: f64) -> f64 {
+        let candidate = match candidate {
+            Some(candidate) => candidate,
+            None => return boxes[i][0].width * boxes[i][0].height,
+        };
+        let b = &boxes[i][candidate];
+        let penalty = |area: f64| if area > 0.0 { conflict + area } else { 0.0 };
+        let out = self.bounds.map_or(0.0, |bounds| penalty(b.outside(&bounds)));
+        let obstacles: f64 = self.obstacles.iter().map(|o| penalty(b.overlap(o))).sum();
+        let labels: f64 = assignment.iter().enumerate()
+            .filter(|&(j, _)| j != i)
+            .filter_map(|(j, c)| c.map(|c| penalty(b.overlap(&boxes[j][c]))))
+            .sum();
+        out + obstacles + labels + candidate as f64
+    }
+
+    /// Keeps the best assignment found, starting from `assignment`
+    fn anneal(&self, boxes: &[Vec<Bounds>], assignment: &mut Assignment, iterations: usize, seed: u64) {
+        if boxes.is_empty() { return };
+        let mut rng = XorShift::new(seed);
+        let choices = self.candidates.len() + self.hide_overlapping as usize;
+        let areas: Vec<f64> = boxes.iter().map(|b| b[0].width * b[0].height).collect();
+        let conflict = 2.0 * areas.iter().cloned().fold(0.0, f64::max);
+        // From about the cost of hiding a label to nothing
+        let start = areas.iter().sum::<f64>() / areas.len() as f64;
+        let mut current = assignment.clone();
+        // Energies relative to the starting one
+        let (mut energy, mut best) = (0.0, 0.0);
+        for step in 0..iterations {
+            let temperature = start * (1.0 - step as f64 / iterations as f64);
+            let i = rng.below(boxes.len());
+            let choice = rng.below(choices);
+            let candidate = if choice < self.candidates.len() { Some(choice) } else { None };
+            let delta = self.cost(boxes, &current, i, candidate, conflict) - self.cost(boxes, &current, i, current[i], conflict);
+            if delta <= 0.0 || rng.next_f64() < (-delta / temperature).exp() {
+                current[i] = candidate;
+                energy += delta;
+                if energy < best - 1e-9 {
+                    best = energy;
+                    assignment.clone_from(&current);
+                }
+            }
+        }
+    }
+
+    /// Hides the labels still overlapping, later labels first
+    fn remove_overlaps(&self, boxes: &[Vec<Bounds>], assignment: &mut Assignment) {
+        if !self.hide_overlapping { return };
+        let mut placed: Vec<Bounds> = vec![];
+        for (candidates, candidate) in boxes.iter().zip(assignment.iter_mut()) {
+            if let Some(c) = *candidate {
+                let b = candidates[c];
+                if self.blocked(&b) || placed.iter().any(|p| b.overlap(p) > 0.0) {
+                    *candidate = None;
+                } else {
+                    placed.push(b);
+                }
+            }
+        }
+    }
+}
+
+/// Labels to keep so that no two of them are closer than `gap`: every one when they fit,
+/// else every second one, every third one and so on. The first label is always kept
+pub fn thin(boxes: &[Bounds], gap: f64) -> Vec<bool> {
+    let step = (1..boxes.len().max(1))
+        .find(|&step| {
+            let kept: Vec<Bounds> = boxes.iter().step_by(step).map(|b| b.padded(gap / 2.0)).collect();
+            kept.windows(2).all(|pair| pair[0].overlap(&pair[1]) == 0.0)
+        })
+        .unwrap_or(boxes.len().max(1));
+    (0..boxes.len()).map(|i| i % step == 0).collect()
+}
+
+/// Small deterministic generator, so that layouts don't change between runs
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> XorShift {
+        // A zero state only yields zeros
+        match seed ^ 0x9e37_79b9_7f4a_7c15 {
+            0 => XorShift(0x9e37_79b9_7f4a_7c15),
+            state => XorShift(state),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label(x: f64, y: f64, text: &str) -> Text {
+        Text { x, y, text: text.to_string(), ..Text::default() }
+    }
+
+    #[test]
+    fn bounds_work() {
+        // 10px Helvetica, "Hi" is 9.44 wide
+        let text = Text { align: TextAlign::End, baseline: TextBaseline::Middle, ..label(100.0, 50.0, "Hi") };
+        let b = Bounds::of_text(&text);
+        assert!((b.x - 90.56).abs() < 1e-9 && (b.width - 9.44).abs() < 1e-9);
+        assert!((b.y + b.height / 2.0 - 50.0).abs() < 1e-9);
+        assert!((b.height - 9.25).abs() < 1e-9);
+
+        let two_lines = Bounds::of_text(&label(0.0, 0.0, "a\nb"));
+        assert!((two_lines.height - (9.25 + 12.0)).abs() < 1e-9);
+        assert!((two_lines.y + 7.18).abs() < 1e-9);
+
+        let a = Bounds::new(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(a.overlap(&Bounds::new(5.0, 5.0, 10.0, 10.0)), 25.0);
+        assert_eq!(a.overlap(&Bounds::new(10.0, 0.0, 10.0, 10.0)), 0.0);
+        assert_eq!(a.outside(&Bounds::new(5.0, 0.0, 10.0, 10.0)), 50.0);
+        assert!(a.contains(&Bounds::new(0.1, 0.2, 9.9, 9.8)) && a.outside(&Bounds::new(-0.1, 0.0, 10.3, 10.7)) == 0.0);
+        assert_eq!(a.closest(20.0, 5.0), (10.0, 5.0));
+    }
+
+    #[test]
+    fn greedy_works() {
+        let labels = vec![label(0.0, 50.0, "first"), label(0.0, 50.0, "second"), label(0.0, 50.0, "third")];
+        let layout = LabelLayoutBuilder::default().candidates(Candidate::right(4.0, 12.0, 1)).build().unwrap();
+        let placed = layout.place(&labels);
+        // On the anchor, then below and above it
+        assert_eq!(placed[0].as_ref().map(|t| (t.x, t.y)), Some((4.0, 50.0)));
+        assert_eq!(placed[1].as_ref().map(|t| t.y), Some(38.0));
+        assert_eq!(placed[2].as_ref().map(|t| t.y), Some(62.0));
+
+        // Only room below
+        let layout = LabelLayout { bounds: Some(Bounds::new(0.0, 40.0, 100.0, 40.0)), ..layout };
+        let placed = layout.place(&labels);
+        assert!(placed[0].is_some() && placed[1].as_ref().map(|t| t.y) == Some(62.0) && placed[2].is_none());
+        let layout = LabelLayout { hide_overlapping: false, ..layout };
+        assert_eq!(layout.place(&labels)[2].as_ref().map(|t| t.y), Some(50.0));
+    }
+
+    #[test]
+    fn annealing_works() {
+        // A grid of points closer than their labels are wide
+        let labels: Vec<Text> = (0..25).map(|i| label((i % 5) as f64 * 14.0, (i / 5) as f64 * 14.0, "abc")).collect();
+        let obstacles: Vec<Bounds> = labels.iter().map(|l| Bounds::new(l.x - 2.0, l.y - 2.0, 4.0, 4.0)).collect();
+        let greedy = LabelLayout { obstacles, ..LabelLayout::default() };
+        let annealing = LabelLayout { placement: Placement::Annealing { iterations: 20000, seed: 1 }, ..greedy.clone() };
+
+        let count = |layout: &LabelLayout| {
+            let placed: Vec<Text> = layout.place(&labels).into_iter().flatten().collect();
+            let boxes: Vec<Bounds> = placed.iter().map(|t| Bounds::of_text(t).padded(1.0)).collect();
+            for (i, a) in boxes.iter().enumerate() {
+                assert!(boxes[i + 1..].iter().all(|b| a.overlap(b) == 0.0));
+                assert!(layout.obstacles.iter().all(|o| a.overlap(o) == 0.0));
+            }
+            placed.len()
+        };
+        let (greedy, annealing) = (count(&greedy), count(&annealing));
+        assert!(greedy < labels.len());
+        assert!(annealing > greedy, "{} labels placed, {} greedily", annealing, greedy);
+    }
+
+    #[test]
+    fn xorshift_works() {
+        // The one seed that cancels the constant
+        let mut rng = XorShift::new(0x9e37_79b9_7f4a_7c15);
+        assert_ne!(rng.next_u64(), 0);
+        assert_ne!(rng.next_u64(), rng.next_u64());
+    }
+
+    #[test]
+    fn leaders_work() {
+        let labels = vec![label(0.0, 0.0, "a"), label(0.0, 0.0, "b")];
+        let candidates = [Candidate::right(4.0, 0.0, 0), Candidate::right(30.0, 0.0, 0)].concat();
+        let layout = LabelLayout { candidates, leader_distance: Some(10.0), ..LabelLayout::default() };
+        let (leaders, texts) = layout.marks(&labels, Rc::new(Style::default()));
+        assert_eq!(texts.len(), 2);
+        assert_eq!(leaders.len(), 1);
+        assert_eq!((leaders[0].x, leaders[0].y, leaders[0].x2, leaders[0].y2), (0.0, 0.0, 30.0, 0.0));
+    }
+
+    #[test]
+    fn thin_works() {
+        let boxes = |width: f64| (0..7).map(|i| Bounds::new(i as f64 * 10.0, 0.0, width, 5.0)).collect::<Vec<_>>();
+        assert!(thin(&boxes(8.0), 1.0).iter().all(|&kept| kept));
+        assert_eq!(thin(&boxes(15.0), 1.0), [true, false, true, false, true, false, true]);
+        assert_eq!(thin(&boxes(25.0), 1.0), [true, false, false, true, false, false, true]);
+        assert!(thin(&[], 1.0).is_empty());
+    }
+}
@@ -0,0 +1,63 @@
+use std::rc::Rc;
+
+use crate::color::{Color, BLACK};
+use crate::core::Style;
+use crate::font::{Font, measure_text};
+use crate::mark::{Rect, Text, TextAlign, TextBaseline, TextOverflow};
+
+/// Color swatches with their labels, stacked from the top left corner at (x, y)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Legend {
+    pub x: f64,
+    pub y: f64,
+    pub items: Vec<(String, Color)>,
+    /// Side of the swatches
+    pub size: f64,
+    /// Distance between the tops of adjacent items
+    pub spacing: f64,
+    /// Color of the labels
+    pub color: Color,
+    pub font: Font,
+    /// Longer labels are truncated with an ellipsis
+    pub max_label_width: Option<f64>,
+}
+
+impl Legend {
+    pub fn new(x: f64, y: f64, items: Vec<(String, Color)>) -> Legend {
+        Legend { x, y, items, size: 10.0, spacing: 16.0, color: BLACK, font: Font::default(), max_label_width: None }
+    }
+
+    /// Width of the swatches and the widest label
+    pub fn width(&self) -> f64 {
+        let widest = self.items.iter().map(|(label, _)| measure_text(label, &self.font).0).fold(0.0, f64::max);
+        self.size + 4.0 + self.max_label_width.map_or(widest, |max| widest.min(max))
+    }
+
+    pub fn marks(&self) -> (Vec<Rect>, Vec<Text>) {
+        self.items.iter().enumerate()
+            .map(|(i, (label, color))| {
+                let top = self.y + i as f64 * self.spacing;
+                let swatch = Rect {
+                    x: self.x,
+                    y: top,
+                    width: self.size,
+                    height: self.size,
+                    style: Rc::new(Style { fill: Some((*color).into()), stroke: None, stroke_width: 0.0, ..Style::default() }),
+                };
+                let text = Text {
+                    x: self.x + self.size + 4.0,
+                    y: top + self.size / 2.0,
+                    text: label.clone(),
+                    align: TextAlign::Start,
+                    baseline: TextBaseline::Middle,
+                    font: self.font.clone(),
+                    max_width: self.max_label_width,
+                    overflow: TextOverflow::Ellipsis,
+                    style: Rc::new(Style { fill: Some(self.color.into()), ..Style::default() }),
+                    ..Text::default()
+                };
+                (swatch, text)
+            })
+            .unzip()
+    }
+}
@@ -0,0 +1,59 @@
+//!
+//! Common charts built from scales, transforms and marks
+//!
+
+use std::collections::HashMap;
+
+use crate::transform::{Density, KdeParams, kde};
+use crate::utils::Extend;
+
+pub mod axis;
+pub use self::axis::{Axis, AxisOrient, format_date, format_number};
+
+pub mod legend;
+pub use self::legend::Legend;
+
+pub mod label;
+pub use self::label::{Bounds, Candidate, LabelLayout, LabelLayoutBuilder, Placement};
+
+pub mod histogram;
+pub use self::histogram::{Histogram, HistogramBuilder, HistogramLayout};
+
+pub mod violin;
+pub use self::violin::{Violin, ViolinBuilder, ViolinLayout};
+
+pub mod ridgeline;
+pub use self::ridgeline::{Ridgeline, RidgelineBuilder, RidgelineLayout};
+
+pub mod bar_chart;
+pub use self::bar_chart::{BarChart, BarChartBuilder, BarLayout, BarSort, Orientation};
+
+pub mod spec;
+pub use self::spec::{Chart, ChartBuilder, Encoding, EncodingBuilder, FieldDef, FieldType, MarkType, ScaleType, Theme};
+
+pub mod vega_lite;
+pub use self::vega_lite::SpecError;
+
+/// Density of the values of each category, categories in first-seen order
+/// Most ticks of an axis, whatever its length
+const MAX_TICKS: usize = 100;
+
+fn densities_by_group(category: &[String], value: &[f64], params: &KdeParams) -> (Vec<String>, Vec<Density>) {
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    let mut groups: Vec<(String, Vec<f64>)> = vec![];
+    for (c, &v) in category.iter().zip(value) {
+        let len = index.len();
+        let i = *index.entry(c).or_insert(len);
+        if i == groups.len() { groups.push((c.clone(), vec![])) };
+        groups[i].1.push(v);
+    }
+    groups.into_iter().map(|(c, values)| (c, kde(&values, params))).unzip()
+}
+
+/// Extent of the grids and highest density of all the densities
+fn density_bounds(densities: &[Density]) -> ([f64; 2], f64) {
+    let xs: Vec<f64> = densities.iter().flat_map(|d| d.x.iter().cloned()).collect();
+    let domain = if xs.is_empty() { [0.0, 1.0] } else { f64::extend(&xs) };
+    let max = densities.iter().flat_map(|d| d.y.iter().cloned()).fold(0.0, f64::max);
+    (domain, max)
+}
@@ -0,0 +1,96 @@
+use std::rc::Rc;
+
+use crate::chart::{densities_by_group, density_bounds};
+use crate::core::Style;
+use crate::mark::Area;
+use crate::scale::{BandScale, BandScaleBuilder, LinearScale, Scale};
+use crate::transform::{Density, KdeParams};
+
+/// Distributions of a numeric variable per category, as densities stacked one over another
+#[derive(Debug, Builder)]
+#[builder(setter(into))]
+pub struct Ridgeline {
+    pub category: Vec<String>,
+    pub value: Vec<f64>,
+    #[builder(default)]
+    pub kde: KdeParams,
+    /// Pixels for the values
+    pub x_range: [f64; 2],
+    /// Pixels where the rows of the categories are laid out, from top to bottom
+    pub y_range: [f64; 2],
+    /// Height of the highest density in rows, above 1 the ridges overlap the row above
+    #[builder(default = "1.0")]
+    pub overlap: f64,
+    /// Inner and outer padding of the rows, 0 so the ridges sit on the next row
+    #[builder(default = "0.0")]
+    pub padding: f64,
+    #[builder(default)]
+    pub style: Rc<Style>,
+}
+
+/// Everything needed to draw a ridgeline chart and its axes
+#[derive(Debug)]
+pub struct RidgelineLayout {
+    pub categories: Vec<String>,
+    pub densities: Vec<Density>,
+    pub band_scale: BandScale<String>,
+    pub value_scale: LinearScale,
+    pub areas: Vec<Area>,
+}
+
+impl Ridgeline {
+    pub fn layout(&self) -> Result<RidgelineLayout, String> {
+        let (categories, densities) = densities_by_group(&self.category, &self.value, &self.kde);
+        let (domain, max_density) = density_bounds(&densities);
+
+        let band_scale: BandScale<String> = BandScaleBuilder::default()
+            .domain(categories.clone())
+            .range(self.y_range)
+            .padding(self.padding)
+            .build()?;
+        let value_scale = LinearScale { domain, range: self.x_range, clamp: false, round: false };
+
+        let k = if max_density > 0.0 { band_scale.step() * self.overlap / max_density } else { 0.0 };
+        let areas = categories.iter().zip(&densities)
+            .map(|(c, d)| {
+                let baseline = band_scale.call_one(c) + band_scale.bandwidth();
+                Area::horizontal(
+                    value_scale.call(&d.x),
+                    vec![baseline; d.x.len()],
+                    d.y.iter().map(|y| baseline - y * k).collect(),
+                    self.style.clone()
+                )
+            })
+            .collect();
+
+        Ok(RidgelineLayout { categories, densities, band_scale, value_scale, areas })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::{Bandwidth, KdeParamsBuilder};
+
+    #[test]
+    fn layout_works() {
+        let ridgeline = RidgelineBuilder::default()
+            .category(vec!["a".to_string(), "b".to_string()])
+            .value(vec![0.0, 5.0])
+            .kde(KdeParamsBuilder::default().bandwidth(Bandwidth::Fixed(1.0)).steps(11_usize).build().unwrap())
+            .x_range([0.0, 110.0])
+            .y_range([0.0, 100.0])
+            .overlap(2.0)
+            .build().unwrap();
+        let layout = ridgeline.layout().unwrap();
+        assert_eq!(layout.categories, ["a", "b"]);
+        let b = &layout.areas[1];
+        assert_eq!(b.y0, vec![100.0; 11]);
+        assert_eq!(b.y1[5], 0.0);
+        assert_eq!(layout.areas[0].y0[0], 50.0);
+
+        // Padded rows are narrower, 40 pixels apart
+        let padded = Ridgeline { padding: 0.5, ..ridgeline };
+        assert_eq!(padded.layout().unwrap().band_scale.bandwidth(), 20.0);
+    }
+}
@@ -0,0 +1,786 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::str::FromStr;
+
+use chrono::{TimeZone, Utc};
+
+use crate::chart::{Axis, AxisOrient, Legend, MAX_TICKS, format_date, format_number};
+use crate::color::{Color, BLACK, WHITE, PALETTE_CATEGORY10, PALETTE_SET2};
+use crate::core::{Canvas, Glyph, LineJoin, Scene, Style};
+use crate::data::{DataType, Table, Value};
+use crate::interpolate::Interpolate;
+use crate::font::{Font, FontWeight};
+use crate::mark::{Area, Line, RectBatch, Segment, SymbolBatch, SymbolShape, Text, TextAlign, TextBaseline, TextOverflow};
+use crate::scale::{BandScale, BandScaleBuilder, DateTime, LinearScale, LogScaleBuilder, PowScaleBuilder, Scale,
+                   SymlogScaleBuilder, TimeScale, ticks};
+use crate::scale::time_ticks::{tick_interval, time_ticks};
+use crate::utils::Extend;
+
+/// How the values of a field are read, which decides the scales of the channels it is bound to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldType {
+    /// Numbers, mapped by continuous scales
+    Quantitative,
+    /// Dates or millis, mapped by time scales
+    Temporal,
+    /// Unordered categories, kept in first-seen order
+    Nominal,
+    /// Ordered categories, sorted numerically when they are all numbers
+    Ordinal,
+}
+
+/// Continuous scale of a quantitative field
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScaleType { Linear, Log, Pow(f64), Sqrt, Symlog }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarkType {
+    /// Hollow symbols, their shape can be encoded
+    Point,
+    Circle,
+    Square,
+    /// Bars from zero along a nominal or ordinal axis, stacked when they share a category
+    Bar,
+    /// A line for each color, its points sorted along x
+    Line,
+    /// An area from zero for each color, areas are not stacked
+    Area,
+}
+
+impl FromStr for MarkType {
+    type Err = String;
+
+    /// Lowercase names, like in Vega-Lite
+    fn from_str(name: &str) -> Result<MarkType, String> {
+        match name {
+            "point" => Ok(MarkType::Point),
+            "circle" => Ok(MarkType::Circle),
+            "square" => Ok(MarkType::Square),
+            "bar" => Ok(MarkType::Bar),
+            "line" => Ok(MarkType::Line),
+            "area" => Ok(MarkType::Area),
+            _ => Err(format!("'{}' should be point, circle, square, bar, line or area", name)),
+        }
+    }
+}
+
+/// A field of the data bound to a channel
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDef {
+    pub field: String,
+    pub field_type: FieldType,
+    /// Scale of quantitative fields, linear by default
+    pub scale: Option<ScaleType>,
+    /// Domain of continuous scales, the extent of the values by default
+    pub domain: Option<[f64; 2]>,
+    /// Whether continuous positions include zero, by default only for bars and areas
+    pub zero: Option<bool>,
+    /// Title of the axis or legend, the name of the field by default
+    pub title: Option<String>,
+    /// Whether the axis or legend of the channel is drawn
+    pub guide: bool,
+}
+
+impl FieldDef {
+    pub fn new(field: &str, field_type: FieldType) -> FieldDef {
+        FieldDef { field: field.to_string(), field_type, scale: None, domain: None, zero: None, title: None, guide: true }
+    }
+
+    pub fn quantitative(field: &str) -> FieldDef { FieldDef::new(field, FieldType::Quantitative) }
+
+    pub fn temporal(field: &str) -> FieldDef { FieldDef::new(field, FieldType::Temporal) }
+
+    pub fn nominal(field: &str) -> FieldDef { FieldDef::new(field, FieldType::Nominal) }
+
+    pub fn ordinal(field: &str) -> FieldDef { FieldDef::new(field, FieldType::Ordinal) }
+
+    pub fn scale(mut self, scale: ScaleType) -> FieldDef {
+        self.scale = Some(scale);
+        self
+    }
+
+    pub fn domain(mut self, domain: [f64; 2]) -> FieldDef {
+        self.domain = Some(domain);
+        self
+    }
+
+    pub fn zero(mut self, zero: bool) -> FieldDef {
+        self.zero = Some(zero);
+        self
+    }
+
+    pub fn title(mut self, title: &str) -> FieldDef {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub fn guide(mut self, guide: bool) -> FieldDef {
+        self.guide = guide;
+        self
+    }
+
+    /// Field typed after its column: numbers are quantitative, dates temporal and the rest nominal
+    pub fn infer(table: &Table, field: &str) -> Result<FieldDef, String> {
+        let field_type = match table.column(field).map_err(|e| e.to_string())?.data_type() {
+            DataType::Float | DataType::Int => FieldType::Quantitative,
+            DataType::DateTime => FieldType::Temporal,
+            DataType::Str | DataType::Categorical | DataType::Bool => FieldType::Nominal,
+        };
+        Ok(FieldDef::new(field, field_type))
+    }
+
+    fn label(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.field)
+    }
+}
+
+/// Channels of the mark bound to fields. `size` is the area of points, `shape` applies to points only
+#[derive(Debug, Clone, Default, PartialEq, Builder)]
+#[builder(setter(into))]
+pub struct Encoding {
+    #[builder(default)]
+    pub x: Option<FieldDef>,
+    #[builder(default)]
+    pub y: Option<FieldDef>,
+    #[builder(default)]
+    pub color: Option<FieldDef>,
+    #[builder(default)]
+    pub size: Option<FieldDef>,
+    #[builder(default)]
+    pub shape: Option<FieldDef>,
+    #[builder(default)]
+    pub opacity: Option<FieldDef>,
+    /// Fields listed when hovering a point or a bar
+    #[builder(default)]
+    pub tooltip: Vec<FieldDef>,
+}
+
+/// Colors of a chart. Nominal fields are colored with the palette, ordinal ones with a ramp of blues
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub background: Color,
+    /// Axes and text
+    pub foreground: Color,
+    pub palette: Vec<Color>,
+}
+
+impl Theme {
+    pub fn light() -> Theme {
+        Theme { background: WHITE, foreground: BLACK, palette: PALETTE_CATEGORY10.to_vec() }
+    }
+
+    pub fn dark() -> Theme {
+        Theme {
+            background: Color { r: 34, g: 34, b: 34, a: 1.0 },
+            foreground: Color { r: 221, g: 221, b: 221, a: 1.0 },
+            palette: PALETTE_SET2.to_vec()
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::light()
+    }
+}
+
+/// A chart described by its data, a mark and the encodings of the mark.
+/// The scales, axes and color legend are inferred from the types of the fields
+#[derive(Debug, Builder)]
+#[builder(setter(into))]
+pub struct Chart {
+    pub data: Table,
+    pub mark: MarkType,
+    #[builder(default)]
+    pub encoding: Encoding,
+    #[builder(default)]
+    pub title: Option<String>,
+    #[builder(default = "800")]
+    pub width: u32,
+    #[builder(default = "400")]
+    pub height: u32,
+    /// Top, Right, Bottom, Left
+    #[builder(default = "(40.0, 140.0, 50.0, 70.0)")]
+    pub margin: (f64, f64, f64, f64),
+    #[builder(default)]
+    pub theme: Theme,
+}
+
+const SHAPES: [SymbolShape; 5] = [SymbolShape::Circle, SymbolShape::Square, SymbolShape::Cross, SymbolShape::Diamond, SymbolShape::Triangle];
+
+/// Values of a field: numbers for quantitative and temporal fields, strings with their domain otherwise
+enum Values {
+    Numbers(Vec<f64>),
+    Categories(Vec<String>, Vec<String>),
+}
+
+impl Values {
+    fn read(table: &Table, def: &FieldDef) -> Result<Values, String> {
+        let column = table.column(&def.field).map_err(|e| e.to_string())?;
+        match def.field_type {
+            FieldType::Quantitative | FieldType::Temporal => {
+                if column.data_type() == DataType::Str || column.data_type() == DataType::Categorical {
+                    let expected = if def.field_type == FieldType::Temporal { "dates" } else { "numbers" };
+                    return Err(format!("'{}' should hold {} to be {:?}", def.field, expected, def.field_type));
+                }
+                if def.scale.is_some() && def.field_type == FieldType::Temporal {
+                    return Err(format!("'{}' is temporal, only quantitative fields can set their scale", def.field));
+                }
+                Ok(Values::Numbers(column.to_f64()))
+            },
+            FieldType::Nominal | FieldType::Ordinal => {
+                if def.scale.is_some() {
+                    return Err(format!("'{}' is {:?}, only quantitative fields can set their scale", def.field, def.field_type));
+                }
+                let values = column.to_strings();
+                let mut seen: HashSet<&String> = HashSet::new();
+                let mut domain: Vec<String> = values.iter().filter(|v| seen.insert(v)).cloned().collect();
+                if def.field_type == FieldType::Ordinal {
+                    let numbers: Option<Vec<f64>> = domain.iter().map(|v| v.parse().ok()).collect();
+                    match numbers {
+                        Some(numbers) => {
+                            let mut order: Vec<usize> = (0..domain.len()).collect();
+                            order.sort_by(|&a, &b| numbers[a].total_cmp(&numbers[b]));
+                            domain = order.into_iter().map(|i| domain[i].clone()).collect();
+                        },
+                        None => domain.sort(),
+                    }
+                }
+                Ok(Values::Categories(values, domain))
+            },
+        }
+    }
+
+    /// Min and max of the numbers, None for categories or when there are no numbers
+    fn extent(&self) -> Option<[f64; 2]> {
+        match self {
+            Values::Numbers(values) => {
+                let [lo, hi] = f64::extend(values);
+                if lo <= hi { Some([lo, hi]) } else { None }
+            },
+            Values::Categories(..) => None,
+        }
+    }
+
+    /// Position of the category of each row in the domain
+    fn indices(&self) -> Option<Vec<usize>> {
+        match self {
+            Values::Numbers(_) => None,
+            Values::Categories(values, domain) => {
+                let index: HashMap<&String, usize> = domain.iter().enumerate().map(|(i, v)| (v, i)).collect();
+                Some(values.iter().map(|v| index[v]).collect())
+            },
+        }
+    }
+}
+
+/// Domain of a continuous scale, empty and single value extents are widened
+fn padded([lo, hi]: [f64; 2]) -> [f64; 2] {
+    if lo < hi { [lo, hi] } else if lo.is_finite() { [lo - 1.0, hi + 1.0] } else { [0.0, 1.0] }
+}
+
+/// Continuous scale of a quantitative field
+fn continuous<R: Interpolate + Default + 'static>(def: &FieldDef, domain: [f64; 2], range: [R; 2]) -> Result<Box<dyn Scale<f64, R>>, String> {
+    Ok(match def.scale.unwrap_or(ScaleType::Linear) {
+        ScaleType::Linear => Box::new(LinearScale { domain, range, clamp: false, round: false }),
+        ScaleType::Log => {
+            if domain[0] <= 0.0 && domain[1] >= 0.0 {
+                return Err(format!("'{}' crosses zero, it can not use a log scale", def.field));
+            }
+            Box::new(LogScaleBuilder::default().domain(domain).range(range).build()?)
+        },
+        ScaleType::Pow(exponent) => Box::new(PowScaleBuilder::default().domain(domain).range(range).exponent(exponent).build()?),
+        ScaleType::Sqrt => Box::new(PowScaleBuilder::default().domain(domain).range(range).exponent(0.5).build()?),
+        ScaleType::Symlog => Box::new(SymlogScaleBuilder::default().domain(domain).range(range).build()?),
+    })
+}
+
+/// Labels of numeric ticks, with the decimals needed by the closest pair
+fn number_labels(values: &[f64]) -> Vec<String> {
+    let step = values.windows(2).map(|w| (w[1] - w[0]).abs()).fold(f64::INFINITY, f64::min);
+    let step = if step.is_finite() { step } else { 1.0 };
+    values.iter().map(|&v| format_number(v, step)).collect()
+}
+
+/// Ticks of a temporal domain with their labels
+fn date_ticks([d0, d1]: [f64; 2], count: usize) -> Vec<(f64, String)> {
+    let (unit, _) = tick_interval(d0, d1, count);
+    time_ticks(&DateTime(d0), &DateTime(d1), count).into_iter()
+        .map(|d| (d.0, format_date(&d, unit)))
+        .collect()
+}
+
+/// Scale of the x or y channel
+enum Position {
+    Continuous(Box<dyn Scale<f64, f64>>),
+    Time(TimeScale),
+    Band(BandScale<String>),
+}
+
+impl Position {
+    /// Scale of a field over the pixels of `range`. Continuous domains default to the extent of the values,
+    /// categories get bands for bars and points otherwise
+    fn new(def: &FieldDef, values: &Values, domain: Option<[f64; 2]>, range: [f64; 2], bars: bool) -> Result<Position, String> {
+        let count = Position::tick_count(range);
+        match values {
+            Values::Categories(_, categories) => {
+                let (inner, outer) = if bars { (0.1, 0.1) } else { (1.0, 0.5) };
+                let scale = BandScaleBuilder::default()
+                    .domain(categories.clone())
+                    .range(range)
+                    .padding_inner(inner)
+                    .padding_outer(outer)
+                    .build()?;
+                Ok(Position::Band(scale))
+            },
+            Values::Numbers(_) => {
+                let [d0, d1] = padded(domain.or_else(|| values.extent()).unwrap_or([0.0, 1.0]));
+                if def.field_type == FieldType::Temporal {
+                    return Ok(Position::Time(TimeScale { domain: [DateTime(d0), DateTime(d1)], range, clamp: false, round: false }));
+                }
+                let domain = if def.scale == Some(ScaleType::Log) { [d0, d1] } else { ticks::nice(d0, d1, count) };
+                Ok(Position::Continuous(continuous(def, domain, range)?))
+            },
+        }
+    }
+
+    /// About a tick every 60 pixels, at most `MAX_TICKS`
+    fn tick_count(range: [f64; 2]) -> usize {
+        ((range[1] - range[0]).abs() / 60.0).max(2.0).min(MAX_TICKS as f64) as usize
+    }
+
+    /// Pixel of a number, NaN for categories
+    fn number(&self, value: f64) -> f64 {
+        match self {
+            Position::Continuous(scale) => scale.call_one(&value),
+            Position::Time(scale) => scale.call_one(&DateTime(value)),
+            Position::Band(_) => f64::NAN,
+        }
+    }
+
+    /// Pixels of the rows, the start of their band for categories
+    fn call(&self, values: &Values) -> Vec<f64> {
+        match (self, values) {
+            (Position::Band(scale), Values::Categories(values, _)) => scale.call(values),
+            (_, Values::Numbers(values)) => values.iter().map(|&v| self.number(v)).collect(),
+            (_, Values::Categories(values, _)) => vec![f64::NAN; values.len()],
+        }
+    }
+
+    fn bandwidth(&self) -> f64 {
+        match self {
+            Position::Band(scale) => scale.bandwidth(),
+            _ => 0.0,
+        }
+    }
+
+    fn ticks(&self) -> Vec<(f64, String)> {
+        match self {
+            Position::Continuous(scale) => {
+                let range = scale.range();
+                let values = scale.ticks(Position::tick_count([range[0], range[1]]));
+                let labels = number_labels(&values);
+                values.iter().map(|v| scale.call_one(v)).zip(labels).collect()
+            },
+            Position::Time(scale) => {
+                date_ticks([scale.domain[0].0, scale.domain[1].0], Position::tick_count(scale.range)).into_iter()
+                    .map(|(v, label)| (scale.call_one(&DateTime(v)), label))
+                    .collect()
+            },
+            Position::Band(scale) => scale.domain().into_iter()
+                .map(|c| (scale.call_one(&c) + scale.bandwidth() / 2.0, c))
+                .collect(),
+        }
+    }
+}
+
+/// Value of a channel for each row, and the entries of its legend
+type Encoded<R> = (Vec<R>, Vec<(String, R)>);
+
+/// Value of a channel for each row and the entries of its legend. Numbers are mapped over `range`,
+/// the i-th of n categories to `discrete(i, n)`
+fn encode<R: Interpolate + Default + 'static>(
+    def: &FieldDef,
+    values: &Values,
+    range: [R; 2],
+    discrete: impl Fn(usize, usize) -> R
+) -> Result<Encoded<R>, String>
+{
+    match values {
+        Values::Categories(_, domain) => {
+            let rows = values.indices().unwrap_or_default().into_iter().map(|i| discrete(i, domain.len())).collect();
+            let legend = domain.iter().enumerate().map(|(i, c)| (c.clone(), discrete(i, domain.len()))).collect();
+            Ok((rows, legend))
+        },
+        Values::Numbers(numbers) => {
+            let domain = padded(def.domain.or_else(|| values.extent()).unwrap_or([0.0, 1.0]));
+            if def.field_type == FieldType::Temporal {
+                let scale = TimeScale { domain: [DateTime(domain[0]), DateTime(domain[1])], range, clamp: false, round: false };
+                let rows = numbers.iter().map(|&v| scale.call_one(&DateTime(v))).collect();
+                let legend = date_ticks(domain, 5).into_iter().map(|(v, label)| (label, scale.call_one(&DateTime(v)))).collect();
+                return Ok((rows, legend));
+            }
+            let scale = continuous(def, domain, range)?;
+            let ticks = scale.ticks(5);
+            let legend = number_labels(&ticks).into_iter().zip(ticks.iter().map(|v| scale.call_one(v))).collect();
+            Ok((numbers.iter().map(|v| scale.call_one(v)).collect(), legend))
+        },
+    }
+}
+
+/// Text of a value in a tooltip
+fn value_text(value: Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Float(x) => x.to_string(),
+        Value::Int(x) => x.to_string(),
+        Value::Str(x) => x,
+        Value::DateTime(x) => Utc.timestamp_millis_opt(x.timestamp_millis()).single()
+            .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default(),
+        Value::Bool(x) => x.to_string(),
+    }
+}
+
+/// Rows of each category of `keys` in first-seen order, a single group without keys
+fn groups(rows: &[usize], keys: Option<&[usize]>) -> Vec<Vec<usize>> {
+    let mut index: HashMap<usize, usize> = HashMap::new();
+    let mut groups: Vec<Vec<usize>> = vec![];
+    for &row in rows {
+        let key = keys.map(|k| k[row]).unwrap_or(0);
+        let i = *index.entry(key).or_insert_with(|| { groups.push(vec![]); groups.len() - 1 });
+        groups[i].push(row);
+    }
+    groups
+}
+
+/// [v0, v1] of each bar, from the sum of the previous bars of its key to the new sum. Positive and negative
+/// values are stacked apart. Also returns the extent of the stacks
+fn stack_bars(keys: &[usize], values: &[f64]) -> (Vec<[f64; 2]>, [f64; 2]) {
+    let mut sums: HashMap<usize, (f64, f64)> = HashMap::new();
+    let mut intervals = vec![];
+    for (key, &v) in keys.iter().zip(values) {
+        let (negative, positive) = sums.entry(*key).or_insert((0.0, 0.0));
+        let sum = if v < 0.0 { negative } else { positive };
+        intervals.push(if v.is_nan() { [f64::NAN; 2] } else { *sum += v; [*sum - v, *sum] });
+    }
+    let extent = sums.values().fold([0.0_f64, 0.0_f64], |[lo, hi], &(n, p)| [lo.min(n), hi.max(p)]);
+    (intervals, extent)
+}
+
+impl Chart {
+    fn values(&self, def: &Option<FieldDef>) -> Result<Option<Values>, String> {
+        def.as_ref().map(|def| Values::read(&self.data, def)).transpose()
+    }
+
+    /// Scene with the marks, the axes, the title and a legend when color is encoded
+    pub fn scene<T: 'static>(&self) -> Result<Scene<T>, String> where
+        SymbolBatch: Glyph<Context = T>,
+        RectBatch: Glyph<Context = T>,
+        Line: Glyph<Context = T>,
+        Area: Glyph<Context = T>,
+        Segment: Glyph<Context = T>,
+        Text: Glyph<Context = T>,
+        crate::mark::Rect: Glyph<Context = T>
+    {
+        let encoding = &self.encoding;
+        let (x_def, y_def) = match (&encoding.x, &encoding.y) {
+            (Some(x), Some(y)) => (x, y),
+            _ => return Err(format!("'{:?}' marks need an x and a y encoding", self.mark)),
+        };
+        let x_values = Values::read(&self.data, x_def)?;
+        let y_values = Values::read(&self.data, y_def)?;
+        let len = self.data.len();
+
+        let (width, height, margin) = (self.width as f64, self.height as f64, self.margin);
+        let x_range = [margin.3, width - margin.1];
+        let y_range = [height - margin.2, margin.0];
+
+        // Bars are stacked from zero along their category: [v0, v1] of each row
+        let vertical = match (&x_values, &y_values) {
+            (Values::Categories(..), Values::Numbers(_)) => true,
+            (Values::Numbers(_), Values::Categories(..)) => false,
+            _ if self.mark == MarkType::Bar => {
+                return Err("'Bar' marks need a nominal or ordinal field on one axis and a quantitative one on the other".to_string());
+            },
+            _ => true,
+        };
+        let mut intervals: Vec<[f64; 2]> = vec![];
+        let (mut x_domain, mut y_domain) = (x_values.extent(), y_values.extent());
+        if self.mark == MarkType::Bar {
+            let (keys, values) = if vertical { (&x_values, &y_values) } else { (&y_values, &x_values) };
+            let (keys, values) = match (keys.indices(), values) {
+                (Some(keys), Values::Numbers(values)) => (keys, values),
+                _ => unreachable!(),
+            };
+            let (stacked, domain) = stack_bars(&keys, values);
+            intervals = stacked;
+            if vertical { y_domain = Some(domain) } else { x_domain = Some(domain) };
+        }
+        let bars = self.mark == MarkType::Bar;
+        let zero = |def: &FieldDef, domain: Option<[f64; 2]>, default: bool| {
+            if def.domain.is_some() { return def.domain };
+            let zero = def.zero.unwrap_or(default) && def.scale != Some(ScaleType::Log);
+            domain.map(|[lo, hi]| if zero { [lo.min(0.0), hi.max(0.0)] } else { [lo, hi] })
+        };
+        let x_domain = zero(x_def, x_domain, bars);
+        let y_domain = zero(y_def, y_domain, bars || self.mark == MarkType::Area);
+
+        let x_scale = Position::new(x_def, &x_values, x_domain, x_range, bars)?;
+        let y_scale = Position::new(y_def, &y_values, y_domain, y_range, bars)?;
+        let (x_band, y_band) = (x_scale.bandwidth(), y_scale.bandwidth());
+        let x: Vec<f64> = x_scale.call(&x_values).into_iter().map(|x| x + x_band / 2.0).collect();
+        let y: Vec<f64> = y_scale.call(&y_values).into_iter().map(|y| y + y_band / 2.0).collect();
+        let rows: Vec<usize> = (0..len).filter(|&i| x[i].is_finite() && y[i].is_finite()).collect();
+
+        let palette = &self.theme.palette;
+        let default_color = palette.first().cloned().unwrap_or(self.theme.foreground);
+        let color_values = self.values(&encoding.color)?;
+        let (colors, color_legend) = match (&encoding.color, &color_values) {
+            (Some(def), Some(values)) => {
+                // Sequential blues for ordered categories
+                let ramp = LinearScale { domain: [0.0, 1.0], range: [Color { r: 198, g: 219, b: 239, a: 1.0 }, Color { r: 8, g: 48, b: 107, a: 1.0 }], clamp: false, round: false };
+                let ordinal = def.field_type == FieldType::Ordinal;
+                encode(def, values, ramp.range, |i, n| {
+                    if ordinal { ramp.call_one(&if n > 1 { i as f64 / (n - 1) as f64 } else { 1.0 }) } else { palette.get(i % palette.len().max(1)).cloned().unwrap_or(default_color) }
+                })?
+            },
+            _ => (vec![default_color; len], vec![]),
+        };
+        let opacities = match (&encoding.opacity, self.values(&encoding.opacity)?) {
+            (Some(def), Some(values)) => encode(def, &values, [0.3, 1.0], |i, n| 0.3 + 0.7 * (i + 1) as f64 / n as f64)?.0,
+            _ => vec![if self.mark == MarkType::Area { 0.7 } else { 1.0 }; len],
+        };
+        let fills: Vec<Color> = colors.iter().zip(&opacities).map(|(c, o)| Color { a: c.a * *o as f32, ..*c }).collect();
+        let tooltip: Option<Vec<String>> = if encoding.tooltip.is_empty() { None } else {
+            let columns = encoding.tooltip.iter()
+                .map(|def| self.data.column(&def.field).map(|c| (def.label(), c)))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            Some((0..len).map(|row| {
+                columns.iter().map(|(label, c)| format!("{}: {}", label, value_text(c.get(row)))).collect::<Vec<String>>().join("\n")
+            }).collect())
+        };
+        let tooltip_of = |rows: &[usize]| tooltip.as_ref().map(|t| rows.iter().map(|&i| t[i].clone()).collect());
+
+        let mut scene = Scene::new(Canvas { width: self.width, height: self.height, background: self.theme.background });
+        match self.mark {
+            MarkType::Point | MarkType::Circle | MarkType::Square => {
+                let sizes = match (&encoding.size, self.values(&encoding.size)?) {
+                    (Some(def), Some(values)) => encode(def, &values, [9.0, 361.0], |i, n| 9.0 + 352.0 * (i + 1) as f64 / n as f64)?.0,
+                    _ => vec![30.0; len],
+                };
+                let shapes: Vec<usize> = match (self.mark, &encoding.shape, self.values(&encoding.shape)?) {
+                    (MarkType::Point, Some(def), Some(values)) => values.indices()
+                        .ok_or(format!("'{}' should be nominal or ordinal to encode shapes", def.field))?,
+                    (MarkType::Square, ..) => vec![1; len],
+                    _ => vec![0; len],
+                };
+                let filled = self.mark != MarkType::Point;
+                for group in groups(&rows, Some(&shapes)) {
+                    let colors: Vec<Option<Color>> = group.iter().map(|&i| Some(fills[i])).collect();
+                    let batch = SymbolBatch {
+                        x: group.iter().map(|&i| x[i]).collect(),
+                        y: group.iter().map(|&i| y[i]).collect(),
+                        size: group.iter().map(|&i| sizes[i]).collect::<Vec<f64>>().into(),
+                        shape: SHAPES[shapes[group[0]] % SHAPES.len()],
+                        fill: if filled { colors.clone().into() } else { None.into() },
+                        stroke: if filled { None.into() } else { colors.into() },
+                        stroke_width: 1.5,
+                        tooltip: tooltip_of(&group),
+                    };
+                    scene.add(Box::new(batch));
+                }
+            },
+            MarkType::Bar => {
+                let (mut rx, mut ry, mut rw, mut rh) = (vec![], vec![], vec![], vec![]);
+                let bars: Vec<usize> = rows.into_iter().filter(|&i| !intervals[i][0].is_nan()).collect();
+                for &i in &bars {
+                    let [v0, v1] = intervals[i];
+                    if vertical {
+                        let (p0, p1) = (y_scale.number(v0), y_scale.number(v1));
+                        rx.push(x[i] - x_band / 2.0);
+                        ry.push(p0.min(p1));
+                        rw.push(x_band);
+                        rh.push((p1 - p0).abs());
+                    } else {
+                        let (p0, p1) = (x_scale.number(v0), x_scale.number(v1));
+                        rx.push(p0.min(p1));
+                        ry.push(y[i] - y_band / 2.0);
+                        rw.push((p1 - p0).abs());
+                        rh.push(y_band);
+                    }
+                }
+                let batch = RectBatch {
+                    x: rx,
+                    y: ry,
+                    width: rw.into(),
+                    height: rh.into(),
+                    fill: bars.iter().map(|&i| Some(fills[i])).collect::<Vec<_>>().into(),
+                    stroke: None.into(),
+                    stroke_width: 1.0,
+                    tooltip: tooltip_of(&bars),
+                };
+                scene.add(Box::new(batch));
+            },
+            MarkType::Line | MarkType::Area => {
+                let keys = color_values.as_ref().and_then(|v| v.indices());
+                // Zero, or the bottom of the plot when it is not in the domain
+                let base = y_scale.number(0.0);
+                let base = if base.is_finite() { base.min(y_range[0]).max(y_range[1]) } else { y_range[0] };
+                for mut group in groups(&rows, keys.as_deref()) {
+                    group.sort_by(|&a, &b| x[a].total_cmp(&x[b]));
+                    let gx: Vec<f64> = group.iter().map(|&i| x[i]).collect();
+                    let gy: Vec<f64> = group.iter().map(|&i| y[i]).collect();
+                    let color = fills[group[0]];
+                    if self.mark == MarkType::Line {
+                        let style = Rc::new(Style { fill: None, stroke: Some(color.into()), stroke_width: 2.0, line_join: LineJoin::Round, ..Style::default() });
+                        scene.add(Box::new(Line { x: gx, y: gy, style, downsample: None }));
+                    } else {
+                        let style = Rc::new(Style { fill: Some(color.into()), stroke: None, stroke_width: 0.0, ..Style::default() });
+                        let baseline = vec![base; gx.len()];
+                        scene.add(Box::new(Area::horizontal(gx, baseline, gy, style)));
+                    }
+                }
+            },
+        }
+
+        let foreground = self.theme.foreground;
+        let axis_style = Rc::new(Style { fill: None, stroke: Some(foreground.into()), stroke_width: 1.0, ..Style::default() });
+        let text_style = Rc::new(Style { fill: Some(foreground.into()), ..Style::default() });
+        let text = |x: f64, y: f64, text: &str, align: TextAlign| Text { x, y, text: text.to_string(), align, style: text_style.clone(), ..Text::default() };
+        let x_axis = Axis::new(AxisOrient::Bottom, y_range[0], x_range, x_scale.ticks());
+        let y_axis = Axis::new(AxisOrient::Left, x_range[0], y_range, y_scale.ticks());
+        // Titles past the labels, the one of y reads upwards
+        let bold = Font { weight: FontWeight::Bold, ..Font::default() };
+        let x_title = Text {
+            baseline: TextBaseline::Top,
+            font: bold.clone(),
+            ..text((x_range[0] + x_range[1]) / 2.0, y_range[0] + x_axis.size() + 6.0, x_def.label(), TextAlign::Center)
+        };
+        let y_title = Text {
+            baseline: TextBaseline::Bottom,
+            font: bold.clone(),
+            angle: -90.0,
+            ..text(x_range[0] - y_axis.size() - 6.0, (y_range[0] + y_range[1]) / 2.0, y_def.label(), TextAlign::Center)
+        };
+        for (def, axis, title) in [(x_def, x_axis, x_title), (y_def, y_axis, y_title)] {
+            if !def.guide { continue };
+            let (segments, texts) = axis.marks(axis_style.clone());
+            segments.into_iter().for_each(|s| scene.add(Box::new(s)));
+            texts.into_iter().for_each(|t| scene.add(Box::new(t)));
+            scene.add(Box::new(title));
+        }
+        if let Some(title) = &self.title {
+            let font = Font { size: 14.0, ..bold.clone() };
+            // Centered clear of the legend
+            let max_width = Some(width - 2.0 * margin.1.max(margin.3));
+            scene.add(Box::new(Text { baseline: TextBaseline::Middle, font, max_width, ..text(width / 2.0, margin.0 / 2.0, title, TextAlign::Center) }));
+        }
+
+        if let Some(def) = encoding.color.as_ref().filter(|def| def.guide) {
+            // Labels cut short of the right edge
+            let legend_x = width - margin.1 + 20.0;
+            let max_width = Some(width - legend_x - 10.0);
+            scene.add(Box::new(Text {
+                font: bold,
+                max_width,
+                overflow: TextOverflow::Ellipsis,
+                ..text(legend_x, margin.0 - 8.0, def.label(), TextAlign::Start)
+            }));
+            let mut legend = Legend { color: foreground, ..Legend::new(legend_x, margin.0, color_legend) };
+            legend.max_label_width = max_width.map(|w| w - legend.size - 4.0);
+            let (swatches, texts) = legend.marks();
+            swatches.into_iter().for_each(|s| scene.add(Box::new(s)));
+            texts.into_iter().for_each(|t| scene.add(Box::new(t)));
+        }
+        Ok(scene)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Column;
+    use crate::svg_backend::SVGContext;
+
+    fn cars() -> Table {
+        Table::new()
+            .with_column("weight", vec![3.5, 1.2, 2.5, 2.0]).unwrap()
+            .with_column("price", vec![30.0, 10.0, 20.0, 15.0]).unwrap()
+            .with_column("cylinders", Column::categorical(&["8", "4", "6", "4"])).unwrap()
+            .with_column("origin", Column::categorical(&["us", "eu", "eu", "jp"])).unwrap()
+    }
+
+    #[test]
+    fn read_works() {
+        let table = cars();
+        match Values::read(&table, &FieldDef::ordinal("cylinders")).unwrap() {
+            Values::Categories(_, domain) => assert_eq!(domain, ["4", "6", "8"]),
+            Values::Numbers(_) => panic!("ordinal fields are categories"),
+        }
+        let nominal = Values::read(&table, &FieldDef::nominal("cylinders")).unwrap();
+        assert_eq!(nominal.indices().unwrap(), [0, 1, 2, 1]);
+        assert!(Values::read(&table, &FieldDef::quantitative("origin")).is_err());
+        assert!(Values::read(&table, &FieldDef::nominal("origin").scale(ScaleType::Log)).is_err());
+
+        assert_eq!(FieldDef::infer(&table, "weight").unwrap(), FieldDef::quantitative("weight"));
+        assert_eq!(FieldDef::infer(&table, "origin").unwrap(), FieldDef::nominal("origin"));
+        assert_eq!("area".parse::<MarkType>(), Ok(MarkType::Area));
+    }
+
+    #[test]
+    fn points_work() {
+        let chart = ChartBuilder::default()
+            .data(cars())
+            .mark(MarkType::Point)
+            .encoding(EncodingBuilder::default()
+                .x(FieldDef::quantitative("weight"))
+                .y(FieldDef::quantitative("price").scale(ScaleType::Log))
+                .color(FieldDef::nominal("origin"))
+                .shape(FieldDef::nominal("cylinders"))
+                .tooltip(vec![FieldDef::nominal("origin")])
+                .build().unwrap())
+            .title(Some("Cars".to_string()))
+            .build().unwrap();
+        let scene: Scene<SVGContext> = chart.scene().unwrap();
+        // 3 symbol batches, 2 axes, 2 axis titles, the title, a legend title and 3 legend items
+        let axes = scene.glyphs.len() - 3 - 3 - 1 - 3 * 2;
+        assert!(axes > 4, "{} axis glyphs", axes);
+
+        let missing = ChartBuilder::default()
+            .data(cars())
+            .mark(MarkType::Point)
+            .encoding(EncodingBuilder::default().x(FieldDef::quantitative("weight")).y(FieldDef::quantitative("year")).build().unwrap())
+            .build().unwrap();
+        assert_eq!(missing.scene::<SVGContext>().err(), Some("'year' not in the dataset".to_string()));
+    }
+
+    #[test]
+    fn bars_stack() {
+        let chart = ChartBuilder::default()
+            .data(cars())
+            .mark(MarkType::Bar)
+            .encoding(EncodingBuilder::default()
+                .x(FieldDef::nominal("origin"))
+                .y(FieldDef::quantitative("price"))
+                .build().unwrap())
+            .height(300_u32)
+            .margin((0.0, 0.0, 100.0, 100.0))
+            .build().unwrap();
+        let scene: Scene<SVGContext> = chart.scene().unwrap();
+        // 1 batch, 2 axes with 3 and 4 ticks (0 to 30 along 200 pixels), 2 axis titles
+        assert_eq!(scene.glyphs.len(), 1 + (1 + 3 + 3) + (1 + 4 + 4) + 2);
+
+        let (intervals, extent) = stack_bars(&[0, 1, 0, 0], &[1.0, 2.0, -3.0, 4.0]);
+        assert_eq!(intervals, [[0.0, 1.0], [0.0, 2.0], [0.0, -3.0], [1.0, 5.0]]);
+        assert_eq!(extent, [-3.0, 5.0]);
+
+        let wrong = ChartBuilder::default()
+            .data(cars())
+            .mark(MarkType::Bar)
+            .encoding(EncodingBuilder::default().x(FieldDef::quantitative("weight")).y(FieldDef::quantitative("price")).build().unwrap())
+            .build().unwrap();
+        assert!(wrong.scene::<SVGContext>().is_err());
+    }
+}
@@ -0,0 +1,649 @@
+//!
+//! Charts from a subset of Vega-Lite (https://vega.github.io/vega-lite/) specs:
+//!
+//! - `data`: inline `values`, an array of records. Optional when the data is given apart,
+//!   see `Chart::from_vega_lite_with_data`
+//! - `mark`: `point`, `circle`, `square`, `bar`, `line` or `area`, or an object with that `type`
+//! - `encoding`: `x`, `y`, `color`, `size`, `shape`, `opacity` and `tooltip`, with `field`, `type`, `title`,
+//!   `aggregate`, `bin`, `scale` (`type`, `exponent`, `domain`, `zero`), `axis` and `legend` (`title`, or null to hide them).
+//!   Binned fields become ordinal fields of the start of their bins
+//! - `transform`: `filter` with a field predicate (`equal`, `lt`, `lte`, `gt`, `gte`, `range`, `oneOf`, `valid`),
+//!   `aggregate` with `groupby`, and `bin`
+//! - `title`, `width`, `height`, `background`, `$schema` and `description`
+//!
+//! Any other property is an error instead of being ignored
+//!
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use serde_json::{Map, Value as JsonValue};
+
+use crate::chart::{Chart, ChartBuilder, Encoding, FieldDef, FieldType, MarkType, ScaleType, Theme};
+use crate::color::Color;
+use crate::data::{DataError, DataType, ReadOptions, Table};
+use crate::data::loader::read_records;
+use crate::transform::{Aggregate, AggregateOp, BinParams, MAX_BINS, Thresholds};
+use crate::transform::bin::{edges, locate};
+
+/// Largest width or height of a spec, the largest side of a PNG too
+pub const MAX_SIDE: u32 = 16384;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpecError {
+    /// Malformed JSON
+    Json(String),
+    /// A property helio does not support, by its path like `encoding.x.timeUnit`
+    Unsupported(String),
+    /// A supported property with a wrong value
+    Invalid { path: String, message: String },
+    Data(DataError),
+}
+
+impl Display for SpecError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            SpecError::Json(message) => write!(f, "Malformed spec: {}", message),
+            SpecError::Unsupported(path) => write!(f, "'{}' is not supported", path),
+            SpecError::Invalid { path, message } => write!(f, "'{}' {}", path, message),
+            SpecError::Data(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for SpecError {}
+
+impl From<DataError> for SpecError {
+    fn from(error: DataError) -> Self {
+        SpecError::Data(error)
+    }
+}
+
+fn invalid(path: &str, message: &str) -> SpecError {
+    SpecError::Invalid { path: path.to_string(), message: message.to_string() }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) }
+}
+
+/// The object at `path`, failing on keys that are not `allowed`
+fn object<'a>(value: &'a JsonValue, path: &str, allowed: &[&str]) -> Result<&'a Map<String, JsonValue>, SpecError> {
+    let map = value.as_object().ok_or_else(|| invalid(path, "should be an object"))?;
+    match map.keys().find(|k| !allowed.contains(&k.as_str())) {
+        Some(key) => Err(SpecError::Unsupported(join(path, key))),
+        None => Ok(map),
+    }
+}
+
+fn string<'a>(value: &'a JsonValue, path: &str) -> Result<&'a str, SpecError> {
+    value.as_str().ok_or_else(|| invalid(path, "should be a string"))
+}
+
+fn number(value: &JsonValue, path: &str) -> Result<f64, SpecError> {
+    value.as_f64().ok_or_else(|| invalid(path, "should be a number"))
+}
+
+fn positive(value: &JsonValue, path: &str) -> Result<f64, SpecError> {
+    Some(number(value, path)?).filter(|x| x.is_finite() && *x > 0.0).ok_or_else(|| invalid(path, "should be a positive number"))
+}
+
+/// Width or height of the chart
+/// Up to `MAX_SIDE`
+fn pixels(value: &JsonValue, path: &str) -> Result<u32, SpecError> {
+    let x = number(value, path)?;
+    if !(x > 0.0 && x.fract() == 0.0) {
+        return Err(invalid(path, "should be a positive whole number of pixels"));
+    }
+    if x > MAX_SIDE as f64 {
+        return Err(invalid(path, &format!("should be at most {} pixels", MAX_SIDE)));
+    }
+    Ok(x as u32)
+}
+
+fn array<'a>(value: &'a JsonValue, path: &str) -> Result<&'a Vec<JsonValue>, SpecError> {
+    value.as_array().ok_or_else(|| invalid(path, "should be an array"))
+}
+
+fn interval(value: &JsonValue, path: &str) -> Result<[f64; 2], SpecError> {
+    match array(value, path)?.as_slice() {
+        [a, b] => Ok([number(a, path)?, number(b, path)?]),
+        _ => Err(invalid(path, "should have two numbers")),
+    }
+}
+
+fn mark(value: &JsonValue, path: &str) -> Result<MarkType, SpecError> {
+    let (name, path) = match value {
+        JsonValue::Object(_) => {
+            let map = object(value, path, &["type"])?;
+            let path = join(path, "type");
+            (string(map.get("type").ok_or_else(|| invalid(&path, "is required"))?, &path)?, path)
+        },
+        _ => (string(value, path)?, path.to_string()),
+    };
+    name.parse().map_err(|_| invalid(&path, "should be point, circle, square, bar, line or area"))
+}
+
+fn aggregate_op(value: &JsonValue, path: &str) -> Result<AggregateOp, SpecError> {
+    match string(value, path)? {
+        "count" => Ok(AggregateOp::Count),
+        "distinct" => Ok(AggregateOp::Distinct),
+        "sum" => Ok(AggregateOp::Sum),
+        "mean" | "average" => Ok(AggregateOp::Mean),
+        "median" => Ok(AggregateOp::Median),
+        "min" => Ok(AggregateOp::Min),
+        "max" => Ok(AggregateOp::Max),
+        "stdev" => Ok(AggregateOp::Stdev),
+        "q1" => Ok(AggregateOp::Quantile(0.25)),
+        "q3" => Ok(AggregateOp::Quantile(0.75)),
+        _ => Err(invalid(path, "should be count, distinct, sum, mean, average, median, min, max, stdev, q1 or q3")),
+    }
+}
+
+/// `true` or an object with `maxbins`, `step` and `extent`. There are at most `MAX_BINS` bins
+fn bin_params(value: &JsonValue, path: &str) -> Result<Option<BinParams>, SpecError> {
+    match value {
+        JsonValue::Bool(binned) => Ok(if *binned { Some(BinParams::default()) } else { None }),
+        _ => {
+            let map = object(value, path, &["maxbins", "step", "extent"])?;
+            let mut params = BinParams::default();
+            if let Some(maxbins) = map.get("maxbins") {
+                let maxbins = positive(maxbins, &join(path, "maxbins"))?.min(MAX_BINS as f64);
+                params.thresholds = Thresholds::Count(maxbins as usize);
+            }
+            if let Some(step) = map.get("step") {
+                params.thresholds = Thresholds::Step(positive(step, &join(path, "step"))?);
+            }
+            if let Some(extent) = map.get("extent") {
+                params.domain = Some(interval(extent, &join(path, "extent"))?);
+            }
+            Ok(Some(params))
+        },
+    }
+}
+
+/// Starts and ends of the bins of some rows
+type BinColumns = (Vec<Option<f64>>, Vec<Option<f64>>);
+
+/// Start and end of the bin of each row of a column, None out of the bins
+fn bin_column(table: &Table, field: &str, params: &BinParams) -> Result<BinColumns, SpecError> {
+    let values = table.column(field)?.to_f64();
+    let edges = edges(&values, params);
+    Ok(values.iter()
+        .map(|&x| match locate(&edges, x) {
+            Some(i) => (Some(edges[i]), Some(edges[i + 1])),
+            None => (None, None),
+        })
+        .unzip())
+}
+
+fn data(value: &JsonValue, path: &str) -> Result<Table, SpecError> {
+    let map = object(value, path, &["values"])?;
+    let path = join(path, "values");
+    let records = array(map.get("values").ok_or_else(|| invalid(&path, "is required, only inline data is supported"))?, &path)?;
+    Ok(read_records(records, &ReadOptions::default())?)
+}
+
+fn filter(table: &Table, value: &JsonValue, path: &str) -> Result<Table, SpecError> {
+    if value.is_string() {
+        return Err(invalid(path, "should be a field predicate, expressions are not supported"));
+    }
+    let map = object(value, path, &["field", "equal", "lt", "lte", "gt", "gte", "range", "oneOf", "valid"])?;
+    let field = string(map.get("field").ok_or_else(|| invalid(&join(path, "field"), "is required"))?, &join(path, "field"))?;
+    let column = table.column(field)?;
+    let numbers = column.to_f64();
+    let strings = column.to_strings();
+
+    let mut rows: Vec<bool> = vec![true; table.len()];
+    let mut keep = |f: &dyn Fn(usize) -> bool| rows.iter_mut().enumerate().for_each(|(i, row)| *row = *row && f(i));
+    for (key, operand) in map {
+        let path = join(path, key);
+        match key.as_str() {
+            "field" => {},
+            "equal" => match operand {
+                JsonValue::Number(n) => { let n = n.as_f64().unwrap_or(f64::NAN); keep(&|i| numbers[i] == n) },
+                JsonValue::String(s) => keep(&|i| !column.is_null(i) && &strings[i] == s),
+                JsonValue::Bool(b) => { let b = b.to_string(); keep(&|i| !column.is_null(i) && strings[i] == b) },
+                _ => return Err(invalid(&path, "should be a number, a string or a boolean")),
+            },
+            "lt" => { let n = number(operand, &path)?; keep(&|i| numbers[i] < n) },
+            "lte" => { let n = number(operand, &path)?; keep(&|i| numbers[i] <= n) },
+            "gt" => { let n = number(operand, &path)?; keep(&|i| numbers[i] > n) },
+            "gte" => { let n = number(operand, &path)?; keep(&|i| numbers[i] >= n) },
+            "range" => { let [lo, hi] = interval(operand, &path)?; keep(&|i| lo <= numbers[i] && numbers[i] <= hi) },
+            "oneOf" => {
+                let options: Vec<String> = array(operand, &path)?.iter()
+                    .map(|o| match o {
+                        JsonValue::String(s) => Ok(s.clone()),
+                        JsonValue::Number(_) | JsonValue::Bool(_) => Ok(o.to_string()),
+                        _ => Err(invalid(&path, "should hold numbers, strings or booleans")),
+                    })
+                    .collect::<Result<_, _>>()?;
+                // Numbers are compared by value, "4" and "4.0" are the same option
+                let option_numbers: Vec<f64> = options.iter().filter_map(|o| o.parse().ok()).collect();
+                keep(&|i| !column.is_null(i) && (options.contains(&strings[i]) || option_numbers.contains(&numbers[i])))
+            },
+            "valid" => {
+                let valid = operand.as_bool().ok_or_else(|| invalid(&path, "should be a boolean"))?;
+                // Nulls and NaNs are invalid
+                let numeric = column.data_type() != DataType::Str && column.data_type() != DataType::Categorical;
+                keep(&|i| (column.is_null(i) || numeric && numbers[i].is_nan()) != valid)
+            },
+            _ => unreachable!(),
+        }
+    }
+    let selected: Vec<usize> = rows.iter().enumerate().filter(|(_, &keep)| keep).map(|(i, _)| i).collect();
+    Ok(table.take(&selected))
+}
+
+fn transform(table: &Table, value: &JsonValue, path: &str) -> Result<Table, SpecError> {
+    let map = value.as_object().ok_or_else(|| invalid(path, "should be an object"))?;
+    if let Some(predicate) = map.get("filter") {
+        object(value, path, &["filter"])?;
+        return filter(table, predicate, &join(path, "filter"));
+    }
+    if let Some(aggregates) = map.get("aggregate") {
+        object(value, path, &["aggregate", "groupby"])?;
+        let aggregates = array(aggregates, &join(path, "aggregate"))?.iter().enumerate()
+            .map(|(i, a)| {
+                let path = format!("{}.aggregate[{}]", path, i);
+                let map = object(a, &path, &["op", "field", "as"])?;
+                let op = aggregate_op(map.get("op").ok_or_else(|| invalid(&join(&path, "op"), "is required"))?, &join(&path, "op"))?;
+                let field = map.get("field").map(|f| string(f, &join(&path, "field"))).transpose()?.unwrap_or("");
+                let name = string(map.get("as").ok_or_else(|| invalid(&join(&path, "as"), "is required"))?, &join(&path, "as"))?;
+                Ok(Aggregate::new(op, field).alias(name))
+            })
+            .collect::<Result<Vec<Aggregate>, SpecError>>()?;
+        let keys = match map.get("groupby") {
+            Some(keys) => array(keys, &join(path, "groupby"))?.iter()
+                .map(|k| string(k, &join(path, "groupby")))
+                .collect::<Result<Vec<&str>, SpecError>>()?,
+            None => vec![],
+        };
+        return Ok(table.group_by(&keys)?.aggregate(&aggregates)?);
+    }
+    if let Some(bin) = map.get("bin") {
+        object(value, path, &["bin", "field", "as"])?;
+        let params = bin_params(bin, &join(path, "bin"))?.unwrap_or_default();
+        let field = string(map.get("field").ok_or_else(|| invalid(&join(path, "field"), "is required"))?, &join(path, "field"))?;
+        let as_path = join(path, "as");
+        let names: Vec<&str> = match map.get("as") {
+            Some(JsonValue::Array(names)) => names.iter().map(|n| string(n, &as_path)).collect::<Result<_, _>>()?,
+            Some(name) => vec![string(name, &as_path)?],
+            None => return Err(invalid(&as_path, "is required")),
+        };
+        let (starts, ends) = bin_column(table, field, &params)?;
+        let mut table = table.clone();
+        match names.as_slice() {
+            [start] => table.add_column(start, starts)?,
+            [start, end] => { table.add_column(start, starts)?; table.add_column(end, ends)?; },
+            _ => return Err(invalid(&as_path, "should be a name or the names of the start and the end")),
+        }
+        return Ok(table);
+    }
+    match map.keys().next() {
+        Some(key) => Err(SpecError::Unsupported(join(path, key))),
+        None => Err(invalid(path, "should have a filter, aggregate or bin")),
+    }
+}
+
+/// A field definition with the transforms it asks for
+struct Channel {
+    def: FieldDef,
+    aggregate: Option<AggregateOp>,
+    bin: Option<BinParams>,
+}
+
+fn channel(value: &JsonValue, path: &str, position: bool) -> Result<Channel, SpecError> {
+    let map = object(value, path, &["field", "type", "title", "aggregate", "bin", "scale", "axis", "legend"])?;
+    let aggregate = map.get("aggregate").map(|a| aggregate_op(a, &join(path, "aggregate"))).transpose()?;
+    let field = match map.get("field") {
+        Some(field) => string(field, &join(path, "field"))?,
+        None if aggregate == Some(AggregateOp::Count) => "",
+        None => return Err(invalid(&join(path, "field"), "is required")),
+    };
+    let type_path = join(path, "type");
+    let field_type = match map.get("type").map(|t| string(t, &type_path)).transpose()? {
+        Some("quantitative") => FieldType::Quantitative,
+        Some("temporal") => FieldType::Temporal,
+        Some("nominal") => FieldType::Nominal,
+        Some("ordinal") => FieldType::Ordinal,
+        None if aggregate == Some(AggregateOp::Count) => FieldType::Quantitative,
+        None => return Err(invalid(&type_path, "is required")),
+        Some(_) => return Err(invalid(&type_path, "should be quantitative, temporal, nominal or ordinal")),
+    };
+    let mut def = FieldDef::new(field, field_type);
+    if let Some(title) = map.get("title") {
+        def.title = Some(string(title, &join(path, "title"))?.to_string());
+    }
+    let bin = map.get("bin").map(|b| bin_params(b, &join(path, "bin"))).transpose()?.flatten();
+
+    if let Some(scale) = map.get("scale") {
+        let path = join(path, "scale");
+        let scale = object(scale, &path, &["type", "exponent", "domain", "zero"])?;
+        let exponent = scale.get("exponent").map(|e| number(e, &join(&path, "exponent"))).transpose()?;
+        if let Some(scale_type) = scale.get("type") {
+            let type_path = join(&path, "type");
+            def.scale = Some(match string(scale_type, &type_path)? {
+                "linear" => ScaleType::Linear,
+                "log" => ScaleType::Log,
+                "pow" => ScaleType::Pow(exponent.unwrap_or(1.0)),
+                "sqrt" => ScaleType::Sqrt,
+                "symlog" => ScaleType::Symlog,
+                _ => return Err(invalid(&type_path, "should be linear, log, pow, sqrt or symlog")),
+            });
+        }
+        if exponent.is_some() && !matches!(def.scale, Some(ScaleType::Pow(_))) {
+            return Err(invalid(&join(&path, "exponent"), "needs a pow scale"));
+        }
+        if let Some(domain) = scale.get("domain") {
+            def.domain = Some(interval(domain, &join(&path, "domain"))?);
+        }
+        if let Some(zero) = scale.get("zero") {
+            def.zero = Some(zero.as_bool().ok_or_else(|| invalid(&join(&path, "zero"), "should be a boolean"))?);
+        }
+    }
+
+    // Axes go with x and y, legends with the other channels
+    let (guide, other) = if position { ("axis", "legend") } else { ("legend", "axis") };
+    if map.contains_key(other) {
+        return Err(SpecError::Unsupported(join(path, other)));
+    }
+    match map.get(guide) {
+        Some(JsonValue::Null) => def.guide = false,
+        Some(value) => {
+            let path = join(path, guide);
+            let guide = object(value, &path, &["title"])?;
+            if let Some(title) = guide.get("title") {
+                def.title = Some(string(title, &join(&path, "title"))?.to_string());
+            }
+        },
+        None => {},
+    }
+    Ok(Channel { def, aggregate, bin })
+}
+
+/// Title of an aggregated field, like Vega-Lite's
+fn aggregate_title(op: AggregateOp, field: &str) -> String {
+    let name = match op {
+        AggregateOp::Count => return "Count of Records".to_string(),
+        AggregateOp::Distinct => "Distinct of",
+        AggregateOp::Sum => "Sum of",
+        AggregateOp::Mean => "Mean of",
+        AggregateOp::Median => "Median of",
+        AggregateOp::Min => "Min of",
+        AggregateOp::Max => "Max of",
+        AggregateOp::Stdev => "Stdev of",
+        AggregateOp::Quantile(p) if p < 0.5 => "Q1 of",
+        _ => "Q3 of",
+    };
+    format!("{} {}", name, field)
+}
+
+const CHANNELS: [&str; 7] = ["x", "y", "color", "size", "shape", "opacity", "tooltip"];
+
+/// The encoding and the data with the bins and aggregates of its channels
+fn encoding(value: &JsonValue, path: &str, mut table: Table) -> Result<(Encoding, Table), SpecError> {
+    let map = object(value, path, &CHANNELS)?;
+    let mut channels: Vec<(&str, Channel)> = vec![];
+    for name in &CHANNELS {
+        let path = join(path, name);
+        match (map.get(*name), *name) {
+            (None, _) => {},
+            (Some(JsonValue::Array(tooltips)), "tooltip") => {
+                for (i, tooltip) in tooltips.iter().enumerate() {
+                    channels.push((name, channel(tooltip, &format!("{}[{}]", path, i), false)?));
+                }
+            },
+            (Some(value), _) => channels.push((name, channel(value, &path, *name == "x" || *name == "y")?)),
+        }
+    }
+
+    for (_, channel) in channels.iter_mut() {
+        if let Some(params) = &channel.bin {
+            let name = format!("bin_{}", channel.def.field);
+            if table.column(&name).is_err() {
+                table.add_column(&name, bin_column(&table, &channel.def.field, params)?.0)?;
+            }
+            if channel.def.title.is_none() { channel.def.title = Some(format!("{} (binned)", channel.def.field)) };
+            channel.def.field = name;
+            channel.def.field_type = FieldType::Ordinal;
+        }
+    }
+
+    // Aggregated channels are grouped by the fields of the other channels
+    if channels.iter().any(|(_, c)| c.aggregate.is_some()) {
+        let mut keys: Vec<&str> = vec![];
+        let mut aggregates = vec![];
+        for (_, channel) in &channels {
+            match channel.aggregate {
+                None if !keys.contains(&channel.def.field.as_str()) => keys.push(&channel.def.field),
+                None => {},
+                Some(op) => aggregates.push(Aggregate::new(op, &channel.def.field)),
+            }
+        }
+        table = table.group_by(&keys)?.aggregate(&aggregates)?;
+        for (_, channel) in channels.iter_mut() {
+            if let Some(op) = channel.aggregate {
+                if channel.def.title.is_none() { channel.def.title = Some(aggregate_title(op, &channel.def.field)) };
+                channel.def.field = Aggregate::new(op, &channel.def.field).name;
+            }
+        }
+    }
+
+    let mut encoding = Encoding::default();
+    for (name, channel) in channels {
+        let def = Some(channel.def);
+        match name {
+            "x" => encoding.x = def,
+            "y" => encoding.y = def,
+            "color" => encoding.color = def,
+            "size" => encoding.size = def,
+            "shape" => encoding.shape = def,
+            "opacity" => encoding.opacity = def,
+            _ => encoding.tooltip.extend(def),
+        }
+    }
+    Ok((encoding, table))
+}
+
+impl Chart {
+    /// Chart of a Vega-Lite spec, see the supported subset in `chart::vega_lite`
+    pub fn from_vega_lite(json: &str) -> Result<Chart, SpecError> {
+        Chart::from_spec(json, None)
+    }
+
+    /// Chart of a Vega-Lite spec over `table`, instead of the data of the spec that can be left out.
+    /// The transforms, bins and aggregates of the spec are applied to `table`
+    pub fn from_vega_lite_with_data(json: &str, table: Table) -> Result<Chart, SpecError> {
+        Chart::from_spec(json, Some(table))
+    }
+
+    fn from_spec(json: &str, table: Option<Table>) -> Result<Chart, SpecError> {
+        let spec: JsonValue = serde_json::from_str(json).map_err(|e| SpecError::Json(e.to_string()))?;
+        let root = object(&spec, "", &["$schema", "description", "title", "width", "height", "background", "data", "mark", "encoding", "transform"])?;
+
+        let mut table = match table {
+            Some(table) => table,
+            None => data(root.get("data").ok_or_else(|| invalid("data", "is required"))?, "data")?,
+        };
+        if let Some(transforms) = root.get("transform") {
+            for (i, t) in array(transforms, "transform")?.iter().enumerate() {
+                table = transform(&table, t, &format!("transform[{}]", i))?;
+            }
+        }
+        let mark = mark(root.get("mark").ok_or_else(|| invalid("mark", "is required"))?, "mark")?;
+        let (encoding, table) = match root.get("encoding") {
+            Some(value) => encoding(value, "encoding", table)?,
+            None => (Encoding::default(), table),
+        };
+
+        let mut builder = ChartBuilder::default();
+        builder.data(table).mark(mark).encoding(encoding);
+        match root.get("title") {
+            Some(JsonValue::Object(_)) => {
+                let title = object(&root["title"], "title", &["text"])?;
+                let text = title.get("text").ok_or_else(|| invalid("title.text", "is required"))?;
+                builder.title(Some(string(text, "title.text")?.to_string()));
+            },
+            Some(title) => { builder.title(Some(string(title, "title")?.to_string())); },
+            None => {},
+        }
+        if let Some(width) = root.get("width") {
+            builder.width(pixels(width, "width")?);
+        }
+        if let Some(height) = root.get("height") {
+            builder.height(pixels(height, "height")?);
+        }
+        if let Some(background) = root.get("background") {
+            let color: Color = string(background, "background")?.parse().map_err(|_| invalid("background", "should be a color"))?;
+            builder.theme(Theme { background: color, ..Theme::default() });
+        }
+        builder.build().map_err(|e| invalid("", &e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Scene;
+    use crate::data::Value;
+    use crate::svg_backend::SVGContext;
+
+    const CARS: &str = r#"[
+        {"name": "ford", "weight": 3.5, "cylinders": 8, "origin": "us"},
+        {"name": "fiat", "weight": 1.2, "cylinders": 4, "origin": "eu"},
+        {"name": "audi", "weight": 2.5, "cylinders": 6, "origin": "eu"},
+        {"name": "seat", "weight": 2.0, "cylinders": 4, "origin": "eu"}
+    ]"#;
+
+    fn spec(body: &str) -> String {
+        format!(r#"{{"data": {{"values": {}}}, {}}}"#, CARS, body)
+    }
+
+    #[test]
+    fn parse_works() {
+        let chart = Chart::from_vega_lite(&spec(r#"
+            "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+            "title": {"text": "Cars"},
+            "width": 300,
+            "mark": {"type": "point"},
+            "encoding": {
+                "x": {"field": "weight", "type": "quantitative", "scale": {"type": "log", "zero": false}, "axis": {"title": "Weight"}},
+                "y": {"field": "cylinders", "type": "ordinal", "axis": null},
+                "color": {"field": "origin", "type": "nominal", "legend": {"title": "Origin"}},
+                "tooltip": [{"field": "name", "type": "nominal"}]
+            }"#)).unwrap();
+        assert_eq!(chart.mark, MarkType::Point);
+        assert_eq!((chart.width, chart.title.as_deref()), (300, Some("Cars")));
+        let x = chart.encoding.x.as_ref().unwrap();
+        assert_eq!((x.scale, x.zero, x.title.as_deref()), (Some(ScaleType::Log), Some(false), Some("Weight")));
+        assert!(!chart.encoding.y.as_ref().unwrap().guide);
+        assert_eq!(chart.encoding.color.as_ref().unwrap().title.as_deref(), Some("Origin"));
+        assert_eq!(chart.encoding.tooltip.len(), 1);
+        assert!(chart.scene::<SVGContext>().is_ok());
+
+        let chart = Chart::from_vega_lite(&spec(r#""width": 16384, "height": 16384, "mark": "point",
+            "encoding": {"x": {"field": "weight", "type": "quantitative"}, "y": {"field": "cylinders", "type": "quantitative"}}"#)).unwrap();
+        assert!(chart.scene::<SVGContext>().is_ok());
+    }
+
+    #[test]
+    fn aggregate_and_bin_work() {
+        let chart = Chart::from_vega_lite(&spec(r#"
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "origin", "type": "nominal"},
+                "y": {"field": "weight", "aggregate": "mean", "type": "quantitative"}
+            }"#)).unwrap();
+        assert_eq!(chart.data.names(), ["origin", "mean_weight"]);
+        let means = chart.data.column("mean_weight").unwrap().to_f64();
+        assert!((means[0] - 3.5).abs() < 1e-9 && (means[1] - 1.9).abs() < 1e-9);
+        assert_eq!(chart.encoding.y.as_ref().unwrap().title.as_deref(), Some("Mean of weight"));
+
+        let histogram = Chart::from_vega_lite(&spec(r#"
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "weight", "bin": {"step": 1}, "type": "quantitative"},
+                "y": {"aggregate": "count"}
+            }"#)).unwrap();
+        assert_eq!(histogram.data.names(), ["bin_weight", "count"]);
+        assert_eq!(histogram.data.column("bin_weight").unwrap().as_f64().unwrap(), [3.0, 1.0, 2.0]);
+        assert_eq!(histogram.data.column("count").unwrap().as_i64().unwrap(), [1, 1, 2]);
+        let scene: Scene<SVGContext> = histogram.scene().unwrap();
+        assert!(!scene.glyphs.is_empty());
+    }
+
+    #[test]
+    fn transforms_work() {
+        let chart = Chart::from_vega_lite(&spec(r#"
+            "mark": "bar",
+            "transform": [
+                {"filter": {"field": "origin", "equal": "eu"}},
+                {"filter": {"field": "weight", "range": [1.5, 3]}},
+                {"bin": {"maxbins": 2}, "field": "weight", "as": ["w0", "w1"]},
+                {"aggregate": [{"op": "count", "as": "n"}], "groupby": ["cylinders"]}
+            ],
+            "encoding": {
+                "x": {"field": "cylinders", "type": "ordinal"},
+                "y": {"field": "n", "type": "quantitative"}
+            }"#)).unwrap();
+        assert_eq!(chart.data.len(), 2);
+        assert_eq!(chart.data.row(0).get("cylinders"), Value::Int(6));
+
+        let one_of = Chart::from_vega_lite(&spec(r#"
+            "mark": "point",
+            "transform": [{"filter": {"field": "cylinders", "oneOf": [4, 8]}}],
+            "encoding": {"x": {"field": "weight", "type": "quantitative"}, "y": {"field": "weight", "type": "quantitative"}}
+            "#)).unwrap();
+        assert_eq!(one_of.data.len(), 3);
+    }
+
+    #[test]
+    fn with_data_works() {
+        let cars = || data(&serde_json::from_str(&format!(r#"{{"values": {}}}"#, CARS)).unwrap(), "data").unwrap();
+        let filtered = Chart::from_vega_lite_with_data(r#"{
+            "mark": "point",
+            "transform": [{"filter": {"field": "origin", "equal": "eu"}}],
+            "encoding": {"x": {"field": "weight", "type": "quantitative"}, "y": {"field": "cylinders", "type": "quantitative"}}
+            }"#, cars()).unwrap();
+        assert_eq!(filtered.data.len(), 3);
+
+        // The data of the spec is left out for the given one
+        let means = Chart::from_vega_lite_with_data(&format!(r#"{{"data": {{"values": []}}, {}}}"#, r#"
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "origin", "type": "nominal"},
+                "y": {"field": "weight", "aggregate": "mean", "type": "quantitative"}
+            }"#), cars()).unwrap();
+        assert_eq!(means.data.names(), ["origin", "mean_weight"]);
+        assert_eq!(means.data.len(), 2);
+        assert!(means.scene::<SVGContext>().is_ok());
+
+        assert_eq!(Chart::from_vega_lite(r#"{"mark": "point"}"#).unwrap_err().to_string(), "'data' is required");
+    }
+
+    #[test]
+    fn errors_are_clear() {
+        let error = |body: &str| Chart::from_vega_lite(&spec(body)).unwrap_err().to_string();
+        assert_eq!(error(r#""mark": "arc""#), "'mark' should be point, circle, square, bar, line or area");
+        assert_eq!(error(r#""mark": "bar", "encoding": {"x": {"field": "name", "type": "nominal", "timeUnit": "year"}}"#),
+                   "'encoding.x.timeUnit' is not supported");
+        assert_eq!(error(r#""mark": "bar", "encoding": {"x2": {"field": "name"}}"#), "'encoding.x2' is not supported");
+        assert_eq!(error(r#""mark": "bar", "transform": [{"filter": "datum.weight > 2"}]"#),
+                   "'transform[0].filter' should be a field predicate, expressions are not supported");
+        assert_eq!(error(r#""mark": "bar", "transform": [{"window": []}]"#), "'transform[0].window' is not supported");
+        assert_eq!(error(r#""mark": "bar", "encoding": {"color": {"field": "name", "type": "nominal", "axis": null}}"#),
+                   "'encoding.color.axis' is not supported");
+        assert_eq!(Chart::from_vega_lite(r#"{"data": {"url": "cars.json"}, "mark": "bar"}"#).unwrap_err().to_string(),
+                   "'data.url' is not supported");
+        assert!(matches!(Chart::from_vega_lite("{"), Err(SpecError::Json(_))));
+        assert_eq!(error(r#""mark": "bar", "width": -10"#), "'width' should be a positive whole number of pixels");
+        assert_eq!(error(r#""mark": "bar", "height": 10.5"#), "'height' should be a positive whole number of pixels");
+        assert_eq!(error(r#""mark": "bar", "width": 4000000000"#), "'width' should be at most 16384 pixels");
+        let binned = |bin: &str| format!(r#""mark": "bar", "encoding": {{"x": {{"field": "weight", "type": "quantitative", "bin": {}}}}}"#, bin);
+        assert_eq!(error(&binned(r#"{"maxbins": 0}"#)), "'encoding.x.bin.maxbins' should be a positive number");
+        assert_eq!(error(&binned(r#"{"step": -1}"#)), "'encoding.x.bin.step' should be a positive number");
+        assert!(Chart::from_vega_lite(&spec(&binned(r#"{"maxbins": 1e300}"#))).is_ok());
+    }
+}
@@ -0,0 +1,97 @@
+use std::rc::Rc;
+
+use crate::chart::{densities_by_group, density_bounds};
+use crate::core::Style;
+use crate::mark::Area;
+use crate::scale::{BandScale, BandScaleBuilder, LinearScale, Scale};
+use crate::transform::{Density, KdeParams};
+
+/// Distributions of a numeric variable per category, as densities mirrored around the center of each band
+#[derive(Debug, Builder)]
+#[builder(setter(into))]
+pub struct Violin {
+    pub category: Vec<String>,
+    pub value: Vec<f64>,
+    #[builder(default)]
+    pub kde: KdeParams,
+    /// Pixels where the bands of the categories are laid out
+    pub x_range: [f64; 2],
+    /// Pixels for the values, usually from bottom to top
+    pub y_range: [f64; 2],
+    /// Inner and outer padding of the bands
+    #[builder(default = "0.1")]
+    pub padding: f64,
+    #[builder(default)]
+    pub style: Rc<Style>,
+}
+
+/// Everything needed to draw a violin chart and its axes
+#[derive(Debug)]
+pub struct ViolinLayout {
+    pub categories: Vec<String>,
+    pub densities: Vec<Density>,
+    pub band_scale: BandScale<String>,
+    pub value_scale: LinearScale,
+    pub areas: Vec<Area>,
+}
+
+impl Violin {
+    pub fn layout(&self) -> Result<ViolinLayout, String> {
+        let (categories, densities) = densities_by_group(&self.category, &self.value, &self.kde);
+        let (domain, max_density) = density_bounds(&densities);
+
+        let band_scale: BandScale<String> = BandScaleBuilder::default()
+            .domain(categories.clone())
+            .range(self.x_range)
+            .padding(self.padding)
+            .build()?;
+        let value_scale = LinearScale { domain, range: self.y_range, clamp: false, round: false };
+
+        // The widest violin fills its band
+        let half = band_scale.bandwidth() / 2.0;
+        let k = if max_density > 0.0 { half / max_density } else { 0.0 };
+        let areas = categories.iter().zip(&densities)
+            .map(|(c, d)| {
+                let center = band_scale.call_one(c) + half;
+                Area::vertical(
+                    value_scale.call(&d.x),
+                    d.y.iter().map(|y| center - y * k).collect(),
+                    d.y.iter().map(|y| center + y * k).collect(),
+                    self.style.clone()
+                )
+            })
+            .collect();
+
+        Ok(ViolinLayout { categories, densities, band_scale, value_scale, areas })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::{Bandwidth, KdeParamsBuilder};
+
+    #[test]
+    fn layout_works() {
+        let violin = ViolinBuilder::default()
+            .category(vec!["a".to_string(), "b".to_string(), "a".to_string()])
+            .value(vec![0.0, 5.0, 1.0])
+            .kde(KdeParamsBuilder::default().bandwidth(Bandwidth::Fixed(1.0)).steps(11_usize).build().unwrap())
+            .x_range([0.0, 200.0])
+            .y_range([100.0, 0.0])
+            .padding(0.0)
+            .build().unwrap();
+        let layout = violin.layout().unwrap();
+        assert_eq!(layout.categories, ["a", "b"]);
+        assert_eq!(layout.value_scale.domain, [-3.0, 8.0]);
+        assert_eq!(layout.areas.len(), 2);
+
+        // b has a single value so it has the highest density and the widest violin
+        let b = &layout.areas[1];
+        assert_eq!(b.x0[5], 100.0);
+        assert_eq!(b.x1[5], 200.0);
+        assert_eq!(b.y0, b.y1);
+        let a = &layout.areas[0];
+        assert!(a.x1.iter().zip(&a.x0).all(|(x1, x0)| ((x1 + x0) / 2.0 - 50.0).abs() < 1e-9));
+    }
+}
@@ -14,10 +14,6 @@ pub struct Color {
 }
 
 impl Color {
-    pub fn to_string(&self) -> String {
-        format!("rgba({:},{:},{:},{:})", self.r, self.g, self.b, self.a)
-    }
-
     pub fn rgba(color: &Option<Self>) -> String {
         match color { 
             Some(c) => c.to_string(), 
@@ -37,6 +33,12 @@ impl Color {
     }
 }
 
+impl Display for Color {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "rgba({:},{:},{:},{:})", self.r, self.g, self.b, self.a)
+    }
+}
+
 impl Default for Color {
     fn default() -> Self {
         BLACK
@@ -94,6 +96,42 @@ impl FromStr for Color {
     }
 }
 
+/// Colors are stored as hex strings, "#rrggbb" when opaque or "#rrggbbaa". The exception is an
+/// alpha that does not fit in 8 bits, which hex would round, it is kept exactly as "rgba(r,g,b,a)"
+#[cfg(feature = "serde")]
+impl serde::Serialize for Color {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let alpha = self.a * 255.0;
+        if self.a.in_delta(1.0) {
+            serializer.serialize_str(&self.to_hex()[..7])
+        } else if (alpha.round() / 255.0).in_delta(self.a) {
+            serializer.serialize_str(&self.to_hex())
+        } else {
+            serializer.collect_str(self)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Color {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let text = String::deserialize(deserializer)?;
+        if let Some(rgba) = text.strip_prefix("rgba(").and_then(|t| t.strip_suffix(')')) {
+            let parts: Vec<&str> = rgba.split(',').map(str::trim).collect();
+            if let [r, g, b, a] = parts[..] {
+                let channel = |v: &str| v.parse::<u8>().map_err(D::Error::custom);
+                let a: f32 = a.parse().map_err(D::Error::custom)?;
+                if !(0.0..=1.0).contains(&a) {
+                    return Err(D::Error::custom(format!("the alpha of {} is not in [0, 1]", text)));
+                }
+                return Ok(Color { r: channel(r)?, g: channel(g)?, b: channel(b)?, a });
+            }
+        }
+        text.parse().map_err(D::Error::custom)
+    }
+}
+
 ///
 /// ParseColorError
 /// 
@@ -333,4 +371,20 @@ mod tests {
 
         Ok(())
     }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_works() -> Result<(), Box<dyn Error>> {
+        let trans = Color{r: 255, g: 100, b: 23, a: 0.5};
+        let third = Color{r: 255, g: 100, b: 23, a: 0.3};
+        let json = serde_json::to_string(&vec![BLUE, Color::from_hex("#ff641780")?, trans, third])?;
+        assert_eq!(json, r##"["#0000ff","#ff641780","rgba(255,100,23,0.5)","rgba(255,100,23,0.3)"]"##);
+        let colors: Vec<Color> = serde_json::from_str(&json)?;
+        // Exactly, also the alphas that hex would round
+        assert_eq!(colors, [BLUE, Color::from_hex("#ff641780")?, trans, third]);
+        assert_eq!(colors[3].a, 0.3);
+        assert!(serde_json::from_str::<Color>(r#""blue""#).is_err());
+        assert!(serde_json::from_str::<Color>(r#""rgba(1,2,3,1.5)""#).is_err());
+        assert!(serde_json::from_str::<Color>(r#""rgba(1,2,300,0.5)""#).is_err());
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -1,33 +1,74 @@
 pub use crate::color::Color;
+pub use crate::paint::Paint;
 pub use crate::mark::{
     Circle, CircleBuilder, Rect, RectBuilder, Segment, SegmentBuilder,
-    Line, LineBuilder, Text, TextBuilder, TextAlign
+    Line, LineBuilder, Area, AreaBuilder, Text, TextBuilder, TextAlign, TextBaseline, TextOverflow,
+    Channel, CircleBatch, CircleBatchBuilder, RectBatch, RectBatchBuilder,
+    SymbolBatch, SymbolBatchBuilder, SymbolShape, Mark
 };
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Canvas {
     pub width: u32,
     pub height: u32,
     pub background: Color
 }
 
-#[derive(Debug, Builder)]
+/// Shape of the ends of open strokes
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum LineCap { #[default] Butt, Round, Square }
+
+/// Shape of the corners of strokes
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum LineJoin { #[default] Miter, Round, Bevel }
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Builder)]
 #[builder(setter(into))]
 pub struct Style {
     #[builder(default)]
-    pub fill: Option<Color>,
+    pub fill: Option<Paint>,
+    #[builder(default)]
+    pub stroke: Option<Paint>,
+    #[builder(default = "1.0")]
+    pub stroke_width: f64,
+    /// Lengths of the dashes and the gaps between them, alternately. Solid when empty
+    #[builder(default)]
+    pub dash_array: Vec<f64>,
+    /// Distance into the dash pattern where the stroke starts
+    #[builder(default)]
+    pub dash_offset: f64,
     #[builder(default)]
-    pub stroke: Option<Color>,
+    pub line_cap: LineCap,
+    #[builder(default)]
+    pub line_join: LineJoin,
+    /// Miter joins longer than this ratio to the stroke width are beveled
+    #[builder(default = "4.0")]
+    pub miter_limit: f64,
+    /// Multiplies the alpha of the fill
+    #[builder(default = "1.0")]
+    pub fill_opacity: f64,
+    /// Multiplies the alpha of the stroke
     #[builder(default = "1.0")]
-    pub stroke_width: f64
+    pub stroke_opacity: f64,
 }
 
 impl Default for Style {
     fn default() -> Style { 
         Style {
-            fill: Some(Color{r: 0, g: 0, b: 0, a: 1.0}), 
+            fill: Some(Paint::Solid(Color{r: 0, g: 0, b: 0, a: 1.0})), 
             stroke: None,
-            stroke_width: 1.0
+            stroke_width: 1.0,
+            dash_array: vec![],
+            dash_offset: 0.0,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Miter,
+            miter_limit: 4.0,
+            fill_opacity: 1.0,
+            stroke_opacity: 1.0,
         }
     }
 }
@@ -41,7 +82,7 @@ pub struct Scene<T> {
 impl<T> Scene<T> {
     pub fn new(canvas: Canvas) -> Scene<T> {
         Scene{
-            canvas: canvas,
+            canvas,
             glyphs: vec![]
         }
     }
@@ -52,7 +93,205 @@ impl<T> Scene<T> {
 }
 
 
+/// Scenes are stored as their canvas and marks, `{"canvas": {..}, "marks": [{"type": "circle", ..}]}`.
+/// Glyphs that are not marks can not be stored. JSON has no NaN nor infinity, so numbers must be finite
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for Scene<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeStruct};
+        let marks = self.glyphs.iter()
+            .map(|glyph| glyph.mark().ok_or_else(|| S::Error::custom("only marks can be serialized")))
+            .collect::<Result<Vec<Mark>, S::Error>>()?;
+        // Checked before writing anything, serde_json would write them as null
+        serde::Serialize::serialize(&marks, finite::Finite).map_err(S::Error::custom)?;
+        let mut state = serializer.serialize_struct("Scene", 2)?;
+        state.serialize_field("canvas", &self.canvas)?;
+        state.serialize_field("marks", &marks)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: 'static> serde::Deserialize<'de> for Scene<T> where Mark: Glyph<Context=T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        struct Fields { canvas: Canvas, marks: Vec<Mark> }
+
+        let fields = Fields::deserialize(deserializer)?;
+        let mut scene = Scene::new(fields.canvas);
+        for mark in fields.marks {
+            scene.add(Box::new(mark));
+        }
+        Ok(scene)
+    }
+}
+
+/// A serializer that writes nothing, it fails on the first number that isn't finite
+#[cfg(feature = "serde")]
+mod finite {
+    use std::fmt::{Display, Formatter};
+    use serde::Serialize;
+    use serde::ser::{self, Serializer};
+
+    #[derive(Debug)]
+    pub struct NonFinite(String);
+
+    impl Display for NonFinite {
+        fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl std::error::Error for NonFinite {}
+
+    impl ser::Error for NonFinite {
+        fn custom<M: Display>(message: M) -> NonFinite {
+            NonFinite(message.to_string())
+        }
+    }
+
+    fn check(x: f64) -> Result<(), NonFinite> {
+        if x.is_finite() { Ok(()) } else { Err(NonFinite(format!("{} can not be stored, numbers must be finite", x))) }
+    }
+
+    pub struct Finite;
+
+    macro_rules! ignore {
+        ($($method:ident: $t:ty),*) => {
+            $(fn $method(self, _: $t) -> Result<(), NonFinite> { Ok(()) })*
+        };
+    }
+
+    impl Serializer for Finite {
+        type Ok = ();
+        type Error = NonFinite;
+        type SerializeSeq = Finite;
+        type SerializeTuple = Finite;
+        type SerializeTupleStruct = Finite;
+        type SerializeTupleVariant = Finite;
+        type SerializeMap = Finite;
+        type SerializeStruct = Finite;
+        type SerializeStructVariant = Finite;
+
+        ignore!(serialize_bool: bool, serialize_i8: i8, serialize_i16: i16, serialize_i32: i32, serialize_i64: i64,
+                serialize_u8: u8, serialize_u16: u16, serialize_u32: u32, serialize_u64: u64,
+                serialize_char: char, serialize_str: &str, serialize_bytes: &[u8], serialize_unit_struct: &'static str);
+
+        fn serialize_f32(self, x: f32) -> Result<(), NonFinite> { check(x as f64) }
+        fn serialize_f64(self, x: f64) -> Result<(), NonFinite> { check(x) }
+        fn serialize_none(self) -> Result<(), NonFinite> { Ok(()) }
+        fn serialize_unit(self) -> Result<(), NonFinite> { Ok(()) }
+        fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), NonFinite> { value.serialize(self) }
+        fn serialize_unit_variant(self, _: &'static str, _: u32, _: &'static str) -> Result<(), NonFinite> { Ok(()) }
+        fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T) -> Result<(), NonFinite> {
+            value.serialize(self)
+        }
+        fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _: &'static str, _: u32, _: &'static str, value: &T) -> Result<(), NonFinite> {
+            value.serialize(self)
+        }
+        fn serialize_seq(self, _: Option<usize>) -> Result<Finite, NonFinite> { Ok(self) }
+        fn serialize_tuple(self, _: usize) -> Result<Finite, NonFinite> { Ok(self) }
+        fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Finite, NonFinite> { Ok(self) }
+        fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Finite, NonFinite> { Ok(self) }
+        fn serialize_map(self, _: Option<usize>) -> Result<Finite, NonFinite> { Ok(self) }
+        fn serialize_struct(self, _: &'static str, _: usize) -> Result<Finite, NonFinite> { Ok(self) }
+        fn serialize_struct_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Finite, NonFinite> { Ok(self) }
+    }
+
+    macro_rules! compound {
+        ($($trait:ident: $method:ident),*) => {
+            $(impl ser::$trait for Finite {
+                type Ok = ();
+                type Error = NonFinite;
+                fn $method<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), NonFinite> { value.serialize(Finite) }
+                fn end(self) -> Result<(), NonFinite> { Ok(()) }
+            })*
+        };
+    }
+
+    compound!(SerializeSeq: serialize_element, SerializeTuple: serialize_element,
+              SerializeTupleStruct: serialize_field, SerializeTupleVariant: serialize_field);
+
+    impl ser::SerializeMap for Finite {
+        type Ok = ();
+        type Error = NonFinite;
+        fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), NonFinite> { key.serialize(Finite) }
+        fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), NonFinite> { value.serialize(Finite) }
+        fn end(self) -> Result<(), NonFinite> { Ok(()) }
+    }
+
+    macro_rules! fields {
+        ($($trait:ident),*) => {
+            $(impl ser::$trait for Finite {
+                type Ok = ();
+                type Error = NonFinite;
+                fn serialize_field<T: ?Sized + Serialize>(&mut self, _: &'static str, value: &T) -> Result<(), NonFinite> { value.serialize(Finite) }
+                fn end(self) -> Result<(), NonFinite> { Ok(()) }
+            })*
+        };
+    }
+
+    fields!(SerializeStruct, SerializeStructVariant);
+}
+
 pub trait Glyph {
     type Context;
     fn draw(& self, ctx: &mut Self::Context);
-}
\ No newline at end of file
+
+    /// The mark drawn by this glyph, to store scenes
+    fn mark(&self) -> Option<Mark> { None }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use std::rc::Rc;
+    use super::*;
+    use crate::color::{RED, WHITE};
+    use crate::paint::{LinearGradient, Pattern, PatternKind, RadialGradient, stops};
+    use crate::svg_backend::SVGContext;
+    use crate::transform::Downsample;
+
+    #[test]
+    fn scene_round_trips() {
+        let style = Rc::new(Style { fill: Some(Color { r: 10, g: 20, b: 30, a: 0.3 }.into()), stroke: Some(RED.into()), stroke_width: 0.1, dash_array: vec![4.0, 2.0], line_cap: LineCap::Round, ..Style::default() });
+        let mut scene: Scene<SVGContext> = Scene::new(Canvas { width: 300, height: 200, background: WHITE });
+        scene.add(Box::new(Circle { x: 1.0 / 3.0, y: 2.0, radius: 1e-10, style: style.clone() }));
+        scene.add(Box::new(LineBuilder::default()
+            .x(vec![0.1, 0.2]).y(vec![1e300, -0.0])
+            .downsample(Some(Downsample::Rdp(0.5)))
+            .build().unwrap()));
+        scene.add(Box::new(Text { x: 0.0, y: 0.0, text: "\"π\"".into(), align: TextAlign::End, angle: -90.0, style, ..Text::default() }));
+        scene.add(Box::new(SymbolBatchBuilder::default()
+            .x(vec![1.0, 2.0]).y(vec![3.0, 4.0])
+            .size(vec![5.0, 6.0])
+            .shape(SymbolShape::Diamond)
+            .fill(vec![Some(RED), None])
+            .tooltip(Some(vec!["a".into(), "b".into()]))
+            .build().unwrap()));
+        for fill in [RadialGradient::centered(stops(&[WHITE, RED])).into(), Pattern { kind: PatternKind::Dots, ..Pattern::default() }.into()] {
+            let style = Rc::new(Style { fill: Some(fill), stroke: Some(LinearGradient::horizontal(stops(&[RED, WHITE])).into()), ..Style::default() });
+            scene.add(Box::new(Rect { x: 0.0, y: 0.0, width: 1.0, height: 1.0, style }));
+        }
+
+        let json = serde_json::to_string(&scene).unwrap();
+        assert!(json.starts_with(r##"{"canvas":{"width":300,"height":200,"background":"#ffffff"},"marks":[{"type":"circle","x":0.3333333333333333"##));
+
+        let loaded: Scene<SVGContext> = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.canvas, scene.canvas);
+        let marks = |scene: &Scene<SVGContext>| scene.glyphs.iter().map(|g| g.mark()).collect::<Vec<_>>();
+        assert_eq!(marks(&loaded), marks(&scene));
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+    }
+
+    #[test]
+    fn non_finite_numbers_are_rejected() {
+        let mut scene: Scene<SVGContext> = Scene::new(Canvas { width: 300, height: 200, background: WHITE });
+        scene.add(Box::new(LineBuilder::default().x(vec![0.0, 1.0]).y(vec![0.0, f64::NAN]).build().unwrap()));
+        let error = serde_json::to_string(&scene).unwrap_err().to_string();
+        assert_eq!(error, "NaN can not be stored, numbers must be finite");
+
+        let style = Rc::new(Style { stroke_width: f64::INFINITY, ..Style::default() });
+        scene.glyphs[0] = Box::new(Circle { x: 0.0, y: 0.0, radius: 1.0, style });
+        assert!(serde_json::to_string(&scene).is_err());
+    }
+}
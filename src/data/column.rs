@@ -0,0 +1,451 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::data::DataError;
+use crate::scale::DateTime;
+use crate::utils::Extend;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DataType { Float, Int, Str, Categorical, DateTime, Bool }
+
+/// Values of a column. Null slots hold a placeholder (NaN for floats and dates)
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnData {
+    Float(Vec<f64>),
+    Int(Vec<i64>),
+    Str(Vec<String>),
+    /// Dictionary encoded strings, each code is an index in `categories`
+    Categorical { codes: Vec<u32>, categories: Vec<String> },
+    DateTime(Vec<DateTime>),
+    Bool(Vec<bool>),
+}
+
+impl ColumnData {
+    pub fn len(&self) -> usize {
+        match self {
+            ColumnData::Float(v) => v.len(),
+            ColumnData::Int(v) => v.len(),
+            ColumnData::Str(v) => v.len(),
+            ColumnData::Categorical { codes, .. } => codes.len(),
+            ColumnData::DateTime(v) => v.len(),
+            ColumnData::Bool(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Dictionary encodes the strings, categories are kept in first-seen order.
+    /// Nones are not a category, they get the code 0 as placeholder
+    pub fn categorical<S: AsRef<str>>(values: impl IntoIterator<Item = Option<S>>) -> ColumnData {
+        let mut categories: Vec<String> = vec![];
+        let mut index: HashMap<String, u32> = HashMap::new();
+        let codes = values.into_iter()
+            .map(|x| match x {
+                None => 0,
+                Some(x) => *index.entry(x.as_ref().to_string()).or_insert_with(|| {
+                    categories.push(x.as_ref().to_string());
+                    categories.len() as u32 - 1
+                })
+            })
+            .collect();
+        ColumnData::Categorical { codes, categories }
+    }
+
+    pub fn data_type(&self) -> DataType {
+        match self {
+            ColumnData::Float(_) => DataType::Float,
+            ColumnData::Int(_) => DataType::Int,
+            ColumnData::Str(_) => DataType::Str,
+            ColumnData::Categorical { .. } => DataType::Categorical,
+            ColumnData::DateTime(_) => DataType::DateTime,
+            ColumnData::Bool(_) => DataType::Bool,
+        }
+    }
+}
+
+/// A single value of a column
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Float(f64),
+    Int(i64),
+    Str(String),
+    DateTime(DateTime),
+    Bool(bool),
+}
+
+impl Value {
+    /// Numeric representation of the value: dates are millis and booleans 0 or 1
+    pub fn to_f64(&self) -> Option<f64> {
+        match self {
+            Value::Float(x) => Some(*x),
+            Value::Int(x) => Some(*x as f64),
+            Value::DateTime(x) => Some(x.0),
+            Value::Bool(x) => Some(if *x { 1.0 } else { 0.0 }),
+            Value::Null | Value::Str(_) => None,
+        }
+    }
+}
+
+/// A typed column with an optional null mask.
+/// Cloning and slicing share the underlying values instead of copying them
+#[derive(Debug, Clone)]
+pub struct Column {
+    data: Rc<ColumnData>,
+    /// `false` marks a null
+    valid: Option<Rc<Vec<bool>>>,
+    offset: usize,
+    len: usize,
+}
+
+impl Column {
+    pub fn new(data: ColumnData) -> Column {
+        let len = data.len();
+        Column { data: Rc::new(data), valid: None, offset: 0, len }
+    }
+
+    /// Column whose rows are null where `valid` is false. The mask needs one entry per row
+    pub fn with_validity(data: ColumnData, valid: Vec<bool>) -> Result<Column, DataError> {
+        if data.len() != valid.len() {
+            return Err(DataError::LengthMismatch { column: "validity mask".into(), expected: data.len(), found: valid.len() });
+        }
+        Ok(Column::masked(data, valid))
+    }
+
+    /// `with_validity` for masks known to have the length of the data
+    fn masked(data: ColumnData, valid: Vec<bool>) -> Column {
+        let len = data.len();
+        let valid = if valid.iter().all(|&v| v) { None } else { Some(Rc::new(valid)) };
+        Column { data: Rc::new(data), valid, offset: 0, len }
+    }
+
+    /// Dictionary encodes the strings, categories are kept in first-seen order
+    pub fn categorical<S: AsRef<str>>(values: &[S]) -> Column {
+        Column::new(ColumnData::categorical(values.iter().map(Some)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn data_type(&self) -> DataType {
+        self.data.data_type()
+    }
+
+    pub fn is_null(&self, row: usize) -> bool {
+        match &self.valid {
+            Some(valid) => !valid[self.offset + row],
+            None => false
+        }
+    }
+
+    pub fn null_count(&self) -> usize {
+        (0..self.len).filter(|&i| self.is_null(i)).count()
+    }
+
+    pub fn as_f64(&self) -> Option<&[f64]> {
+        match &*self.data {
+            ColumnData::Float(v) => Some(&v[self.offset..self.offset + self.len]),
+            _ => None
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<&[i64]> {
+        match &*self.data {
+            ColumnData::Int(v) => Some(&v[self.offset..self.offset + self.len]),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&[String]> {
+        match &*self.data {
+            ColumnData::Str(v) => Some(&v[self.offset..self.offset + self.len]),
+            _ => None
+        }
+    }
+
+    pub fn as_codes(&self) -> Option<&[u32]> {
+        match &*self.data {
+            ColumnData::Categorical { codes, .. } => Some(&codes[self.offset..self.offset + self.len]),
+            _ => None
+        }
+    }
+
+    /// All the categories of a categorical column, also those outside of a slice
+    pub fn categories(&self) -> Option<&[String]> {
+        match &*self.data {
+            ColumnData::Categorical { categories, .. } => Some(categories),
+            _ => None
+        }
+    }
+
+    pub fn as_datetime(&self) -> Option<&[DateTime]> {
+        match &*self.data {
+            ColumnData::DateTime(v) => Some(&v[self.offset..self.offset + self.len]),
+            _ => None
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<&[bool]> {
+        match &*self.data {
+            ColumnData::Bool(v) => Some(&v[self.offset..self.offset + self.len]),
+            _ => None
+        }
+    }
+
+    pub fn get(&self, row: usize) -> Value {
+        assert!(row < self.len, "row {} out of bounds for a column of {}", row, self.len);
+        if self.is_null(row) { return Value::Null };
+        let i = self.offset + row;
+        match &*self.data {
+            ColumnData::Float(v) => Value::Float(v[i]),
+            ColumnData::Int(v) => Value::Int(v[i]),
+            ColumnData::Str(v) => Value::Str(v[i].clone()),
+            ColumnData::Categorical { codes, categories } => Value::Str(categories[codes[i] as usize].clone()),
+            ColumnData::DateTime(v) => Value::DateTime(v[i].clone()),
+            ColumnData::Bool(v) => Value::Bool(v[i]),
+        }
+    }
+
+    /// Numeric values of the column, nulls and strings become NaN
+    pub fn to_f64(&self) -> Vec<f64> {
+        if let Some(v) = self.as_f64() {
+            return (0..self.len).map(|i| if self.is_null(i) { f64::NAN } else { v[i] }).collect();
+        }
+        (0..self.len).map(|i| self.get(i).to_f64().unwrap_or(f64::NAN)).collect()
+    }
+
+    /// String values of the column, nulls become empty strings
+    pub fn to_strings(&self) -> Vec<String> {
+        (0..self.len)
+            .map(|i| match self.get(i) {
+                Value::Null => String::new(),
+                Value::Float(x) => x.to_string(),
+                Value::Int(x) => x.to_string(),
+                Value::Str(x) => x,
+                Value::DateTime(x) => x.0.to_string(),
+                Value::Bool(x) => x.to_string(),
+            })
+            .collect()
+    }
+
+    /// Min and max of the numeric values, ignoring nulls
+    pub fn extent(&self) -> Option<[f64; 2]> {
+        let values: Vec<f64> = self.to_f64().into_iter().filter(|x| !x.is_nan()).collect();
+        if values.is_empty() { return None };
+        Some(f64::extend(&values))
+    }
+
+    /// Zero-copy view of `len` rows starting at `offset`
+    pub fn slice(&self, offset: usize, len: usize) -> Column {
+        assert!(offset + len <= self.len, "slice out of bounds");
+        Column { data: self.data.clone(), valid: self.valid.clone(), offset: self.offset + offset, len }
+    }
+
+    /// New column with the given rows, in that order
+    pub fn take(&self, rows: &[usize]) -> Column {
+        let idx: Vec<usize> = rows.iter()
+            .map(|&r| {
+                assert!(r < self.len, "row {} out of bounds for a column of {}", r, self.len);
+                self.offset + r
+            })
+            .collect();
+        fn gather<T: Clone>(v: &[T], idx: &[usize]) -> Vec<T> {
+            idx.iter().map(|&i| v[i].clone()).collect()
+        }
+        let data = match &*self.data {
+            ColumnData::Float(v) => ColumnData::Float(gather(v, &idx)),
+            ColumnData::Int(v) => ColumnData::Int(gather(v, &idx)),
+            ColumnData::Str(v) => ColumnData::Str(gather(v, &idx)),
+            ColumnData::Categorical { codes, categories } => ColumnData::Categorical {
+                codes: gather(codes, &idx),
+                categories: categories.clone()
+            },
+            ColumnData::DateTime(v) => ColumnData::DateTime(gather(v, &idx)),
+            ColumnData::Bool(v) => ColumnData::Bool(gather(v, &idx)),
+        };
+        match &self.valid {
+            Some(valid) => Column::masked(data, gather(valid, &idx)),
+            None => Column::new(data)
+        }
+    }
+
+    /// Compares two rows, nulls (and NaN) go last
+    pub fn compare(&self, a: usize, b: usize) -> Ordering {
+        match (self.is_null(a), self.is_null(b)) {
+            (true, true) => return Ordering::Equal,
+            (true, false) => return Ordering::Greater,
+            (false, true) => return Ordering::Less,
+            _ => {}
+        }
+        let (i, j) = (self.offset + a, self.offset + b);
+        let floats = |x: f64, y: f64| match (x.is_nan(), y.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            _ => x.partial_cmp(&y).unwrap()
+        };
+        match &*self.data {
+            ColumnData::Float(v) => floats(v[i], v[j]),
+            ColumnData::Int(v) => v[i].cmp(&v[j]),
+            ColumnData::Str(v) => v[i].cmp(&v[j]),
+            ColumnData::Categorical { codes, categories } => {
+                categories[codes[i] as usize].cmp(&categories[codes[j] as usize])
+            },
+            ColumnData::DateTime(v) => floats(v[i].0, v[j].0),
+            ColumnData::Bool(v) => v[i].cmp(&v[j]),
+        }
+    }
+}
+
+impl PartialEq for Column {
+    fn eq(&self, other: &Column) -> bool {
+        self.len == other.len
+            && self.data_type() == other.data_type()
+            && (0..self.len).all(|i| self.get(i) == other.get(i))
+    }
+}
+
+impl From<ColumnData> for Column {
+    fn from(data: ColumnData) -> Self {
+        Column::new(data)
+    }
+}
+
+macro_rules! column_from {
+    ($t:ty, $variant:ident, $null:expr) => {
+        impl From<Vec<$t>> for Column {
+            fn from(values: Vec<$t>) -> Self {
+                Column::new(ColumnData::$variant(values))
+            }
+        }
+
+        impl From<Vec<Option<$t>>> for Column {
+            fn from(values: Vec<Option<$t>>) -> Self {
+                let valid = values.iter().map(|x| x.is_some()).collect();
+                let values = values.into_iter().map(|x| x.unwrap_or($null)).collect();
+                Column::masked(ColumnData::$variant(values), valid)
+            }
+        }
+    };
+}
+
+column_from!(f64, Float, f64::NAN);
+column_from!(i64, Int, 0);
+column_from!(String, Str, String::new());
+column_from!(DateTime, DateTime, DateTime(f64::NAN));
+column_from!(bool, Bool, false);
+
+impl From<Vec<&str>> for Column {
+    fn from(values: Vec<&str>) -> Self {
+        Column::new(ColumnData::Str(values.into_iter().map(String::from).collect()))
+    }
+}
+
+/// Extent of the numeric values, ignoring nulls. Lets continuous scales take a column as domain
+impl From<&Column> for [f64; 2] {
+    fn from(column: &Column) -> Self {
+        f64::extend(&column.to_f64())
+    }
+}
+
+/// Extent of the dates, ignoring nulls. Lets time scales take a column as domain
+impl From<&Column> for [DateTime; 2] {
+    fn from(column: &Column) -> Self {
+        let values: Vec<DateTime> = column.to_f64().into_iter().map(DateTime).collect();
+        DateTime::extend(&values)
+    }
+}
+
+/// All the rows, see `to_f64`
+impl From<&Column> for Vec<f64> {
+    fn from(column: &Column) -> Self {
+        column.to_f64()
+    }
+}
+
+/// All the rows, see `to_strings`. A band scale wants the distinct values as domain instead
+impl From<&Column> for Vec<String> {
+    fn from(column: &Column) -> Self {
+        column.to_strings()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::BarChartBuilder;
+    use crate::scale::{LinearScaleBuilder, TimeScaleBuilder};
+
+    #[test]
+    fn nulls_work() {
+        let column: Column = vec![Some(1.0), None, Some(3.0)].into();
+        assert_eq!(column.null_count(), 1);
+        assert_eq!(column.get(1), Value::Null);
+        assert!(column.as_f64().unwrap()[1].is_nan());
+        assert_eq!(column.extent(), Some([1.0, 3.0]));
+    }
+
+    #[test]
+    fn slice_works() {
+        let column: Column = vec![Some(1), None, Some(3), Some(4)].into();
+        let slice = column.slice(1, 2);
+        assert_eq!(slice.len(), 2);
+        assert_eq!(slice.as_i64(), Some(&[0, 3][..]));
+        assert!(slice.is_null(0));
+        assert_eq!(slice.slice(1, 1).get(0), Value::Int(3));
+        assert!(Rc::ptr_eq(&column.data, &slice.data));
+        let taken = slice.take(&[1, 0]);
+        assert_eq!(taken.get(0), Value::Int(3));
+        assert!(taken.is_null(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn take_checks_bounds() {
+        let column: Column = vec![1, 2, 3, 4].into();
+        // Row 3 of the data, but past the end of the slice
+        column.slice(0, 2).take(&[2]);
+    }
+
+    #[test]
+    fn validity_works() {
+        let column = Column::with_validity(ColumnData::Int(vec![1, 2]), vec![true, false]).unwrap();
+        assert_eq!(column.get(1), Value::Null);
+        let err = Column::with_validity(ColumnData::Int(vec![1, 2]), vec![true]);
+        assert_eq!(err, Err(DataError::LengthMismatch { column: "validity mask".into(), expected: 2, found: 1 }));
+    }
+
+    #[test]
+    fn categorical_works() {
+        let column = Column::categorical(&["b", "a", "b", "c"]);
+        assert_eq!(column.data_type(), DataType::Categorical);
+        assert_eq!(column.as_codes(), Some(&[0, 1, 0, 2][..]));
+        assert_eq!(column.categories().unwrap(), ["b", "a", "c"]);
+        assert_eq!(column.get(3), Value::Str("c".into()));
+        assert_eq!(column.take(&[3, 0]).to_strings(), ["c", "b"]);
+    }
+
+    #[test]
+    fn builders_take_columns() {
+        let value: Column = vec![Some(3.0), None, Some(-1.0)].into();
+        let scale = LinearScaleBuilder::<f64>::default().domain(&value).build().unwrap();
+        assert_eq!(scale.domain, [-1.0, 3.0]);
+
+        let dates: Column = vec![Some(DateTime(5.0)), None, Some(2.0.into())].into();
+        let scale = TimeScaleBuilder::<f64>::default().domain(&dates).build().unwrap();
+        assert_eq!(scale.domain, [DateTime(2.0), DateTime(5.0)]);
+
+        let category = Column::categorical(&["a", "b", "a"]);
+        let chart = BarChartBuilder::default().category(&category).value(&value).build().unwrap();
+        assert_eq!(chart.category, ["a", "b", "a"]);
+        assert!(chart.value[1].is_nan());
+    }
+}
@@ -0,0 +1,259 @@
+//!
+//! Loads CSV and JSON into a `Table`, inferring the type of each column
+//!
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use chrono::{DateTime as ChDateTime, NaiveDate, NaiveDateTime, Utc};
+use serde_json::{self, Value as JsonValue};
+
+use crate::data::{Column, ColumnData, DataError, DataType, Table};
+use crate::scale::DateTime;
+
+/// Date formats tried after the custom ones, besides RFC 3339
+const ISO_DATE_FORMATS: [&str; 4] = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d"];
+
+#[derive(Debug, Builder, Clone)]
+#[builder(setter(into))]
+pub struct ReadOptions {
+    /// Only used by CSV
+    #[builder(default = "b','")]
+    pub delimiter: u8,
+    /// Cells (after trimming) read as null
+    #[builder(default = r#"vec!["".into(), "NA".into(), "N/A".into(), "null".into()]"#)]
+    pub null_values: Vec<String>,
+    /// chrono formats like "%b %e %Y", tried before the ISO 8601 ones
+    #[builder(default)]
+    pub date_formats: Vec<String>,
+    /// Column types that skip the inference
+    #[builder(default)]
+    pub types: HashMap<String, DataType>,
+    /// String columns with at most this ratio of unique values per row are categorical
+    #[builder(default = "0.5")]
+    pub categorical_ratio: f64,
+}
+
+impl Default for ReadOptions {
+    fn default() -> ReadOptions {
+        ReadOptionsBuilder::default().build().unwrap()
+    }
+}
+
+fn parse_bool(s: &str) -> Option<bool> {
+    match s.to_lowercase().as_str() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None
+    }
+}
+
+fn parse_date(s: &str, formats: &[String]) -> Option<DateTime> {
+    let from_naive = |d: NaiveDateTime| DateTime::from(ChDateTime::<Utc>::from_naive_utc_and_offset(d, Utc));
+    let custom = formats.iter().map(|f| f.as_str());
+    for format in custom.chain(ISO_DATE_FORMATS.iter().cloned()) {
+        if let Ok(d) = NaiveDateTime::parse_from_str(s, format) {
+            return Some(from_naive(d));
+        }
+        if let Ok(d) = NaiveDate::parse_from_str(s, format) {
+            return Some(from_naive(d.and_hms_opt(0, 0, 0).unwrap()));
+        }
+    }
+    ChDateTime::parse_from_rfc3339(s).ok().map(|d| DateTime::from(d.with_timezone(&Utc)))
+}
+
+/// The narrowest type all the non null cells can be parsed to
+fn infer(cells: &[Option<String>], options: &ReadOptions) -> DataType {
+    let values: Vec<&str> = cells.iter().filter_map(|c| c.as_deref()).collect();
+    if values.is_empty() { return DataType::Str };
+    if values.iter().all(|v| parse_bool(v).is_some()) { return DataType::Bool };
+    if values.iter().all(|v| v.parse::<i64>().is_ok()) { return DataType::Int };
+    if values.iter().all(|v| v.parse::<f64>().is_ok()) { return DataType::Float };
+    if values.iter().all(|v| parse_date(v, &options.date_formats).is_some()) { return DataType::DateTime };
+    let unique: HashSet<&str> = values.iter().cloned().collect();
+    if unique.len() as f64 <= options.categorical_ratio * values.len() as f64 {
+        DataType::Categorical
+    } else {
+        DataType::Str
+    }
+}
+
+fn build_column(name: &str, cells: Vec<Option<String>>, data_type: DataType, options: &ReadOptions) -> Result<Column, DataError> {
+    let valid: Vec<bool> = cells.iter().map(|c| c.is_some()).collect();
+    fn parse<T>(name: &str, cells: &[Option<String>], expected: DataType, null: T, f: impl Fn(&str) -> Option<T>) -> Result<Vec<T>, DataError>
+        where T: Clone
+    {
+        cells.iter().enumerate()
+            .map(|(row, cell)| match cell {
+                None => Ok(null.clone()),
+                Some(s) => f(s).ok_or_else(|| DataError::Parse {
+                    column: name.to_string(), row, value: s.clone(), expected
+                })
+            })
+            .collect()
+    }
+    let data = match data_type {
+        DataType::Bool => ColumnData::Bool(parse(name, &cells, data_type, false, parse_bool)?),
+        DataType::Int => ColumnData::Int(parse(name, &cells, data_type, 0, |s| s.parse().ok())?),
+        DataType::Float => ColumnData::Float(parse(name, &cells, data_type, f64::NAN, |s| s.parse().ok())?),
+        DataType::DateTime => ColumnData::DateTime(
+            parse(name, &cells, data_type, DateTime(f64::NAN), |s| parse_date(s, &options.date_formats))?
+        ),
+        DataType::Str => ColumnData::Str(cells.into_iter().map(|c| c.unwrap_or_default()).collect()),
+        DataType::Categorical => ColumnData::categorical(cells),
+    };
+    Column::with_validity(data, valid)
+}
+
+/// Builds a table from columns of raw cells
+fn build_table(names: Vec<String>, columns: Vec<Vec<Option<String>>>, options: &ReadOptions) -> Result<Table, DataError> {
+    let mut table = Table::new();
+    for (name, cells) in names.into_iter().zip(columns) {
+        let data_type = match options.types.get(&name) {
+            Some(t) => *t,
+            None => infer(&cells, options)
+        };
+        let column = build_column(&name, cells, data_type, options)?;
+        table.add_column(&name, column)?;
+    }
+    Ok(table)
+}
+
+fn cell(raw: &str, options: &ReadOptions) -> Option<String> {
+    let trimmed = raw.trim();
+    if options.null_values.iter().any(|n| n == trimmed) { None } else { Some(trimmed.to_string()) }
+}
+
+/// Reads a CSV with headers
+pub fn read_csv<R: Read>(reader: R, options: &ReadOptions) -> Result<Table, DataError> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .from_reader(reader);
+    let names: Vec<String> = rdr.headers()
+        .map_err(|e| DataError::Format(e.to_string()))?
+        .iter().map(String::from).collect();
+    let mut columns: Vec<Vec<Option<String>>> = vec![vec![]; names.len()];
+    for result in rdr.records() {
+        let record = result.map_err(|e| DataError::Format(e.to_string()))?;
+        for (i, column) in columns.iter_mut().enumerate() {
+            column.push(record.get(i).and_then(|raw| cell(raw, options)));
+        }
+    }
+    build_table(names, columns, options)
+}
+
+fn json_cell(value: Option<&JsonValue>, options: &ReadOptions) -> Result<Option<String>, DataError> {
+    match value {
+        None | Some(JsonValue::Null) => Ok(None),
+        Some(JsonValue::String(s)) => Ok(cell(s, options)),
+        Some(JsonValue::Number(n)) => Ok(Some(n.to_string())),
+        Some(JsonValue::Bool(b)) => Ok(Some(b.to_string())),
+        Some(other) => Err(DataError::Format(format!("nested values are not supported: {}", other)))
+    }
+}
+
+/// Builds a table from JSON objects, columns are ordered by first appearance
+pub(crate) fn read_records(records: &[JsonValue], options: &ReadOptions) -> Result<Table, DataError> {
+    let mut names: Vec<String> = vec![];
+    for record in records {
+        let object = record.as_object()
+            .ok_or_else(|| DataError::Format(format!("expected an object but found {}", record)))?;
+        for key in object.keys() {
+            if !names.contains(key) { names.push(key.clone()) };
+        }
+    }
+    let columns = names.iter()
+        .map(|name| records.iter().map(|r| json_cell(r.get(name), options)).collect())
+        .collect::<Result<Vec<Vec<Option<String>>>, DataError>>()?;
+    build_table(names, columns, options)
+}
+
+/// Reads a JSON array of records like `[{"a": 1, "b": "x"}, ...]`
+pub fn read_json<R: Read>(reader: R, options: &ReadOptions) -> Result<Table, DataError> {
+    let value: JsonValue = serde_json::from_reader(reader).map_err(|e| DataError::Format(e.to_string()))?;
+    match value {
+        JsonValue::Array(records) => read_records(&records, options),
+        other => Err(DataError::Format(format!("expected an array of records but found {}", other)))
+    }
+}
+
+/// Reads newline delimited JSON, one record per line
+pub fn read_ndjson<R: Read>(reader: R, options: &ReadOptions) -> Result<Table, DataError> {
+    let mut records = vec![];
+    for line in BufReader::new(reader).lines() {
+        let line = line.map_err(|e| DataError::Io(e.to_string()))?;
+        if line.trim().is_empty() { continue };
+        records.push(serde_json::from_str(&line).map_err(|e| DataError::Format(e.to_string()))?);
+    }
+    read_records(&records, options)
+}
+
+/// Reads a .csv, .tsv, .json, .ndjson or .jsonl file
+pub fn read_file<P: AsRef<Path>>(path: P, options: &ReadOptions) -> Result<Table, DataError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|e| DataError::Io(format!("{}: {}", path.display(), e)))?;
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "csv" => read_csv(file, options),
+        "tsv" => read_csv(file, &ReadOptions { delimiter: b'\t', ..options.clone() }),
+        "json" => read_json(file, options),
+        "ndjson" | "jsonl" => read_ndjson(file, options),
+        ext => Err(DataError::Format(format!("unknown file extension '{}'", ext)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Value;
+
+    #[test]
+    fn infer_works() {
+        let csv = "a,b,c,d,e,f\n1,1.5,true,2020-01-02,x,hello\n2,NA,false,2020-01-03T10:00:00,x,world\n,3,TRUE,2020-01-04,y,again\n4,4,false,,x,bye\n";
+        let table = read_csv(csv.as_bytes(), &ReadOptions::default()).unwrap();
+        let types: Vec<DataType> = table.columns().iter().map(|c| c.data_type()).collect();
+        assert_eq!(types, [DataType::Int, DataType::Float, DataType::Bool, DataType::DateTime, DataType::Categorical, DataType::Str]);
+        assert_eq!(table.row(2).get("a"), Value::Null);
+        assert_eq!(table.row(1).get("b"), Value::Null);
+        assert_eq!(table.column("d").unwrap().as_datetime().unwrap()[1], DateTime(1578045600000.0));
+    }
+
+    #[test]
+    fn options_work() {
+        let csv = "symbol;date;price\nMSFT;Jan 1 2000;39.81\nMSFT;Feb 1 2000;-\n";
+        let options = ReadOptionsBuilder::default()
+            .delimiter(b';')
+            .date_formats(vec!["%b %e %Y".to_string()])
+            .null_values(vec!["-".to_string()])
+            .types(vec![("symbol".to_string(), DataType::Str)].into_iter().collect::<HashMap<_, _>>())
+            .build().unwrap();
+        let table = read_csv(csv.as_bytes(), &options).unwrap();
+        assert_eq!(table.column("symbol").unwrap().data_type(), DataType::Str);
+        assert_eq!(table.column("date").unwrap().as_datetime().unwrap()[0], DateTime(946684800000.0));
+        assert_eq!(table.column("price").unwrap().null_count(), 1);
+
+        let options = ReadOptionsBuilder::default()
+            .types(vec![("price".to_string(), DataType::Int)].into_iter().collect::<HashMap<_, _>>())
+            .build().unwrap();
+        assert_eq!(
+            read_csv("price\n39.81\n".as_bytes(), &options),
+            Err(DataError::Parse { column: "price".into(), row: 0, value: "39.81".into(), expected: DataType::Int })
+        );
+    }
+
+    #[test]
+    fn json_works() {
+        let json = r#"[{"a": 1, "b": "x"}, {"a": 2.5, "c": true}, {"a": null, "b": "y"}]"#;
+        let table = read_json(json.as_bytes(), &ReadOptions::default()).unwrap();
+        assert_eq!(table.names(), ["a", "b", "c"]);
+        assert_eq!(table.column("a").unwrap().data_type(), DataType::Float);
+        assert_eq!(table.row(1).get("b"), Value::Null);
+        assert_eq!(table.row(0).get("c"), Value::Null);
+
+        let ndjson = "{\"a\": 1}\n\n{\"a\": 2}\n";
+        let table = read_ndjson(ndjson.as_bytes(), &ReadOptions::default()).unwrap();
+        assert_eq!(table.column("a").unwrap().as_i64().unwrap(), [1, 2]);
+
+        assert!(read_json("{\"a\": 1}".as_bytes(), &ReadOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,48 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+pub mod column;
+pub use self::column::{Column, ColumnData, DataType, Value};
+
+pub mod table;
+pub use self::table::{Table, Row};
+
+pub mod loader;
+pub use self::loader::{ReadOptions, ReadOptionsBuilder, read_csv, read_json, read_ndjson, read_file};
+
+///
+/// DataError
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataError {
+    ColumnNotFound(String),
+    DuplicateColumn(String),
+    LengthMismatch { column: String, expected: usize, found: usize },
+    TypeMismatch { column: String, expected: DataType, found: DataType },
+    Parse { column: String, row: usize, value: String, expected: DataType },
+    /// Malformed CSV or JSON
+    Format(String),
+    Io(String),
+}
+
+impl Display for DataError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            DataError::ColumnNotFound(name) => write!(f, "'{}' not in the dataset", name),
+            DataError::DuplicateColumn(name) => write!(f, "'{}' is already in the dataset", name),
+            DataError::LengthMismatch { column, expected, found } => {
+                write!(f, "'{}' has {} rows but the dataset has {}", column, found, expected)
+            },
+            DataError::TypeMismatch { column, expected, found } => {
+                write!(f, "'{}' should be {:?} but it is {:?}", column, expected, found)
+            },
+            DataError::Parse { column, row, value, expected } => {
+                write!(f, "'{}' in row {} of '{}' is not {:?}", value, row, column, expected)
+            },
+            DataError::Format(message) => write!(f, "Malformed data: {}", message),
+            DataError::Io(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for DataError {}
@@ -0,0 +1,175 @@
+use crate::data::{Column, DataError, Value};
+use crate::transform::GroupBy;
+
+/// Named columns of the same length
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Table {
+    names: Vec<String>,
+    columns: Vec<Column>,
+}
+
+/// A row of a table, values are read from its columns on demand
+#[derive(Debug, Clone, Copy)]
+pub struct Row<'a> {
+    table: &'a Table,
+    index: usize,
+}
+
+impl<'a> Row<'a> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn get(&self, name: &str) -> Value {
+        self.table.column(name).map(|c| c.get(self.index)).unwrap_or(Value::Null)
+    }
+
+    /// Numeric value of the column, None for nulls, strings or unknown columns
+    pub fn f64(&self, name: &str) -> Option<f64> {
+        self.get(name).to_f64()
+    }
+}
+
+impl Table {
+    pub fn new() -> Table {
+        Table::default()
+    }
+
+    /// Builder style `add_column`
+    pub fn with_column(mut self, name: &str, column: impl Into<Column>) -> Result<Table, DataError> {
+        self.add_column(name, column)?;
+        Ok(self)
+    }
+
+    pub fn add_column(&mut self, name: &str, column: impl Into<Column>) -> Result<(), DataError> {
+        let column = column.into();
+        if self.names.iter().any(|n| n == name) {
+            return Err(DataError::DuplicateColumn(name.to_string()));
+        }
+        if !self.columns.is_empty() && column.len() != self.len() {
+            return Err(DataError::LengthMismatch { column: name.to_string(), expected: self.len(), found: column.len() });
+        }
+        self.names.push(name.to_string());
+        self.columns.push(column);
+        Ok(())
+    }
+
+    pub fn column(&self, name: &str) -> Result<&Column, DataError> {
+        self.names.iter().position(|n| n == name)
+            .map(|i| &self.columns[i])
+            .ok_or_else(|| DataError::ColumnNotFound(name.to_string()))
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// Number of rows
+    pub fn len(&self) -> usize {
+        self.columns.first().map(|c| c.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn row(&self, index: usize) -> Row<'_> {
+        Row { table: self, index }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = Row<'_>> {
+        (0..self.len()).map(move |index| Row { table: self, index })
+    }
+
+    fn map_columns(&self, f: impl Fn(&Column) -> Column) -> Table {
+        Table { names: self.names.clone(), columns: self.columns.iter().map(f).collect() }
+    }
+
+    /// New table with the given rows, in that order
+    pub fn take(&self, rows: &[usize]) -> Table {
+        self.map_columns(|c| c.take(rows))
+    }
+
+    /// New table with the rows matching `predicate`
+    pub fn filter(&self, mut predicate: impl FnMut(&Row) -> bool) -> Table {
+        let rows: Vec<usize> = self.rows().filter(|row| predicate(row)).map(|row| row.index).collect();
+        self.take(&rows)
+    }
+
+    /// Zero-copy view of `len` rows starting at `offset`
+    pub fn slice(&self, offset: usize, len: usize) -> Table {
+        self.map_columns(|c| c.slice(offset, len))
+    }
+
+    /// New table sorted by the given column, nulls last. The sort is stable
+    pub fn sort_by(&self, name: &str, ascending: bool) -> Result<Table, DataError> {
+        let column = self.column(name)?;
+        let mut rows: Vec<usize> = (0..self.len()).collect();
+        rows.sort_by(|&a, &b| {
+            let nulls = (column.is_null(a), column.is_null(b));
+            if ascending || nulls.0 || nulls.1 { column.compare(a, b) } else { column.compare(b, a) }
+        });
+        Ok(self.take(&rows))
+    }
+
+    /// Splits the rows by the values of the `keys` columns, see `GroupBy::aggregate`
+    pub fn group_by(&self, keys: &[&str]) -> Result<GroupBy<'_>, DataError> {
+        GroupBy::new(self, keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::DataType;
+
+    fn cars() -> Table {
+        Table::new()
+            .with_column("name", vec!["ford", "fiat", "audi", "seat"]).unwrap()
+            .with_column("weight", vec![Some(3.5), Some(1.2), None, Some(2.0)]).unwrap()
+            .with_column("cylinders", Column::categorical(&["8", "4", "6", "4"])).unwrap()
+    }
+
+    #[test]
+    fn column_works() {
+        let table = cars();
+        assert_eq!(table.len(), 4);
+        assert_eq!(table.names(), ["name", "weight", "cylinders"]);
+        assert_eq!(table.column("weight").unwrap().data_type(), DataType::Float);
+        assert_eq!(table.column("price"), Err(DataError::ColumnNotFound("price".into())));
+        assert_eq!(
+            Table::new().with_column("a", vec![1.0]).unwrap().with_column("b", vec![1.0, 2.0]),
+            Err(DataError::LengthMismatch { column: "b".into(), expected: 1, found: 2 })
+        );
+    }
+
+    #[test]
+    fn filter_works() {
+        let table = cars().filter(|row| row.f64("weight").map(|w| w > 1.5).unwrap_or(false));
+        assert_eq!(table.column("name").unwrap().as_str().unwrap(), ["ford", "seat"]);
+        assert_eq!(table.row(1).get("cylinders"), Value::Str("4".into()));
+    }
+
+    #[test]
+    fn sort_works() {
+        let table = cars();
+        let asc = table.sort_by("weight", true).unwrap();
+        assert_eq!(asc.column("name").unwrap().as_str().unwrap(), ["fiat", "seat", "ford", "audi"]);
+        let desc = table.sort_by("weight", false).unwrap();
+        assert_eq!(desc.column("name").unwrap().as_str().unwrap(), ["ford", "seat", "fiat", "audi"]);
+        let by_name = table.sort_by("cylinders", true).unwrap();
+        assert_eq!(by_name.column("name").unwrap().as_str().unwrap(), ["fiat", "seat", "audi", "ford"]);
+    }
+
+    #[test]
+    fn slice_works() {
+        let table = cars().slice(1, 2);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.column("name").unwrap().as_str().unwrap(), ["fiat", "audi"]);
+        assert_eq!(table.row(1).get("weight"), Value::Null);
+    }
+}
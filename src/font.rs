@@ -0,0 +1,240 @@
+//!
+//! Fonts and their metrics, to measure text without a renderer.
+//! Advance widths of the printable ASCII characters are bundled for the standard
+//! Helvetica, Times and Courier fonts, which are metric compatible with Arial, Times New Roman
+//! and Courier New. Unknown families are measured as the next family of the list, or as Helvetica.
+//!
+
+/// Weights below 600 are measured as normal and the rest as bold
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum FontWeight { Normal, Bold, Weight(u16) }
+
+impl FontWeight {
+    /// CSS weight, 400 is normal and 700 bold
+    pub fn value(&self) -> u16 {
+        match self {
+            FontWeight::Normal => 400,
+            FontWeight::Bold => 700,
+            FontWeight::Weight(weight) => *weight,
+        }
+    }
+}
+
+/// Italics are measured like upright text
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum FontStyle { Normal, Italic, Oblique }
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Builder)]
+#[builder(setter(into))]
+pub struct Font {
+    /// A CSS font family list like "Helvetica, Arial, sans-serif"
+    #[builder(default = r#""sans-serif".to_string()"#)]
+    pub family: String,
+    /// In pixels
+    #[builder(default = "10.0")]
+    pub size: f64,
+    #[builder(default = "FontWeight::Normal")]
+    pub weight: FontWeight,
+    #[builder(default = "FontStyle::Normal")]
+    pub style: FontStyle,
+}
+
+impl Default for Font {
+    fn default() -> Font {
+        FontBuilder::default().build().unwrap()
+    }
+}
+
+/// Advance widths of the characters from ' ' to '~' and the vertical metrics, in units of 1/1000 em
+pub struct FontMetrics {
+    pub widths: [u16; 95],
+    /// Width of the characters without a bundled width
+    pub default_width: u16,
+    pub ascent: u16,
+    pub descent: u16,
+}
+
+impl FontMetrics {
+    /// Metrics of the first family of the list that is known, Helvetica when none is
+    pub fn of(font: &Font) -> &'static FontMetrics {
+        let bold = font.weight.value() >= 600;
+        let family = font.family.split(',')
+            .map(|name| name.trim().trim_matches(|c| c == '"' || c == '\'').to_lowercase())
+            .find_map(|name| generic_family(&name))
+            .unwrap_or(Generic::Sans);
+        match (family, bold) {
+            (Generic::Sans, false) => &HELVETICA,
+            (Generic::Sans, true) => &HELVETICA_BOLD,
+            (Generic::Serif, false) => &TIMES,
+            (Generic::Serif, true) => &TIMES_BOLD,
+            (Generic::Mono, _) => &COURIER,
+        }
+    }
+
+    /// Advance width of `c` in units of 1/1000 em
+    pub fn width(&self, c: char) -> u16 {
+        match c {
+            ' '..='~' => self.widths[c as usize - ' ' as usize],
+            '\u{2026}' => self.width('.') * 3,
+            // Zero width and control characters
+            '\u{200b}'..='\u{200f}' | '\u{feff}' => 0,
+            _ if c.is_control() => 0,
+            _ => self.default_width,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Generic { Sans, Serif, Mono }
+
+fn generic_family(name: &str) -> Option<Generic> {
+    match name {
+        "sans-serif" | "helvetica" | "arial" | "helvetica neue" | "liberation sans" | "arimo" | "system-ui" => Some(Generic::Sans),
+        "serif" | "times" | "times new roman" | "liberation serif" | "tinos" | "georgia" => Some(Generic::Serif),
+        "monospace" | "courier" | "courier new" | "liberation mono" | "cousine" | "menlo" | "consolas" => Some(Generic::Mono),
+        _ => None,
+    }
+}
+
+/// Width, ascent and descent of a line of `text` in pixels. Ascent and descent are positive distances from the baseline
+pub fn measure_text(text: &str, font: &Font) -> (f64, f64, f64) {
+    let metrics = FontMetrics::of(font);
+    let em = font.size / 1000.0;
+    let width: u32 = text.chars().map(|c| metrics.width(c) as u32).sum();
+    (width as f64 * em, metrics.ascent as f64 * em, metrics.descent as f64 * em)
+}
+
+/// Lines of `text` no wider than `max_width`, broken at spaces. Words wider than that get a line of their own
+pub fn wrap_text(text: &str, font: &Font, max_width: f64) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+        if line.is_empty() || measure_text(&candidate, font).0 <= max_width {
+            line = candidate;
+        } else {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        }
+    }
+    if !line.is_empty() || lines.is_empty() { lines.push(line) };
+    lines
+}
+
+/// `text` cut to fit in `max_width` with an ellipsis, unchanged when it already fits
+pub fn truncate_text(text: &str, font: &Font, max_width: f64) -> String {
+    if measure_text(text, font).0 <= max_width { return text.to_string() };
+    let mut width = measure_text("\u{2026}", font).0;
+    let em = font.size / 1000.0;
+    let metrics = FontMetrics::of(font);
+    let mut cut: String = text.chars()
+        .take_while(|&c| { width += metrics.width(c) as f64 * em; width <= max_width })
+        .collect();
+    cut.truncate(cut.trim_end().len());
+    cut + "\u{2026}"
+}
+
+pub static HELVETICA: FontMetrics = FontMetrics {
+    widths: [
+        278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+        556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+        1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+        667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+        333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+        556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+    ],
+    default_width: 556,
+    ascent: 718,
+    descent: 207,
+};
+
+pub static HELVETICA_BOLD: FontMetrics = FontMetrics {
+    widths: [
+        278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278,
+        556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611,
+        975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778,
+        667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556,
+        333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611,
+        611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+    ],
+    default_width: 611,
+    ascent: 718,
+    descent: 207,
+};
+
+pub static TIMES: FontMetrics = FontMetrics {
+    widths: [
+        250, 333, 408, 500, 500, 833, 778, 180, 333, 333, 500, 564, 250, 333, 250, 278,
+        500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 278, 278, 564, 564, 564, 444,
+        921, 722, 667, 667, 722, 611, 556, 722, 722, 333, 389, 722, 611, 889, 722, 722,
+        556, 722, 667, 556, 611, 722, 722, 944, 722, 722, 611, 333, 278, 333, 469, 500,
+        333, 444, 500, 444, 500, 444, 333, 500, 500, 278, 278, 500, 278, 778, 500, 500,
+        500, 500, 333, 389, 278, 500, 500, 722, 500, 500, 444, 480, 200, 480, 541,
+    ],
+    default_width: 500,
+    ascent: 683,
+    descent: 217,
+};
+
+pub static TIMES_BOLD: FontMetrics = FontMetrics {
+    widths: [
+        250, 333, 555, 500, 500, 1000, 833, 278, 333, 333, 500, 570, 250, 333, 250, 278,
+        500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 333, 333, 570, 570, 570, 500,
+        930, 722, 667, 722, 722, 667, 611, 778, 778, 389, 500, 778, 667, 944, 722, 778,
+        611, 778, 722, 556, 667, 722, 722, 1000, 722, 722, 667, 333, 278, 333, 581, 500,
+        333, 500, 556, 444, 556, 444, 333, 500, 556, 278, 333, 556, 278, 833, 556, 500,
+        556, 556, 444, 389, 333, 556, 500, 722, 500, 500, 444, 394, 220, 394, 520,
+    ],
+    default_width: 556,
+    ascent: 683,
+    descent: 217,
+};
+
+pub static COURIER: FontMetrics = FontMetrics {
+    widths: [600; 95],
+    default_width: 600,
+    ascent: 629,
+    descent: 157,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measure_works() {
+        let font = Font { size: 20.0, ..Font::default() };
+        let (width, ascent, descent) = measure_text("Hi!", &font);
+        assert!((width - (722.0 + 222.0 + 278.0) * 0.02).abs() < 1e-9);
+        assert!((ascent - 14.36).abs() < 1e-9);
+        assert!((descent - 4.14).abs() < 1e-9);
+        assert_eq!(measure_text("", &font).0, 0.0);
+
+        let bold = Font { weight: FontWeight::Weight(800), ..font.clone() };
+        assert!(measure_text("Hi!", &bold).0 > width);
+        let mono = Font { family: "'Courier New', monospace".to_string(), ..font.clone() };
+        assert_eq!(measure_text("iiii", &mono).0, measure_text("MMMM", &mono).0);
+        let unknown = Font { family: "Fancy, serif".to_string(), ..font };
+        assert_eq!(FontMetrics::of(&unknown).ascent, TIMES.ascent);
+        // Not bundled
+        assert!((measure_text("π", &Font::default()).0 - 5.56).abs() < 1e-9);
+    }
+
+    #[test]
+    fn wrap_works() {
+        let font = Font { family: "monospace".to_string(), size: 10.0, ..Font::default() };
+        // 6 pixels per character
+        assert_eq!(wrap_text("a long  title of\tthe chart", &font, 60.0), ["a long", "title of", "the chart"]);
+        assert_eq!(wrap_text("unbreakable word", &font, 30.0), ["unbreakable", "word"]);
+        assert_eq!(wrap_text("", &font, 30.0), [""]);
+
+        assert_eq!(truncate_text("short", &font, 30.0), "short");
+        assert_eq!(truncate_text("longer one", &font, 42.0), "long\u{2026}");
+        assert_eq!(truncate_text("longer one", &font, 54.0), "longer\u{2026}");
+        // No space before the ellipsis
+        assert_eq!(truncate_text("longer ones", &font, 60.0), "longer\u{2026}");
+        assert_eq!(truncate_text("abc", &font, 1.0), "\u{2026}");
+    }
+}
@@ -0,0 +1,122 @@
+use crate::color::{Color, BLACK, WHITE};
+
+/// Values that can be blended linearly, so they can be used as the
+/// output range of a continuous scale.
+pub trait Interpolate: Clone {
+    /// Returns the value at `t` between `a` (t = 0) and `b` (t = 1)
+    fn interpolate(a: &Self, b: &Self, t: f64) -> Self;
+
+    /// Rounds the numeric components of the value
+    fn round(&self) -> Self { self.clone() }
+
+    /// Range used by a scale that is built without one
+    fn unit_range() -> [Self; 2];
+
+    /// Result of inputs a scale can't map, like the logarithm of 0. NaN for numbers, so that nothing is drawn
+    fn undefined() -> Self { Self::unit_range()[0].clone() }
+}
+
+impl Interpolate for f64 {
+    fn interpolate(a: &f64, b: &f64, t: f64) -> f64 {
+        a + t * (b - a)
+    }
+
+    fn round(&self) -> f64 {
+        f64::round(*self)
+    }
+
+    fn unit_range() -> [f64; 2] {
+        [0.0, 1.0]
+    }
+
+    fn undefined() -> f64 { f64::NAN }
+}
+
+impl Interpolate for Color {
+    /// Interpolates each RGBA channel independently
+    fn interpolate(a: &Color, b: &Color, t: f64) -> Color {
+        let channel = |x: u8, y: u8| {
+            f64::interpolate(&(x as f64), &(y as f64), t).round().clamp(0.0, 255.0) as u8
+        };
+        let alpha = f64::interpolate(&(a.a as f64), &(b.a as f64), t).clamp(0.0, 1.0);
+        Color {
+            r: channel(a.r, b.r),
+            g: channel(a.g, b.g),
+            b: channel(a.b, b.b),
+            a: alpha as f32
+        }
+    }
+
+    fn unit_range() -> [Color; 2] {
+        [BLACK, WHITE]
+    }
+
+    /// Transparent
+    fn undefined() -> Color { Color { a: 0.0, ..BLACK } }
+}
+
+impl Interpolate for (f64, f64) {
+    fn interpolate(a: &(f64, f64), b: &(f64, f64), t: f64) -> (f64, f64) {
+        (f64::interpolate(&a.0, &b.0, t), f64::interpolate(&a.1, &b.1, t))
+    }
+
+    fn round(&self) -> (f64, f64) {
+        (self.0.round(), self.1.round())
+    }
+
+    fn unit_range() -> [(f64, f64); 2] {
+        [(0.0, 0.0), (1.0, 1.0)]
+    }
+
+    fn undefined() -> (f64, f64) { (f64::NAN, f64::NAN) }
+}
+
+impl<T: Interpolate, const N: usize> Interpolate for [T; N] {
+    /// Interpolates the arrays element-wise
+    fn interpolate(a: &[T; N], b: &[T; N], t: f64) -> [T; N] {
+        std::array::from_fn(|i| T::interpolate(&a[i], &b[i], t))
+    }
+
+    fn round(&self) -> [T; N] {
+        std::array::from_fn(|i| self[i].round())
+    }
+
+    fn unit_range() -> [[T; N]; 2] {
+        let [start, end] = T::unit_range();
+        [std::array::from_fn(|_| start.clone()), std::array::from_fn(|_| end.clone())]
+    }
+
+    fn undefined() -> [T; N] {
+        std::array::from_fn(|_| T::undefined())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{BLUE, RED};
+
+    #[test]
+    fn f64_works() {
+        assert_eq!(f64::interpolate(&10.0, &20.0, 0.0), 10.0);
+        assert_eq!(f64::interpolate(&10.0, &20.0, 0.5), 15.0);
+        assert_eq!(f64::interpolate(&10.0, &20.0, 2.0), 30.0);
+        assert_eq!(Interpolate::round(&1.6), 2.0);
+    }
+
+    #[test]
+    fn color_works() {
+        assert_eq!(Color::interpolate(&BLUE, &RED, 0.0), BLUE);
+        assert_eq!(Color::interpolate(&BLUE, &RED, 1.0), RED);
+        assert_eq!(Color::interpolate(&BLUE, &RED, 0.5), Color{r: 128, g: 0, b: 128, a: 1.0});
+        assert_eq!(Color::interpolate(&BLUE, &RED, 2.0), Color{r: 255, g: 0, b: 0, a: 1.0});
+    }
+
+    #[test]
+    fn tuple_and_array_works() {
+        assert_eq!(<(f64, f64)>::interpolate(&(0.0, 10.0), &(10.0, 0.0), 0.25), (2.5, 7.5));
+        assert_eq!(<[f64; 3]>::interpolate(&[0.0, 1.0, 2.0], &[10.0, 11.0, 12.0], 0.5), [5.0, 6.0, 7.0]);
+        assert_eq!([0.4, 1.6].round(), [0.0, 2.0]);
+        assert_eq!(<[f64; 2]>::unit_range(), [[0.0, 0.0], [1.0, 1.0]]);
+    }
+}
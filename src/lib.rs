@@ -2,7 +2,13 @@
 
 #[macro_use] pub mod utils;
 pub mod color; 
+pub mod paint;
+pub mod interpolate;
+pub mod font;
 pub mod core;
 pub mod svg_backend;
 pub mod scale;
-pub mod mark;
\ No newline at end of file
+pub mod mark;
+pub mod data;
+pub mod transform;
+pub mod chart;
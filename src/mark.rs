@@ -1,8 +1,13 @@
 use std::rc::Rc;
 
 use crate::core::Style;
+use crate::color::{Color, BLACK};
+use crate::font::{Font, wrap_text, truncate_text};
+use crate::paint::Paint;
+use crate::transform::Downsample;
 
-#[derive(Debug, Builder, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Builder, Default)]
 #[builder(setter(into))]
 pub struct Circle {
     pub x: f64,
@@ -12,7 +17,8 @@ pub struct Circle {
     pub style: Rc<Style>
 }
 
-#[derive(Debug, Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Builder)]
 #[builder(setter(into))]
 pub struct Rect {
     pub x: f64,
@@ -23,7 +29,8 @@ pub struct Rect {
     pub style: Rc<Style>
 }
 
-#[derive(Debug, Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Builder)]
 #[builder(setter(into))]
 pub struct Segment {
     pub x: f64,
@@ -34,13 +41,17 @@ pub struct Segment {
     pub style: Rc<Style>
 }
 
-#[derive(Debug, Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Builder)]
 #[builder(setter(into))]
 pub struct Line {
     pub x: Vec<f64>,
     pub y: Vec<f64>,
     #[builder(default)]
-    pub style: Rc<Style>
+    pub style: Rc<Style>,
+    /// Draws fewer points for long series
+    #[builder(default)]
+    pub downsample: Option<Downsample>
 }
 
 impl Line {
@@ -50,12 +61,63 @@ impl Line {
     pub fn add_y(&mut self, y: f64) {
         self.y.push(y);
     }
+
+    /// The points to draw, after downsampling to the width of the line in pixels
+    pub fn points(&self) -> Vec<(f64, f64)> {
+        match &self.downsample {
+            Some(downsample) => {
+                let (lo, hi) = self.x.iter().filter(|x| x.is_finite())
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &x| (lo.min(x), hi.max(x)));
+                let width = if hi > lo { hi - lo } else { 1.0 };
+                downsample.apply(&self.x, &self.y, width).into_iter().map(|i| (self.x[i], self.y[i])).collect()
+            },
+            None => self.x.iter().cloned().zip(self.y.iter().cloned()).collect(),
+        }
+    }
+}
+
+/// Region between a top line (x1, y1) and a baseline (x0, y0) that share their rows
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Builder)]
+#[builder(setter(into))]
+pub struct Area {
+    pub x0: Vec<f64>,
+    pub y0: Vec<f64>,
+    pub x1: Vec<f64>,
+    pub y1: Vec<f64>,
+    #[builder(default)]
+    pub style: Rc<Style>
+}
+
+impl Area {
+    /// Area along x, between y0 and y1
+    pub fn horizontal(x: Vec<f64>, y0: Vec<f64>, y1: Vec<f64>, style: Rc<Style>) -> Area {
+        Area { x0: x.clone(), y0, x1: x, y1, style }
+    }
+
+    /// Area along y, between x0 and x1
+    pub fn vertical(y: Vec<f64>, x0: Vec<f64>, x1: Vec<f64>, style: Rc<Style>) -> Area {
+        Area { x0, y0: y.clone(), x1, y1: y, style }
+    }
 }
 
-#[derive(Debug, Clone)]
-pub enum TextAlign { Start, Center, End, }
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextAlign { #[default] Start, Center, End, }
 
-#[derive(Debug, Builder)]
+/// Vertical position of `y` in the text, like the textBaseline of the html5 canvas
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextBaseline { Top, Middle, #[default] Alphabetic, Bottom, Hanging }
+
+/// What happens to the lines wider than the `max_width` of a text
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum TextOverflow { #[default] Wrap, Ellipsis }
+
+/// Lines are separated by '\n' and laid out below each other, the block is aligned on (x, y) by `baseline`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Builder)]
 #[builder(setter(into))]
 pub struct Text {
     pub x: f64,
@@ -64,5 +126,386 @@ pub struct Text {
     #[builder(default = "TextAlign::Start")]
     pub align: TextAlign,
     #[builder(default)]
+    pub baseline: TextBaseline,
+    #[builder(default)]
+    pub font: Font,
+    /// Clockwise rotation in degrees around (x, y)
+    #[builder(default)]
+    pub angle: f64,
+    /// Shift along the rotated text
+    #[builder(default)]
+    pub dx: f64,
+    /// Shift across the rotated text, downwards when it is not rotated
+    #[builder(default)]
+    pub dy: f64,
+    /// Distance between the baselines of the lines, in multiples of the font size
+    #[builder(default = "1.2")]
+    pub line_height: f64,
+    /// Lines wider than this are wrapped at spaces or truncated, depending on `overflow`
+    #[builder(default)]
+    pub max_width: Option<f64>,
+    #[builder(default)]
+    pub overflow: TextOverflow,
+    #[builder(default)]
     pub style: Rc<Style>
 }
+
+impl Default for Text {
+    fn default() -> Text {
+        TextBuilder::default().x(0.0).y(0.0).text("").build().unwrap()
+    }
+}
+
+impl Text {
+    /// The lines as laid out, after wrapping or truncating them to `max_width`
+    pub fn lines(&self) -> Vec<String> {
+        self.text.split('\n').flat_map(|line| match (self.max_width, self.overflow) {
+            (None, _) => vec![line.to_string()],
+            (Some(width), TextOverflow::Wrap) => wrap_text(line, &self.font, width),
+            (Some(width), TextOverflow::Ellipsis) => vec![truncate_text(line, &self.font, width)],
+        }).collect()
+    }
+
+    /// Distance between the baselines of the lines in pixels
+    pub fn line_spacing(&self) -> f64 {
+        self.line_height * self.font.size
+    }
+}
+
+/// A visual property shared by all the rows of a batch or given for each one
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(untagged))]
+pub enum Channel<T> {
+    Constant(T),
+    PerRow(Vec<T>)
+}
+
+impl<T> Channel<T> {
+    pub fn get(&self, row: usize) -> &T {
+        match self {
+            Channel::Constant(value) => value,
+            Channel::PerRow(values) => &values[row]
+        }
+    }
+
+    /// Number of values, None when constant
+    pub fn rows(&self) -> Option<usize> {
+        match self {
+            Channel::Constant(_) => None,
+            Channel::PerRow(values) => Some(values.len())
+        }
+    }
+}
+
+/// Checks the columns of a batch with a known length have the same one
+fn validate_rows(lengths: &[Option<usize>]) -> Result<(), String> {
+    let mut known = lengths.iter().flatten();
+    match known.next() {
+        Some(first) if known.any(|len| len != first) => Err("x, y and the per-row channels must have the same length".into()),
+        _ => Ok(())
+    }
+}
+
+/// Rows that can be drawn, the shortest of the lengths
+fn shortest(lengths: &[Option<usize>]) -> usize {
+    lengths.iter().flatten().copied().min().unwrap_or(0)
+}
+
+impl<T> From<T> for Channel<T> {
+    fn from(value: T) -> Self {
+        Channel::Constant(value)
+    }
+}
+
+impl<T> From<Vec<T>> for Channel<T> {
+    fn from(values: Vec<T>) -> Self {
+        Channel::PerRow(values)
+    }
+}
+
+/// Colors of each row, like the output of a color scale
+impl From<Vec<Option<Color>>> for Channel<Option<Paint>> {
+    fn from(colors: Vec<Option<Color>>) -> Self {
+        Channel::PerRow(colors.into_iter().map(|color| color.map(Paint::from)).collect())
+    }
+}
+
+/// Many circles stored by columns, drawn in one pass
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Builder)]
+#[builder(setter(into), build_fn(validate = "Self::validate"))]
+pub struct CircleBatch {
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub radius: Channel<f64>,
+    #[builder(default = "Channel::Constant(Some(BLACK.into()))")]
+    pub fill: Channel<Option<Paint>>,
+    #[builder(default = "Channel::Constant(None)")]
+    pub stroke: Channel<Option<Paint>>,
+    #[builder(default = "1.0")]
+    pub stroke_width: f64,
+    /// Text shown when hovering each row
+    #[builder(default)]
+    pub tooltip: Option<Vec<String>>
+}
+
+impl CircleBatchBuilder {
+    fn validate(&self) -> Result<(), String> {
+        validate_rows(&[
+            self.x.as_ref().map(Vec::len),
+            self.y.as_ref().map(Vec::len),
+            self.radius.as_ref().and_then(Channel::rows),
+            self.fill.as_ref().and_then(Channel::rows),
+            self.stroke.as_ref().and_then(Channel::rows),
+            self.tooltip.as_ref().and_then(|tooltip| tooltip.as_ref().map(Vec::len)),
+        ])
+    }
+}
+
+impl CircleBatch {
+    /// Number of rows drawn, the shortest of the columns
+    pub fn rows(&self) -> usize {
+        shortest(&[
+            Some(self.x.len()),
+            Some(self.y.len()),
+            self.radius.rows(),
+            self.fill.rows(),
+            self.stroke.rows(),
+            self.tooltip.as_ref().map(Vec::len),
+        ])
+    }
+}
+
+/// Many rects stored by columns, drawn in one pass
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Builder)]
+#[builder(setter(into), build_fn(validate = "Self::validate"))]
+pub struct RectBatch {
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub width: Channel<f64>,
+    pub height: Channel<f64>,
+    #[builder(default = "Channel::Constant(Some(BLACK.into()))")]
+    pub fill: Channel<Option<Paint>>,
+    #[builder(default = "Channel::Constant(None)")]
+    pub stroke: Channel<Option<Paint>>,
+    #[builder(default = "1.0")]
+    pub stroke_width: f64,
+    /// Text shown when hovering each row
+    #[builder(default)]
+    pub tooltip: Option<Vec<String>>
+}
+
+impl RectBatchBuilder {
+    fn validate(&self) -> Result<(), String> {
+        validate_rows(&[
+            self.x.as_ref().map(Vec::len),
+            self.y.as_ref().map(Vec::len),
+            self.width.as_ref().and_then(Channel::rows),
+            self.height.as_ref().and_then(Channel::rows),
+            self.fill.as_ref().and_then(Channel::rows),
+            self.stroke.as_ref().and_then(Channel::rows),
+            self.tooltip.as_ref().and_then(|tooltip| tooltip.as_ref().map(Vec::len)),
+        ])
+    }
+}
+
+impl RectBatch {
+    /// Number of rows drawn, the shortest of the columns
+    pub fn rows(&self) -> usize {
+        shortest(&[
+            Some(self.x.len()),
+            Some(self.y.len()),
+            self.width.rows(),
+            self.height.rows(),
+            self.fill.rows(),
+            self.stroke.rows(),
+            self.tooltip.as_ref().map(Vec::len),
+        ])
+    }
+}
+
+/// Shapes of the symbols, borrowed from https://github.com/d3/d3-shape
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SymbolShape { Circle, Square, Cross, Diamond, Triangle }
+
+impl SymbolShape {
+    /// Vertices of a polygon with the given `area`, centered at 0,0. Circles have none
+    pub fn vertices(&self, area: f64) -> Vec<(f64, f64)> {
+        match self {
+            SymbolShape::Circle => vec![],
+            SymbolShape::Square => {
+                let r = area.sqrt() / 2.0;
+                vec![(-r, -r), (r, -r), (r, r), (-r, r)]
+            },
+            SymbolShape::Cross => {
+                let r = (area / 5.0).sqrt() / 2.0;
+                vec![(-3.0 * r, -r), (-r, -r), (-r, -3.0 * r), (r, -3.0 * r), (r, -r), (3.0 * r, -r),
+                     (3.0 * r, r), (r, r), (r, 3.0 * r), (-r, 3.0 * r), (-r, r), (-3.0 * r, r)]
+            },
+            SymbolShape::Diamond => {
+                let tan30: f64 = (1.0_f64 / 3.0).sqrt();
+                let y = (area / (tan30 * 2.0)).sqrt();
+                let x = y * tan30;
+                vec![(0.0, -y), (x, 0.0), (0.0, y), (-x, 0.0)]
+            },
+            SymbolShape::Triangle => {
+                let sqrt3: f64 = 3.0_f64.sqrt();
+                let y = -(area / (sqrt3 * 3.0)).sqrt();
+                vec![(0.0, y * 2.0), (-sqrt3 * y, -y), (sqrt3 * y, -y)]
+            }
+        }
+    }
+}
+
+/// Many symbols stored by columns, drawn in one pass. `size` is the area of each symbol
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Builder)]
+#[builder(setter(into), build_fn(validate = "Self::validate"))]
+pub struct SymbolBatch {
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub size: Channel<f64>,
+    #[builder(default = "SymbolShape::Circle")]
+    pub shape: SymbolShape,
+    #[builder(default = "Channel::Constant(Some(BLACK.into()))")]
+    pub fill: Channel<Option<Paint>>,
+    #[builder(default = "Channel::Constant(None)")]
+    pub stroke: Channel<Option<Paint>>,
+    #[builder(default = "1.0")]
+    pub stroke_width: f64,
+    /// Text shown when hovering each row
+    #[builder(default)]
+    pub tooltip: Option<Vec<String>>
+}
+
+impl SymbolBatchBuilder {
+    fn validate(&self) -> Result<(), String> {
+        validate_rows(&[
+            self.x.as_ref().map(Vec::len),
+            self.y.as_ref().map(Vec::len),
+            self.size.as_ref().and_then(Channel::rows),
+            self.fill.as_ref().and_then(Channel::rows),
+            self.stroke.as_ref().and_then(Channel::rows),
+            self.tooltip.as_ref().and_then(|tooltip| tooltip.as_ref().map(Vec::len)),
+        ])
+    }
+}
+
+impl SymbolBatch {
+    /// Number of rows drawn, the shortest of the columns
+    pub fn rows(&self) -> usize {
+        shortest(&[
+            Some(self.x.len()),
+            Some(self.y.len()),
+            self.size.rows(),
+            self.fill.rows(),
+            self.stroke.rows(),
+            self.tooltip.as_ref().map(Vec::len),
+        ])
+    }
+}
+
+/// Any of the marks, so scenes can be stored and loaded back
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(tag = "type", rename_all = "snake_case"))]
+pub enum Mark {
+    Circle(Circle),
+    Rect(Rect),
+    Segment(Segment),
+    Line(Line),
+    Area(Area),
+    Text(Text),
+    CircleBatch(CircleBatch),
+    RectBatch(RectBatch),
+    SymbolBatch(SymbolBatch),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::RED;
+
+    #[test]
+    fn channel_works() {
+        let constant: Channel<f64> = 5.0.into();
+        let per_row: Channel<f64> = vec![1.0, 2.0].into();
+        assert_eq!(constant.get(10), &5.0);
+        assert_eq!(per_row.get(1), &2.0);
+
+        let batch = CircleBatchBuilder::default()
+            .x(vec![1.0, 2.0])
+            .y(vec![1.0, 2.0])
+            .radius(3.0)
+            .fill(vec![Some(RED), None])
+            .build().unwrap();
+        assert_eq!(batch.fill.get(0), &Some(RED.into()));
+        assert_eq!(batch.stroke.get(1), &None);
+        assert_eq!(batch.rows(), 2);
+
+        let uneven = CircleBatchBuilder::default()
+            .x(vec![1.0, 2.0])
+            .y(vec![1.0])
+            .radius(3.0)
+            .build();
+        assert!(uneven.is_err());
+        let uneven = RectBatchBuilder::default()
+            .x(vec![1.0, 2.0])
+            .y(vec![1.0, 2.0])
+            .width(vec![1.0])
+            .height(1.0)
+            .build();
+        assert!(uneven.is_err());
+
+        // Struct literals and loaded scenes aren't validated, only the shortest column is drawn
+        let batch = CircleBatch { y: vec![1.0], ..batch };
+        assert_eq!(batch.rows(), 1);
+    }
+
+    #[test]
+    fn symbol_area_works() {
+        for shape in &[SymbolShape::Square, SymbolShape::Cross, SymbolShape::Diamond, SymbolShape::Triangle] {
+            let v = shape.vertices(64.0);
+            // Shoelace formula
+            let area: f64 = (0..v.len())
+                .map(|i| { let (a, b) = (v[i], v[(i + 1) % v.len()]); a.0 * b.1 - b.0 * a.1 })
+                .sum::<f64>().abs() / 2.0;
+            assert!((area - 64.0).abs() < 1e-9, "{:?} has area {}", shape, area);
+        }
+    }
+
+    #[test]
+    fn line_downsample_works() {
+        let x: Vec<f64> = (0..1000).map(|i| i as f64 / 100.0).collect();
+        let y: Vec<f64> = x.iter().map(|x| x.sin()).collect();
+        let mut line = LineBuilder::default().x(x).y(y).build().unwrap();
+        assert_eq!(line.points().len(), 1000);
+
+        // 10 pixels wide
+        line.downsample = Some(Downsample::Lttb);
+        assert_eq!(line.points().len(), 20);
+        line.downsample = Some(Downsample::MinMax);
+        assert!(line.points().len() <= 40);
+        assert_eq!(line.points()[0], (0.0, 0.0));
+
+        // Points out of the screen don't widen the line
+        line.x[999] = f64::INFINITY;
+        line.downsample = Some(Downsample::Lttb);
+        assert_eq!(line.points().len(), 20);
+    }
+
+    #[test]
+    fn text_lines_work() {
+        let font = Font { family: "monospace".into(), ..Font::default() };
+        let mut text = TextBuilder::default().x(0.0).y(0.0).text("one two three\nfour").font(font).build().unwrap();
+        assert_eq!(text.lines(), ["one two three", "four"]);
+        assert_eq!(text.line_spacing(), 12.0);
+
+        // 6 pixels per character
+        text.max_width = Some(50.0);
+        assert_eq!(text.lines(), ["one two", "three", "four"]);
+        text.overflow = TextOverflow::Ellipsis;
+        assert_eq!(text.lines(), ["one t\u{2026}", "four"]);
+    }
+}
@@ -0,0 +1,168 @@
+//!
+//! What fills or strokes a shape: a solid color, a gradient or a repeating pattern.
+//! The geometry of gradients is relative to the bounding box of the shape by default, from (0, 0)
+//! at its top left corner to (1, 1) at the bottom right one. Shapes without width or height,
+//! like horizontal segments, have no such box and aren't painted, their gradients need
+//! `GradientUnits::UserSpace`.
+//!
+
+use crate::color::{Color, BLACK};
+
+/// Stored as a color string when solid, otherwise as the fields of the gradient or pattern
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(untagged))]
+pub enum Paint {
+    Solid(Color),
+    LinearGradient(LinearGradient),
+    RadialGradient(RadialGradient),
+    Pattern(Pattern),
+}
+
+impl From<Color> for Paint {
+    fn from(color: Color) -> Paint {
+        Paint::Solid(color)
+    }
+}
+
+impl From<LinearGradient> for Paint {
+    fn from(gradient: LinearGradient) -> Paint {
+        Paint::LinearGradient(gradient)
+    }
+}
+
+impl From<RadialGradient> for Paint {
+    fn from(gradient: RadialGradient) -> Paint {
+        Paint::RadialGradient(gradient)
+    }
+}
+
+impl From<Pattern> for Paint {
+    fn from(pattern: Pattern) -> Paint {
+        Paint::Pattern(pattern)
+    }
+}
+
+/// Colors evenly spread from 0 to 1, to use as the stops of a gradient
+pub fn stops(colors: &[Color]) -> Vec<(f64, Color)> {
+    let last = colors.len().saturating_sub(1).max(1) as f64;
+    colors.iter().enumerate().map(|(i, &color)| (i as f64 / last, color)).collect()
+}
+
+/// What the coordinates of a gradient are relative to
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum GradientUnits {
+    /// Fractions of the bounding box of the shape
+    #[default]
+    BoundingBox,
+    /// Pixels, like the coordinates of the shape
+    UserSpace,
+}
+
+/// Colors blended along the line from (x1, y1) to (x2, y2)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinearGradient {
+    pub x1: f64,
+    pub y1: f64,
+    pub x2: f64,
+    pub y2: f64,
+    /// Offset from 0 to 1 along the line, and the color there
+    pub stops: Vec<(f64, Color)>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub units: GradientUnits,
+}
+
+impl LinearGradient {
+    /// From the left side to the right one
+    pub fn horizontal(stops: Vec<(f64, Color)>) -> LinearGradient {
+        LinearGradient { x1: 0.0, y1: 0.0, x2: 1.0, y2: 0.0, stops, units: GradientUnits::BoundingBox }
+    }
+
+    /// From the bottom to the top, like the ramps of vertical legends
+    pub fn vertical(stops: Vec<(f64, Color)>) -> LinearGradient {
+        LinearGradient { x1: 0.0, y1: 1.0, x2: 0.0, y2: 0.0, stops, units: GradientUnits::BoundingBox }
+    }
+
+    /// From the pixel (x1, y1) to (x2, y2), it also paints flat shapes like horizontal lines
+    pub fn between(x1: f64, y1: f64, x2: f64, y2: f64, stops: Vec<(f64, Color)>) -> LinearGradient {
+        LinearGradient { x1, y1, x2, y2, stops, units: GradientUnits::UserSpace }
+    }
+}
+
+/// Colors blended from the center (cx, cy) to the circle of radius r around it
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RadialGradient {
+    pub cx: f64,
+    pub cy: f64,
+    pub r: f64,
+    /// Offset from 0 at the center to 1 at the circle, and the color there
+    pub stops: Vec<(f64, Color)>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub units: GradientUnits,
+}
+
+impl RadialGradient {
+    /// Filling the bounding box from its center
+    pub fn centered(stops: Vec<(f64, Color)>) -> RadialGradient {
+        RadialGradient { cx: 0.5, cy: 0.5, r: 0.5, stops, units: GradientUnits::BoundingBox }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum PatternKind {
+    /// Parallel lines
+    Hatch,
+    /// Two sets of lines crossing at right angles
+    CrossHatch,
+    Dots,
+}
+
+/// Lines or dots repeated every `spacing` pixels, like for regions of missing data
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Builder)]
+#[builder(setter(into))]
+pub struct Pattern {
+    #[builder(default = "PatternKind::Hatch")]
+    pub kind: PatternKind,
+    #[builder(default = "BLACK")]
+    pub color: Color,
+    /// Painted behind the lines or dots, transparent when None
+    #[builder(default)]
+    pub background: Option<Color>,
+    /// Distance between the lines or the centers of the dots
+    #[builder(default = "6.0")]
+    pub spacing: f64,
+    /// Clockwise rotation in degrees, 0 draws horizontal lines
+    #[builder(default = "45.0")]
+    pub angle: f64,
+    /// Width of the lines or diameter of the dots
+    #[builder(default = "1.0")]
+    pub width: f64,
+}
+
+impl Default for Pattern {
+    fn default() -> Pattern {
+        PatternBuilder::default().build().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{RED, WHITE};
+
+    #[test]
+    fn stops_work() {
+        assert_eq!(stops(&[RED, WHITE, BLACK]), [(0.0, RED), (0.5, WHITE), (1.0, BLACK)]);
+        assert_eq!(stops(&[RED]), [(0.0, RED)]);
+        assert!(stops(&[]).is_empty());
+
+        let paint: Paint = LinearGradient::vertical(stops(&[RED, BLACK])).into();
+        assert!(matches!(paint, Paint::LinearGradient(LinearGradient { y1, y2, .. }) if y1 == 1.0 && y2 == 0.0));
+        assert_eq!(Paint::from(RED), Paint::Solid(RED));
+        assert_eq!(LinearGradient::between(0.0, 5.0, 100.0, 5.0, vec![]).units, GradientUnits::UserSpace);
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::cmp::Eq;
+use std::hash::Hash;
+use std::fmt::Debug;
+
+use crate::scale::Scale;
+
+/// Splits a continuous range in uniform bands, one per value of the domain.
+/// Values are mapped to the start of their band, values not in the domain to NaN
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, Builder, Clone, PartialEq)]
+#[builder(setter(into), build_fn(skip))]
+pub struct BandScale<D> where D: Eq + Hash + Clone + Debug {
+    /// See `set_domain`, the index of the values is kept with it
+    domain: Vec<D>,
+    pub range: [f64; 2],
+    /// Fraction of the step between bands, in [0, 1]
+    pub padding_inner: f64,
+    /// Fraction of the step before the first and after the last band
+    pub padding_outer: f64,
+    /// How the outer space is distributed, 0 leaves it all at the end and 1 at the start
+    pub align: f64,
+    pub round: bool,
+    #[builder(setter(skip))]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    index: HashMap<D, usize>,
+}
+
+#[allow(dead_code)]
+impl<D> BandScaleBuilder<D> where D: Eq + Hash + Clone + Debug {
+    /// Sets the inner and outer paddings
+    pub fn padding(&mut self, padding: f64) -> &mut Self {
+        self.padding_inner = Some(padding);
+        self.padding_outer = Some(padding);
+        self
+    }
+
+    pub fn build(&self) -> Result<BandScale<D>, String> {
+        let domain = Clone::clone(self.domain.as_ref()
+                .ok_or("domain must be initialized")?);
+        let padding_inner = self.padding_inner.unwrap_or(0.0);
+        if !(0.0..=1.0).contains(&padding_inner) {
+            return Err(format!("padding_inner must be in [0, 1], got {}", padding_inner));
+        }
+        let mut scale = BandScale::new(domain, self.range.unwrap_or([0.0, 1.0]));
+        scale.padding_inner = padding_inner;
+        scale.padding_outer = self.padding_outer.unwrap_or(0.0);
+        scale.align = self.align.unwrap_or(0.5);
+        scale.round = self.round.unwrap_or(false);
+        Ok(scale)
+    }
+}
+
+impl<D> BandScale<D> where D: Eq + Hash + Clone + Debug {
+    pub fn new(domain: Vec<D>, range: [f64; 2]) -> BandScale<D> {
+        let mut scale = BandScale { domain: vec![], range, padding_inner: 0.0, padding_outer: 0.0, align: 0.5, round: false, index: HashMap::new() };
+        scale.set_domain(domain);
+        scale
+    }
+
+    /// Replaces the domain and rebuilds the index of its values
+    pub fn set_domain(&mut self, domain: Vec<D>) {
+        self.index.clear();
+        for (i, x) in domain.iter().enumerate() {
+            self.index.entry(x.clone()).or_insert(i);
+        }
+        self.domain = domain;
+    }
+
+    /// Distance between the starts of adjacent bands
+    pub fn step(&self) -> f64 {
+        let n = self.domain.len() as f64;
+        let step = (self.range[1] - self.range[0]).abs()
+            / (n - self.padding_inner + self.padding_outer * 2.0).max(1.0);
+        if self.round { step.floor() } else { step }
+    }
+
+    /// Width of each band
+    pub fn bandwidth(&self) -> f64 {
+        let bandwidth = self.step() * (1.0 - self.padding_inner);
+        if self.round { bandwidth.round() } else { bandwidth }
+    }
+
+    /// Start of the band of the i-th value of the domain
+    fn position(&self, i: usize) -> f64 {
+        let n = self.domain.len();
+        let reverse = self.range[1] < self.range[0];
+        let (start, stop) = if reverse { (self.range[1], self.range[0]) } else { (self.range[0], self.range[1]) };
+        let step = self.step();
+        let mut start = start + (stop - start - step * (n as f64 - self.padding_inner)) * self.align;
+        if self.round { start = start.round() };
+        let i = if reverse { n - 1 - i } else { i };
+        start + step * i as f64
+    }
+
+    fn get(&self, x: &D) -> f64 {
+        self.index.get(x).map(|&i| self.position(i)).unwrap_or(f64::NAN)
+    }
+
+    pub fn call(&self, data: &[D]) -> Vec<f64> {
+        data.iter().map(|x| self.get(x)).collect()
+    }
+}
+
+/// The index is rebuilt from the domain
+#[cfg(feature = "serde")]
+impl<'de, D> serde::Deserialize<'de> for BandScale<D> where D: Eq + Hash + Clone + Debug + serde::Deserialize<'de> {
+    fn deserialize<De: serde::Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        #[derive(serde::Deserialize)]
+        struct Fields<D> { domain: Vec<D>, range: [f64; 2], padding_inner: f64, padding_outer: f64, align: f64, round: bool }
+
+        let fields: Fields<D> = Fields::deserialize(deserializer)?;
+        let mut scale = BandScale::new(fields.domain, fields.range);
+        scale.padding_inner = fields.padding_inner;
+        scale.padding_outer = fields.padding_outer;
+        scale.align = fields.align;
+        scale.round = fields.round;
+        Ok(scale)
+    }
+}
+
+impl<D> Scale<D, f64> for BandScale<D> where D: Eq + Hash + Clone + Debug + 'static {
+    fn call_one(&self, x: &D) -> f64 {
+        self.get(x)
+    }
+
+    fn domain(&self) -> Vec<D> { self.domain.clone() }
+
+    fn range(&self) -> Vec<f64> { self.range.to_vec() }
+
+    /// The whole domain, `count` is ignored
+    fn ticks(&self, _count: usize) -> Vec<D> { self.domain.clone() }
+
+    fn copy(&self) -> Box<dyn Scale<D, f64>> { Box::new(self.clone()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn call_works() {
+        let scale: BandScale<&str> = BandScaleBuilder::default()
+            .domain(vec!["a", "b", "c"])
+            .range([0.0, 120.0])
+            .build().unwrap();
+        assert_eq!(scale.call(&["a", "b", "c", "d"])[..3], [0.0, 40.0, 80.0]);
+        assert!(scale.call_one(&"d").is_nan());
+        assert_eq!(scale.bandwidth(), 40.0);
+
+        let reversed = BandScale::new(vec!["a", "b", "c"], [120.0, 0.0]);
+        assert_eq!(reversed.call(&["a", "b", "c"]), [80.0, 40.0, 0.0]);
+    }
+
+    #[test]
+    fn padding_works() {
+        let scale: BandScale<&str> = BandScaleBuilder::default()
+            .domain(vec!["a", "b"])
+            .range([0.0, 100.0])
+            .padding(0.5)
+            .build().unwrap();
+        assert_eq!(scale.step(), 40.0);
+        assert_eq!(scale.bandwidth(), 20.0);
+        assert_eq!(scale.call(&["a", "b"]), [20.0, 60.0]);
+
+        let scale: BandScale<&str> = BandScaleBuilder::default()
+            .domain(vec!["a", "b", "c"])
+            .range([0.0, 100.0])
+            .padding_inner(0.2)
+            .round(true)
+            .build().unwrap();
+        assert_eq!(scale.step(), 35.0);
+        assert_eq!(scale.bandwidth(), 28.0);
+        assert_eq!(scale.call(&["a", "b", "c"]), [1.0, 36.0, 71.0]);
+    }
+
+    #[test]
+    fn set_domain_works() {
+        let mut scale = BandScale::new(vec!["a", "b"], [0.0, 100.0]);
+        scale.set_domain(vec!["c", "b", "a", "d"]);
+        assert_eq!(scale.call(&["a", "b", "c", "d"]), [50.0, 25.0, 0.0, 75.0]);
+        assert_eq!(scale.domain(), ["c", "b", "a", "d"]);
+    }
+
+    #[test]
+    fn padding_is_checked() {
+        for &padding in &[-0.1, 1.5, f64::NAN] {
+            let scale: Result<BandScale<&str>, String> = BandScaleBuilder::default()
+                .domain(vec!["a", "b"])
+                .range([0.0, 100.0])
+                .padding_inner(padding)
+                .build();
+            assert!(scale.is_err());
+        }
+    }
+}
@@ -1,33 +1,67 @@
 
-use crate::scale::interpolate;
+use crate::scale::{interpolate, interpolate_one, invert, invert_one, ticks, Scale, ContinuousScale};
+use crate::interpolate::Interpolate;
 
-#[derive(Debug, Builder, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Builder, Clone, Default, PartialEq)]
 #[builder(setter(into))]
-pub struct LinearScale {
+pub struct LinearScale<R = f64> where R: Interpolate {
     #[builder(default = "[0.0, 1.0]")]
     pub domain:[f64; 2],
-    #[builder(default = "[0.0, 1.0]")]
-    pub range: [f64; 2],
+    #[builder(default = "R::unit_range()")]
+    pub range: [R; 2],
     #[builder(default)]
     pub clamp: bool,
     #[builder(default)]
     pub round: bool
 }
 
-impl LinearScale {
+impl<R: Interpolate> LinearScale<R> {
+
+    pub fn call(&self, data: &[f64]) -> Vec<R> {
+        interpolate(data, &self.domain, &self.range, self.clamp, self.round, |x| {x})
+    }
 
-    pub fn call(&self, data: &[f64]) -> Vec<f64> {
-        interpolate(data, &self.domain, &self.range, self.clamp, self.round, |x| {x}, |x| {x}, |x| {x})
+    /// Extends the domain to round values, see `ticks::nice`
+    pub fn nice(mut self, count: usize) -> Self {
+        self.domain = ticks::nice(self.domain[0], self.domain[1], count);
+        self
     }
+}
+
+impl LinearScale<f64> {
 
     pub fn invert(&self, data: &[f64]) -> Vec<f64> {
-        interpolate(data, &self.range, &self.domain, self.clamp, self.round, |x| {x}, |x| {x}, |x| {x})
+        invert(data, &self.domain, &self.range, self.clamp, self.round, |x| {x}, |x| {x})
+    }
+}
+
+impl<R: Interpolate + 'static> Scale<f64, R> for LinearScale<R> {
+    fn call_one(&self, x: &f64) -> R {
+        interpolate_one(*x, &self.domain, &self.range, self.clamp, self.round, &|x| {x})
+    }
+
+    fn domain(&self) -> Vec<f64> { self.domain.to_vec() }
+
+    fn range(&self) -> Vec<R> { self.range.to_vec() }
+
+    fn ticks(&self, count: usize) -> Vec<f64> {
+        ticks::ticks(self.domain[0], self.domain[1], count)
+    }
+
+    fn copy(&self) -> Box<dyn Scale<f64, R>> { Box::new(self.clone()) }
+}
+
+impl ContinuousScale<f64> for LinearScale<f64> {
+    fn invert_one(&self, y: f64) -> f64 {
+        invert_one(y, &self.domain, &self.range, self.clamp, self.round, &|x| {x}, &|x| {x})
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::color::{Color, BLUE, RED};
 
     #[test]
     fn builder_works() {
@@ -82,7 +116,7 @@ mod tests {
         scale.clamp = false;
         scale.domain = [10.0, 20.0];
         
-        assert_eq!( scale.clamp, false);
+        assert!( !scale.clamp );
         assert_eq!( scale.domain, [10.0, 20.0]);
     }  
 
@@ -97,4 +131,43 @@ mod tests {
         assert_eq!( scale.call(&[0.1, 1.1, 2.1, 3.1]), [1.0, 11.0, 21.0, 31.0] );
         assert_eq!( scale.invert(&[0.1, 10.1, 20.1, 30.1]), &[0.0, 1.0, 2.0, 3.0] );
     }
+
+    #[test]
+    fn color_range_works() {
+        let scale: LinearScale<Color> = LinearScaleBuilder::default()
+            .domain([0.0, 10.0])
+            .range([BLUE, RED])
+            .build().unwrap();
+
+        assert_eq!( scale.call(&[0.0, 5.0, 10.0]), [BLUE, Color{r: 128, g: 0, b: 128, a: 1.0}, RED] );
+    }
+
+    #[test]
+    fn point_range_works() {
+        let scale: LinearScale<(f64, f64)> = LinearScaleBuilder::default()
+            .domain([0.0, 10.0])
+            .range([(0.0, 100.0), (100.0, 0.0)])
+            .round(true)
+            .build().unwrap();
+
+        assert_eq!( scale.call(&[0.0, 2.5, 10.0]), [(0.0, 100.0), (25.0, 75.0), (100.0, 0.0)] );
+    }
+
+    #[test]
+    fn scale_trait_works() {
+        let scale: LinearScale = LinearScaleBuilder::default()
+            .domain([0.0, 10.0])
+            .range([0.0, 100.0])
+            .build().unwrap();
+
+        let boxed: Box<dyn ContinuousScale<f64>> = Box::new(scale);
+        assert_eq!( boxed.call_one(&5.0), 50.0 );
+        assert_eq!( boxed.invert_one(50.0), 5.0 );
+        assert_eq!( boxed.domain(), [0.0, 10.0] );
+        assert_eq!( boxed.range(), [0.0, 100.0] );
+        assert_eq!( boxed.ticks(2), [0.0, 5.0, 10.0] );
+
+        let copy: Box<dyn Scale<f64, f64>> = boxed.copy();
+        assert_eq!( copy.call(&[0.0, 10.0]), [0.0, 100.0] );
+    }
 }
\ No newline at end of file
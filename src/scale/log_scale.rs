@@ -1,23 +1,43 @@
-use crate::scale::interpolate;
+use crate::scale::{interpolate_one, invert, invert_one, ticks, Scale, ContinuousScale};
+use crate::interpolate::Interpolate;
 use crate::utils::InDelta;
 use std::f64::consts::{E};
 
-#[derive(Debug, Builder, Default, PartialEq)]
-#[builder(setter(into))]
-pub struct LogScale {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Builder, Clone, Default, PartialEq)]
+#[builder(setter(into), build_fn(validate = "Self::validate"))]
+pub struct LogScale<R = f64> where R: Interpolate + Default {
+    /// Must be strictly positive or strictly negative. Negative domains are reflected: -log(-x)
     #[builder(default = "[1.0, 10.0]")]
     pub domain:[f64; 2],
-    #[builder(default = "[0.0, 1.0]")]
-    pub range: [f64; 2],
+    #[builder(default = "R::unit_range()")]
+    pub range: [R; 2],
     #[builder(default)]
     pub clamp: bool,
     #[builder(default)]
     pub round: bool,
+    /// Finite and greater than 1
     #[builder(default = "10.0")]
     pub base: f64,
+    /// Returned for values outside the sign of the domain (like 0) and NaN. `R::undefined()` when None, NaN for numbers
+    #[builder(default)]
+    pub unknown: Option<R>,
 }
 
-impl LogScale {
+impl<R: Interpolate + Default> LogScaleBuilder<R> {
+    fn validate(&self) -> Result<(), String> {
+        match self.base {
+            Some(base) if !(base > 1.0 && base.is_finite()) => Err(format!("base must be finite and greater than 1, not {}", base)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<R: Interpolate + Default> LogScale<R> {
+
+    fn reflected(&self) -> bool {
+        self.domain[0] < 0.0
+    }
 
     fn ease(&self) -> impl Fn(f64) -> f64 {
         let mut f: fn(x:f64, base:f64) -> f64 = |x, base| x.log(base);
@@ -31,7 +51,8 @@ impl LogScale {
             f = |x, _base| x.log2()
         }
         let base = self.base;
-        move |x: f64| f(x, base)
+        let sign = if self.reflected() { -1.0 } else { 1.0 };
+        move |x: f64| sign * f(sign * x, base)
     }
     fn inv_ease(&self) -> impl Fn(f64) -> f64 { 
         let mut f: fn(x:f64, base:f64) -> f64 = |x, base| base.powf(x);
@@ -42,15 +63,81 @@ impl LogScale {
             f = |x, _base| x.exp2();
         }
         let base = self.base;
-        move |x| f(x, base)
+        let sign = if self.reflected() { -1.0 } else { 1.0 };
+        move |x| sign * f(sign * x, base)
+    }
+
+    /// Whether the logarithm of `x` exists for the sign of the domain. Clamped values always do
+    fn is_defined(&self, x: f64) -> bool {
+        if x.is_nan() { return false };
+        self.clamp || if self.reflected() { x < 0.0 } else { x > 0.0 }
+    }
+
+    fn scale(&self, x: f64, ease: &impl Fn(f64) -> f64) -> R {
+        if !self.is_defined(x) { return self.unknown.clone().unwrap_or_else(R::undefined) };
+        interpolate_one(x, &self.domain, &self.range, self.clamp, self.round, ease)
     }
    
-    pub fn call(&self, data: &[f64]) -> Vec<f64> {
-        interpolate(data, &self.domain, &self.range, self.clamp, self.round, self.ease(), |x| {x}, |x| {x})
+    pub fn call(&self, data: &[f64]) -> Vec<R> {
+        let ease = self.ease();
+        data.iter().map(|&x| self.scale(x, &ease)).collect()
     }
+}
+
+impl LogScale<f64> {
 
     pub fn invert(&self, data: &[f64]) -> Vec<f64> {
-        interpolate(data, &self.range, &self.domain, self.clamp, self.round, |x| {x}, self.ease(), self.inv_ease())
+        invert(data, &self.domain, &self.range, self.clamp, self.round, self.ease(), self.inv_ease())
+    }
+}
+
+impl<R: Interpolate + Default + 'static> Scale<f64, R> for LogScale<R> {
+    fn call_one(&self, x: &f64) -> R {
+        self.scale(*x, &self.ease())
+    }
+
+    fn domain(&self) -> Vec<f64> { self.domain.to_vec() }
+
+    fn range(&self) -> Vec<R> { self.range.to_vec() }
+
+    /// Powers of the base, subdivided by the integer multiples of small bases when the domain spans few of them.
+    /// None for invalid bases, like those of deserialized scales, and domains with an infinite logarithm
+    fn ticks(&self, count: usize) -> Vec<f64> {
+        if !(self.base > 1.0 && self.base.is_finite()) { return vec![] };
+        let (log, pow) = (self.ease(), self.inv_ease());
+        let reverse = self.domain[1] < self.domain[0];
+        let (u, v) = if reverse { (self.domain[1], self.domain[0]) } else { (self.domain[0], self.domain[1]) };
+        let (i, j) = (log(u), log(v));
+        if !(i.is_finite() && j.is_finite()) { return vec![] };
+        let n = count as f64;
+        let mut z = vec![];
+        // Bigger bases would have too many multiples per power
+        if self.base.fract() == 0.0 && self.base <= 16.0 && j - i < n {
+            let multiples: Vec<f64> = (1..self.base as i64).map(|k| k as f64).collect();
+            for exp in i.floor() as i64..=j.ceil() as i64 {
+                // Walk each decade upwards, which for reflected domains means from the biggest multiple
+                let ks: Box<dyn Iterator<Item=&f64>> = if u > 0.0 { Box::new(multiples.iter()) } else { Box::new(multiples.iter().rev()) };
+                for k in ks {
+                    let t = if (exp < 0) == (u > 0.0) { k / pow(-exp as f64) } else { k * pow(exp as f64) };
+                    if t < u { continue };
+                    if t > v { break };
+                    z.push(t);
+                }
+            }
+            if ((z.len() * 2) as f64) < n { z = ticks::ticks(u, v, count) };
+        } else {
+            z = ticks::ticks(i, j, (j - i).min(n) as usize).into_iter().map(pow).collect();
+        }
+        if reverse { z.reverse() };
+        z
+    }
+
+    fn copy(&self) -> Box<dyn Scale<f64, R>> { Box::new(self.clone()) }
+}
+
+impl ContinuousScale<f64> for LogScale<f64> {
+    fn invert_one(&self, y: f64) -> f64 {
+        invert_one(y, &self.domain, &self.range, self.clamp, self.round, &self.ease(), &self.inv_ease())
     }
 }
 
@@ -59,14 +146,13 @@ impl LogScale {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::f64::{ NAN, INFINITY };
 
     #[test]
     fn builder_works() {
         let scale: LogScale = LogScaleBuilder::default()
             .clamp(false)
             .build().unwrap();
-        assert_eq!(scale, LogScale{range: [0.0, 1.0], domain: [1.0, 10.0], clamp: false, round: false, base: 10.0});
+        assert_eq!(scale, LogScale{range: [0.0, 1.0], domain: [1.0, 10.0], clamp: false, round: false, base: 10.0, unknown: None});
     }
 
     #[test]
@@ -77,10 +163,10 @@ mod tests {
             .base(10.0)
             .build().unwrap();
 
+        // Undefined, rather than a valid pixel
         let res = scale.call(&[-1.0, 0.0, 0.1, 1.0, 10.0, 100.0, 1000.0]);
-        let expect = [NAN, -INFINITY, -1.0, 0.0, 1.0, 2.0, 3.0];
-        assert!(res[0].is_nan());
-        assert_eq!(res[1..], expect[1..]);
+        assert!( res[0].is_nan() && res[1].is_nan() );
+        assert_eq!( res[2..], [-1.0, 0.0, 1.0, 2.0, 3.0]);
         
         scale.domain = [1.0, 2.0];
         scale.base = 2.0;
@@ -102,7 +188,7 @@ mod tests {
         let mut scale: LogScale = LogScaleBuilder::default()
             .build().unwrap();
 
-        assert_eq!( scale.invert(&[-INFINITY,-10.0, -1.0, 0.0, 1.0, 2.0, 3.0]), [0.0, 0.0000000001, 0.1, 1.0, 10.0, 100.0, 1000.0]);
+        assert_eq!( scale.invert(&[-f64::INFINITY,-10.0, -1.0, 0.0, 1.0, 2.0, 3.0]), [0.0, 0.0000000001, 0.1, 1.0, 10.0, 100.0, 1000.0]);
 
         scale.domain = [1.0, 2.0];
         scale.base = 2.0;
@@ -151,4 +237,73 @@ mod tests {
         assert_eq!( scale.invert(&[-1.0, 0.0, 0.1, 0.2]), [0.0, 1.0, 1.0, 2.0]);
         // 
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn ticks_works() {
+        let mut scale: LogScale = LogScaleBuilder::default()
+            .domain([1.0, 100.0])
+            .build().unwrap();
+
+        assert_eq!( scale.ticks(10), [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 20.0, 30.0, 40.0, 50.0, 60.0, 70.0, 80.0, 90.0, 100.0] );
+
+        scale.domain = [1.0, 1e9];
+        assert_eq!( scale.ticks(5), [1.0, 100.0, 10000.0, 1000000.0, 100000000.0] );
+
+        scale.domain = [0.01, 0.1];
+        assert_eq!( scale.ticks(10), [0.01, 0.02, 0.03, 0.04, 0.05, 0.06, 0.07, 0.08, 0.09, 0.1] );
+
+        scale.domain = [100.0, 1.0];
+        assert_eq!( scale.ticks(2), [100.0, 10.0, 1.0] );
+
+        // Powers only
+        scale.domain = [1.0, 1e18];
+        scale.base = 1e9;
+        assert_eq!( scale.ticks(10), [1.0, 1e9, 1e18] );
+        scale.base = 1.0;
+        assert!( scale.ticks(10).is_empty() );
+        scale.base = 10.0;
+        scale.domain = [0.0, 10.0];
+        assert!( scale.ticks(10).is_empty() );
+    }
+
+    #[test]
+    fn base_is_validated() {
+        for base in [1.0, 0.5, -2.0, f64::NAN, f64::INFINITY] {
+            assert!( LogScaleBuilder::<f64>::default().base(base).build().is_err(), "{}", base );
+        }
+        assert!( LogScaleBuilder::<f64>::default().base(2.0).build().is_ok() );
+    }
+
+    #[test]
+    fn negative_domain_works() {
+        let mut scale: LogScale = LogScaleBuilder::default()
+            .domain([-1000.0, -1.0])
+            .range([0.0, 3.0])
+            .build().unwrap();
+
+        assert_eq!( scale.call(&[-1000.0, -100.0, -10.0, -1.0]), [0.0, 1.0, 2.0, 3.0] );
+        assert_eq!( scale.invert(&[0.0, 1.0, 2.0, 3.0]), [-1000.0, -100.0, -10.0, -1.0] );
+        assert_eq!( scale.ticks(3), [-1000.0, -100.0, -10.0, -1.0] );
+
+        scale.domain = [-50.0, -1.0];
+        assert_eq!( scale.ticks(10), [-50.0, -40.0, -30.0, -20.0, -10.0, -9.0, -8.0, -7.0, -6.0, -5.0, -4.0, -3.0, -2.0, -1.0] );
+    }
+
+    #[test]
+    fn unknown_works() {
+        let mut scale: LogScale = LogScaleBuilder::default()
+            .domain([1.0, 100.0])
+            .range([0.0, 100.0])
+            .unknown(-1.0)
+            .build().unwrap();
+
+        assert_eq!( scale.call(&[-10.0, 0.0, f64::NAN, 10.0]), [-1.0, -1.0, -1.0, 50.0] );
+        assert_eq!( scale.call_one(&0.0), -1.0 );
+
+        scale.domain = [-100.0, -1.0];
+        assert_eq!( scale.call(&[10.0, 0.0, -10.0]), [-1.0, -1.0, 50.0] );
+
+        scale.clamp = true;
+        assert_eq!( scale.call(&[10.0, 0.0, -1000.0]), [100.0, 100.0, 0.0] );
+    }
+}
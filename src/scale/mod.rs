@@ -1,31 +1,115 @@
-use std::f64::NAN;
+use crate::interpolate::Interpolate;
 
-fn interpolate(
-    data: &[f64], 
-    domain: &[f64; 2], 
-    range: &[f64; 2], 
+/// Maps values of a domain `D` into values of a range `R`
+pub trait Scale<D, R> {
+    fn call_one(&self, x: &D) -> R;
+
+    fn call(&self, data: &[D]) -> Vec<R> {
+        data.iter().map(|x| self.call_one(x)).collect()
+    }
+
+    fn domain(&self) -> Vec<D>;
+
+    fn range(&self) -> Vec<R>;
+
+    /// Representative values of the domain, approximately `count` for continuous scales
+    fn ticks(&self, count: usize) -> Vec<D>;
+
+    fn copy(&self) -> Box<dyn Scale<D, R>>;
+}
+
+/// Scales with a continuous numeric range that can be inverted
+pub trait ContinuousScale<D>: Scale<D, f64> {
+    fn invert_one(&self, y: f64) -> D;
+
+    fn invert(&self, data: &[f64]) -> Vec<D> {
+        data.iter().map(|&y| self.invert_one(y)).collect()
+    }
+}
+
+/// Scales with a discrete domain, where values outside of it are mapped to `unknown`
+pub trait DiscreteScale<D, R>: Scale<D, R> {
+    fn unknown(&self) -> R;
+}
+
+impl<D, R> Clone for Box<dyn Scale<D, R>> {
+    fn clone(&self) -> Self {
+        self.copy()
+    }
+}
+
+/// Maps `x` from `domain` to t € [0,1], eased by `ease`
+fn normalize(x: f64, domain: &[f64; 2], clamp: bool, ease: &impl Fn(f64) -> f64) -> f64 {
+    if x.is_nan() { return f64::NAN };
+    let d0 = ease(domain[0]);
+    let d1 = ease(domain[1]);
+    let x_in = if clamp { domain[1].min(x).max(domain[0]) } else { x };
+    (ease(x_in) - d0) / (d1 - d0)
+}
+
+fn interpolate_one<R: Interpolate>(
+    x: f64,
+    domain: &[f64; 2],
+    range: &[R; 2],
+    clamp: bool,
+    round: bool,
+    ease: &impl Fn(f64) -> f64
+) -> R
+{
+    let unit = normalize(x, domain, clamp, ease);          // deinterpolate  f(x) -> t ; t € [0,1]
+    let out = R::interpolate(&range[0], &range[1], unit); // reinterpolate  f(t) -> y
+    if round { out.round() } else { out }
+}
+
+fn interpolate<R: Interpolate>(
+    data: &[f64],
+    domain: &[f64; 2],
+    range: &[R; 2],
+    clamp: bool,
+    round: bool,
+    ease: impl Fn(f64) -> f64
+) -> Vec<R>
+{
+    data.iter()
+        .map(|&x: &f64| interpolate_one(x, domain, range, clamp, round, &ease))
+        .collect()
+}
+
+fn invert_one(
+    y: f64,
+    domain: &[f64; 2],
+    range: &[f64; 2],
     clamp: bool,
     round: bool,
-    d_ease: impl Fn(f64) -> f64,
-    r_ease: impl Fn(f64) -> f64,
+    ease: &impl Fn(f64) -> f64,
+    inv_ease: &impl Fn(f64) -> f64
+) -> f64
+{
+    let d0 = ease(domain[0]);
+    let d1 = ease(domain[1]);
+    let unit = normalize(y, range, clamp, &|x| x);
+    let out = inv_ease((unit * (d1 - d0)) + d0);
+    if round { out.round() } else { out }
+}
+
+fn invert(
+    data: &[f64],
+    domain: &[f64; 2],
+    range: &[f64; 2],
+    clamp: bool,
+    round: bool,
+    ease: impl Fn(f64) -> f64,
     inv_ease: impl Fn(f64) -> f64
-) -> Vec<f64> 
+) -> Vec<f64>
 {
     data.iter()
-        .map(|&x: &f64| {
-            if x.is_nan() { return NAN};
-            let d0 = d_ease(domain[0]); 
-            let d1 = d_ease(domain[1]);
-            let r0 = r_ease(range[0]);
-            let r1 = r_ease(range[1]);
-            let x_in = if clamp { domain[1].min(x).max(domain[0]) } else { x };
-            let unit = (d_ease(x_in) - d0) / (d1 - d0);   // deinterpolate  f(x) -> t ; t € [0,1]
-            let out = inv_ease((unit * (r1 - r0)) + r0);  // reinterpolate  f(t) -> y
-            if round { out.round() } else { out }
-        })
+        .map(|&y: &f64| invert_one(y, domain, range, clamp, round, &ease, &inv_ease))
         .collect()
 }
 
+pub mod ticks;
+pub mod time_ticks;
+
 pub mod linear_scale;
 pub use self::linear_scale::{LinearScale, LinearScaleBuilder};
 
@@ -35,8 +119,14 @@ pub use self::pow_scale::{PowScale, PowScaleBuilder};
 pub mod log_scale;
 pub use self::log_scale::{LogScale, LogScaleBuilder};
 
+pub mod symlog_scale;
+pub use self::symlog_scale::{SymlogScale, SymlogScaleBuilder};
+
 pub mod time_scale;
 pub use self::time_scale::{TimeScale, TimeScaleBuilder, DateTime};
 
 pub mod ordinal_scale;
-pub use self::ordinal_scale::{OrdinalScale, OrdinalScaleBuilder};
\ No newline at end of file
+pub use self::ordinal_scale::{OrdinalScale, OrdinalScaleBuilder};
+
+pub mod band_scale;
+pub use self::band_scale::{BandScale, BandScaleBuilder};
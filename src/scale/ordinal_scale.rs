@@ -1,21 +1,32 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std;
+use std::cell::RefCell;
 use std::cmp::Eq;
 use std::hash::Hash;
 use std::fmt::Debug;
 
-#[derive(Debug, Builder, Default, PartialEq)]
+use crate::scale::{Scale, DiscreteScale};
+
+#[derive(Debug, Builder, Clone, Default, PartialEq)]
 #[builder(setter(into), build_fn(skip))]
 pub struct OrdinalScale<D, R> where 
     D: Eq + Hash + Clone + Debug,
     R: Default + Clone + Debug
 {
     pub domain: Vec<D>,
+    /// Cycled when shorter than the domain
     pub range: Vec<R>,
     #[builder(default)]
     pub unknown: R,
+    /// When true, values not in the domain are appended to it, in first-seen order, instead of mapping to `unknown`
+    #[builder(default)]
+    pub implicit: bool,
+    #[builder(setter(skip))]
+    map: HashMap<D, R>,
+    #[builder(setter(skip))]
+    implicit_domain: RefCell<Vec<D>>,
     #[builder(setter(skip))]
-    map: HashMap<D, R>
+    implicit_map: RefCell<HashMap<D, R>>
 }
 
 #[allow(dead_code)]
@@ -30,14 +41,9 @@ impl<D, R> OrdinalScaleBuilder<D, R> where
                 .ok_or("range must be initialized")?);
         let unknown = Clone::clone(self.unknown.as_ref()
                 .unwrap_or(&Default::default()));
-        let map: HashMap<D, R> = izip!(domain.clone(), range.clone()).collect();
+        let implicit = self.implicit.unwrap_or(false);
         
-        Ok(OrdinalScale {
-            domain: domain,
-            range: range,
-            unknown: unknown,
-            map: map
-        })
+        Ok(OrdinalScale::new(domain, range, unknown, implicit))
     }
 }
 
@@ -45,18 +51,130 @@ impl<D, R> OrdinalScale<D, R> where
     D: Eq + Hash + Clone + Debug,
     R: Default + Clone + Debug
 {
+    fn new(domain: Vec<D>, range: Vec<R>, unknown: R, implicit: bool) -> OrdinalScale<D, R> {
+        let mut map: HashMap<D, R> = HashMap::new();
+        if !range.is_empty() {
+            for (i, x) in domain.iter().enumerate() {
+                map.entry(x.clone()).or_insert_with(|| range[i % range.len()].clone());
+            }
+        }
+
+        OrdinalScale {
+            domain,
+            range,
+            unknown,
+            implicit,
+            map,
+            implicit_domain: RefCell::new(vec![]),
+            implicit_map: RefCell::new(HashMap::new())
+        }
+    }
+
+    /// Implicit scale whose domain holds the unique values of `data` in first-seen order
+    pub fn from_data(data: &[D], range: Vec<R>) -> OrdinalScale<D, R> {
+        let mut seen: HashSet<&D> = HashSet::new();
+        let domain: Vec<D> = data.iter().filter(|x| seen.insert(x)).cloned().collect();
+        OrdinalScale::new(domain, range, Default::default(), true)
+    }
+
+    fn get(&self, x: &D) -> R {
+        if let Some(y) = self.map.get(x) { return y.clone() };
+        if !self.implicit || self.range.is_empty() { return self.unknown.clone() };
+        if let Some(y) = self.implicit_map.borrow().get(x) { return y.clone() };
+
+        let mut implicit_domain = self.implicit_domain.borrow_mut();
+        let y = self.range[(self.domain.len() + implicit_domain.len()) % self.range.len()].clone();
+        implicit_domain.push(x.clone());
+        self.implicit_map.borrow_mut().insert(x.clone(), y.clone());
+        y
+    }
 
     pub fn call(&self, data: &[D]) -> Vec<R> {
         data.iter()
-            .map(|x| { self.map.get(x).unwrap_or(&self.unknown).clone() })
+            .map(|x| { self.get(x) })
             .collect()
     }
 }
 
+/// Public fields of an ordinal scale, the maps are rebuilt from them
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct OrdinalFields<D, R> {
+    domain: Vec<D>,
+    range: Vec<R>,
+    unknown: R,
+    implicit: bool
+}
+
+/// Values appended to the domain of implicit scales are stored as part of it
+#[cfg(feature = "serde")]
+impl<D, R> serde::Serialize for OrdinalScale<D, R> where
+    D: Eq + Hash + Clone + Debug + serde::Serialize,
+    R: Default + Clone + Debug + serde::Serialize
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut domain = self.domain.clone();
+        domain.extend(self.implicit_domain.borrow().iter().cloned());
+        OrdinalFields { domain, range: self.range.clone(), unknown: self.unknown.clone(), implicit: self.implicit }
+            .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, D, R> serde::Deserialize<'de> for OrdinalScale<D, R> where
+    D: Eq + Hash + Clone + Debug + serde::Deserialize<'de>,
+    R: Default + Clone + Debug + serde::Deserialize<'de>
+{
+    fn deserialize<De: serde::Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        let fields: OrdinalFields<D, R> = OrdinalFields::deserialize(deserializer)?;
+        Ok(OrdinalScale::new(fields.domain, fields.range, fields.unknown, fields.implicit))
+    }
+}
+
+impl<D, R> Scale<D, R> for OrdinalScale<D, R> where
+    D: Eq + Hash + Clone + Debug + 'static,
+    R: Default + Clone + Debug + 'static
+{
+    fn call_one(&self, x: &D) -> R {
+        self.get(x)
+    }
+
+    /// The domain followed by the values implicitly appended to it
+    fn domain(&self) -> Vec<D> {
+        self.domain.iter().chain(self.implicit_domain.borrow().iter()).cloned().collect()
+    }
+
+    fn range(&self) -> Vec<R> { self.range.clone() }
+
+    /// The whole domain, `count` is ignored
+    fn ticks(&self, _count: usize) -> Vec<D> { Scale::domain(self) }
+
+    fn copy(&self) -> Box<dyn Scale<D, R>> { Box::new(self.clone()) }
+}
+
+impl<D, R> DiscreteScale<D, R> for OrdinalScale<D, R> where
+    D: Eq + Hash + Clone + Debug + 'static,
+    R: Default + Clone + Debug + 'static
+{
+    fn unknown(&self) -> R { self.unknown.clone() }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_works() {
+        let scale: OrdinalScale<String, f64> = OrdinalScale::from_data(&["a".to_string()], vec![1.0, 2.0, 3.0]);
+        scale.call(&["b".to_string()]);
+        let json = serde_json::to_string(&scale).unwrap();
+        assert_eq!(json, r#"{"domain":["a","b"],"range":[1.0,2.0,3.0],"unknown":0.0,"implicit":true}"#);
+
+        let loaded: OrdinalScale<String, f64> = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.call(&["b".to_string(), "c".to_string()]), [2.0, 3.0]);
+    }
+
     #[test]
     fn builder_works() {
         let scale: OrdinalScale<&str, &str> = OrdinalScaleBuilder::default()
@@ -85,4 +203,52 @@ mod tests {
         assert_eq!( scale.call(&["d", "b", "c", "a", "r"]), ["four", "two", "three", "one", "paco"] );
 
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn scale_trait_works() {
+        let scale: OrdinalScale<&str, &str> = OrdinalScaleBuilder::default()
+            .domain(vec!["a", "b"])
+            .range(vec!["one", "two"])
+            .unknown("none")
+            .build().unwrap();
+
+        let boxed: Box<dyn DiscreteScale<&str, &str>> = Box::new(scale);
+        assert_eq!( boxed.call_one(&"b"), "two" );
+        assert_eq!( boxed.call(&["a", "z"]), ["one", "none"] );
+        assert_eq!( boxed.ticks(10), ["a", "b"] );
+        assert_eq!( boxed.unknown(), "none" );
+        assert_eq!( boxed.copy().range(), ["one", "two"] );
+    }
+
+    #[test]
+    fn range_cycles() {
+        let scale: OrdinalScale<&str, &str> = OrdinalScaleBuilder::default()
+            .domain(vec!["a", "b", "c"])
+            .range(vec!["one", "two"])
+            .build().unwrap();
+
+        assert_eq!( scale.call(&["a", "b", "c", "d"]), ["one", "two", "one", ""] );
+    }
+
+    #[test]
+    fn implicit_works() {
+        let scale: OrdinalScale<&str, &str> = OrdinalScaleBuilder::default()
+            .domain(vec!["a"])
+            .range(vec!["one", "two", "three"])
+            .implicit(true)
+            .build().unwrap();
+
+        assert_eq!( scale.call(&["c", "a", "b", "c", "d"]), ["two", "one", "three", "two", "one"] );
+        assert_eq!( scale.domain, ["a"] );
+        assert_eq!( Scale::domain(&scale), ["a", "c", "b", "d"] );
+        assert_eq!( scale.call_one(&"b"), "three" );
+    }
+
+    #[test]
+    fn from_data_works() {
+        let scale: OrdinalScale<&str, &str> = OrdinalScale::from_data(&["z", "x", "z", "y"], vec!["one", "two"]);
+
+        assert_eq!( scale.domain, ["z", "x", "y"] );
+        assert_eq!( scale.call(&["x", "y", "z", "w"]), ["two", "one", "one", "two"] );
+    }
+}
@@ -1,13 +1,15 @@
 
-use crate::scale::interpolate;
+use crate::scale::{interpolate, interpolate_one, invert, invert_one, ticks, Scale, ContinuousScale};
+use crate::interpolate::Interpolate;
 
-#[derive(Debug, Builder, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Builder, Clone, Default, PartialEq)]
 #[builder(setter(into))]
-pub struct PowScale {
+pub struct PowScale<R = f64> where R: Interpolate {
     #[builder(default = "[0.0, 1.0]")]
     pub domain:[f64; 2],
-    #[builder(default = "[0.0, 1.0]")]
-    pub range: [f64; 2],
+    #[builder(default = "R::unit_range()")]
+    pub range: [R; 2],
     #[builder(default)]
     pub clamp: bool,
     #[builder(default)]
@@ -20,23 +22,48 @@ fn raise(x: f64, exp: f64) -> f64 {
     if x < 0.0 {-(x.powf(exp))} else {x.powf(exp)}
 }
 
-impl PowScale {
+impl<R: Interpolate> PowScale<R> {
 
-    fn ease(&self) -> (impl Fn(f64) -> f64) { 
+    fn ease(&self) -> impl Fn(f64) -> f64 { 
         let exp = self.exponent;
         move |x| raise(x, exp)
     }
-    fn inv_ease(&self) -> (impl Fn(f64) -> f64) { 
+    fn inv_ease(&self) -> impl Fn(f64) -> f64 { 
         let exp = self.exponent;
         move |x| raise(x, 1.0 / exp)
     }
    
-    pub fn call(&self, data: &[f64]) -> Vec<f64> {
-        interpolate(data, &self.domain, &self.range, self.clamp, self.round, self.ease(), |x| {x}, |x| {x})
+    pub fn call(&self, data: &[f64]) -> Vec<R> {
+        interpolate(data, &self.domain, &self.range, self.clamp, self.round, self.ease())
     }
+}
+
+impl PowScale<f64> {
 
     pub fn invert(&self, data: &[f64]) -> Vec<f64> {
-        interpolate(data, &self.range, &self.domain, self.clamp, self.round, |x| {x}, self.ease(), self.inv_ease())
+        invert(data, &self.domain, &self.range, self.clamp, self.round, self.ease(), self.inv_ease())
+    }
+}
+
+impl<R: Interpolate + 'static> Scale<f64, R> for PowScale<R> {
+    fn call_one(&self, x: &f64) -> R {
+        interpolate_one(*x, &self.domain, &self.range, self.clamp, self.round, &self.ease())
+    }
+
+    fn domain(&self) -> Vec<f64> { self.domain.to_vec() }
+
+    fn range(&self) -> Vec<R> { self.range.to_vec() }
+
+    fn ticks(&self, count: usize) -> Vec<f64> {
+        ticks::ticks(self.domain[0], self.domain[1], count)
+    }
+
+    fn copy(&self) -> Box<dyn Scale<f64, R>> { Box::new(self.clone()) }
+}
+
+impl ContinuousScale<f64> for PowScale<f64> {
+    fn invert_one(&self, y: f64) -> f64 {
+        invert_one(y, &self.domain, &self.range, self.clamp, self.round, &self.ease(), &self.inv_ease())
     }
 }
 
@@ -113,4 +140,17 @@ mod tests {
         assert_eq!( scale.call(&[0.5,1.0,2.0,3.0,4.0]), [ 1.0, 1.0, 2.0, 3.0, 5.0 ]);
         assert_eq!( scale.invert(&[ 0.8125, 1.0, 1.75, 3.0, 4.75 ]), [1.0,1.0,2.0,3.0,4.0]);
     }
+
+    #[test]
+    fn scale_trait_works() {
+        let scale: PowScale = PowScaleBuilder::default()
+            .domain([0.0, 2.0])
+            .range([0.0, 4.0])
+            .build().unwrap();
+
+        let boxed: Box<dyn ContinuousScale<f64>> = Box::new(scale);
+        assert_eq!( boxed.call_one(&1.0), 1.0 );
+        assert_eq!( boxed.invert_one(1.0), 1.0 );
+        assert_eq!( boxed.ticks(4), [0.0, 0.5, 1.0, 1.5, 2.0] );
+    }
 }
\ No newline at end of file
@@ -0,0 +1,199 @@
+use crate::scale::{interpolate, interpolate_one, invert, invert_one, ticks, Scale, ContinuousScale};
+use crate::interpolate::Interpolate;
+
+/// Bi-symmetric log transformation, linear around zero and logarithmic beyond `constant`.
+/// See "A bi-symmetric log transformation for wide-range data" by Webber
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Builder, Clone, Default, PartialEq)]
+#[builder(setter(into), build_fn(validate = "Self::validate"))]
+pub struct SymlogScale<R = f64> where R: Interpolate {
+    #[builder(default = "[0.0, 1.0]")]
+    pub domain:[f64; 2],
+    #[builder(default = "R::unit_range()")]
+    pub range: [R; 2],
+    #[builder(default)]
+    pub clamp: bool,
+    #[builder(default)]
+    pub round: bool,
+    /// Finite and positive
+    #[builder(default = "1.0")]
+    pub constant: f64,
+}
+
+impl<R: Interpolate> SymlogScaleBuilder<R> {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(constant) = self.constant {
+            if !(constant > 0.0 && constant.is_finite()) { return Err(format!("constant must be finite and positive, not {}", constant)) };
+        }
+        if let Some(domain) = self.domain {
+            if !domain.iter().all(|x| x.is_finite()) { return Err(format!("domain must be finite, not {:?}", domain)) };
+        }
+        Ok(())
+    }
+}
+
+impl<R: Interpolate> SymlogScale<R> {
+
+    fn ease(&self) -> impl Fn(f64) -> f64 {
+        let c = self.constant;
+        move |x| x.signum() * (x.abs() / c).ln_1p()
+    }
+    fn inv_ease(&self) -> impl Fn(f64) -> f64 {
+        let c = self.constant;
+        move |x| x.signum() * x.abs().exp_m1() * c
+    }
+
+    pub fn call(&self, data: &[f64]) -> Vec<R> {
+        interpolate(data, &self.domain, &self.range, self.clamp, self.round, self.ease())
+    }
+}
+
+impl SymlogScale<f64> {
+
+    pub fn invert(&self, data: &[f64]) -> Vec<f64> {
+        invert(data, &self.domain, &self.range, self.clamp, self.round, self.ease(), self.inv_ease())
+    }
+}
+
+impl<R: Interpolate + 'static> Scale<f64, R> for SymlogScale<R> {
+    fn call_one(&self, x: &f64) -> R {
+        interpolate_one(*x, &self.domain, &self.range, self.clamp, self.round, &self.ease())
+    }
+
+    fn domain(&self) -> Vec<f64> { self.domain.to_vec() }
+
+    fn range(&self) -> Vec<R> { self.range.to_vec() }
+
+    /// Zero and the powers of ten beyond `constant` on each side, at most `count` of them, or linear ticks
+    /// when the domain spans too few of them. None for invalid constants or domains, like those of deserialized scales
+    fn ticks(&self, count: usize) -> Vec<f64> {
+        let reverse = self.domain[1] < self.domain[0];
+        let (u, v) = if reverse { (self.domain[1], self.domain[0]) } else { (self.domain[0], self.domain[1]) };
+        let valid = self.constant > 0.0 && self.constant.is_finite() && u.is_finite() && v.is_finite();
+        if count == 0 || !valid { return vec![] };
+        // Exponents of finite numbers are within these bounds
+        let magnitude = |x: f64| if x == 0.0 { -323.0 } else { x.abs().log10().floor() };
+        let lowest = self.constant.log10().ceil().max(-323.0) as i32;
+        let highest = magnitude(u).max(magnitude(v)).min(308.0) as i32;
+
+        let exponents: Vec<i32> = (lowest..=highest).collect();
+        // The powers are shared between the sides of zero
+        let zero = (u <= 0.0 && v >= 0.0) as usize;
+        let sides = (u < 0.0) as usize + (v > 0.0) as usize;
+        let per_side = count.saturating_sub(zero) / sides.max(1);
+        let every = exponents.len().div_ceil(per_side.max(1)).max(1);
+        let powers: Vec<f64> = exponents.iter().rev().step_by(every).take(per_side).rev().map(|&e| 10_f64.powi(e)).collect();
+
+        let mut z: Vec<f64> = powers.iter().rev().map(|p| -p).filter(|t| *t >= u && *t <= v).collect();
+        if u <= 0.0 && v >= 0.0 { z.push(0.0) };
+        z.extend(powers.iter().filter(|t| **t >= u && **t <= v));
+
+        if z.len() * 2 < count { z = ticks::ticks(u, v, count) };
+        if reverse { z.reverse() };
+        z
+    }
+
+    fn copy(&self) -> Box<dyn Scale<f64, R>> { Box::new(self.clone()) }
+}
+
+impl ContinuousScale<f64> for SymlogScale<f64> {
+    fn invert_one(&self, y: f64) -> f64 {
+        invert_one(y, &self.domain, &self.range, self.clamp, self.round, &self.ease(), &self.inv_ease())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_works() {
+        let scale: SymlogScale = SymlogScaleBuilder::default()
+            .clamp(false)
+            .build().unwrap();
+        assert_eq!(scale, SymlogScale{range: [0.0, 1.0], domain: [0.0, 1.0], clamp: false, round: false, constant: 1.0});
+    }
+
+    #[test]
+    fn call_works() {
+        let mut scale: SymlogScale = SymlogScaleBuilder::default()
+            .domain([-100.0, 100.0])
+            .range([0.0, 100.0])
+            .build().unwrap();
+
+        let res = scale.call(&[-100.0, 0.0, 100.0]);
+        assert_eq!( res, [0.0, 50.0, 100.0] );
+        let res = scale.call(&[-10.0, 10.0]);
+        assert!( (res[0] - (50.0 - 50.0 * 11_f64.ln() / 101_f64.ln())).abs() < 1e-12 );
+        assert!( (res[1] - (50.0 + 50.0 * 11_f64.ln() / 101_f64.ln())).abs() < 1e-12 );
+
+        scale.domain = [0.0, 1e6];
+        scale.constant = 1e3;
+        let res = scale.call(&[1e3, 1e6]);
+        assert!( (res[0] - 100.0 * 2_f64.ln() / 1001_f64.ln()).abs() < 1e-12 );
+        assert_eq!( res[1], 100.0 );
+    }
+
+    #[test]
+    fn invert_works() {
+        let scale: SymlogScale = SymlogScaleBuilder::default()
+            .domain([-100.0, 100.0])
+            .range([0.0, 100.0])
+            .build().unwrap();
+
+        let res = scale.invert(&scale.call(&[-100.0, -10.0, -1.0, 0.0, 1.0, 10.0, 100.0]));
+        for (x, y) in res.iter().zip(&[-100.0, -10.0, -1.0, 0.0, 1.0, 10.0, 100.0]) {
+            assert!( (x - y).abs() < 1e-9 );
+        }
+    }
+
+    #[test]
+    fn clamp_and_round_works() {
+        let scale: SymlogScale = SymlogScaleBuilder::default()
+            .domain([-100.0, 100.0])
+            .range([0.0, 100.0])
+            .clamp(true)
+            .round(true)
+            .build().unwrap();
+
+        assert_eq!( scale.call(&[-1000.0, -10.0, 0.0, 1000.0]), [0.0, 24.0, 50.0, 100.0] );
+        assert_eq!( scale.invert(&[-10.0, 50.0, 110.0]), [-100.0, 0.0, 100.0] );
+    }
+
+    #[test]
+    fn ticks_works() {
+        let mut scale: SymlogScale = SymlogScaleBuilder::default()
+            .domain([-1000.0, 1000.0])
+            .build().unwrap();
+
+        assert_eq!( scale.ticks(10), [-1000.0, -100.0, -10.0, -1.0, 0.0, 1.0, 10.0, 100.0, 1000.0] );
+
+        scale.domain = [-1e6, 1e6];
+        assert_eq!( scale.ticks(9), [-1e6, -1e4, -100.0, -1.0, 0.0, 1.0, 100.0, 1e4, 1e6] );
+        assert_eq!( scale.ticks(4), [-1e6, 0.0, 1e6] );
+        assert_eq!( scale.ticks(1), [0.0] );
+        assert!( scale.ticks(0).is_empty() );
+
+        scale.domain = [10.0, 0.0];
+        assert_eq!( scale.ticks(3), [10.0, 1.0, 0.0] );
+        assert_eq!( scale.ticks(10), [10.0, 9.0, 8.0, 7.0, 6.0, 5.0, 4.0, 3.0, 2.0, 1.0, 0.0] );
+
+        scale.domain = [0.0, 1e300];
+        scale.constant = 1e-300;
+        assert!( scale.ticks(10).len() <= 10 );
+
+        // Invalid, as when deserialized
+        scale.constant = 0.0;
+        assert!( scale.ticks(10).is_empty() );
+        scale.constant = 1.0;
+        scale.domain = [0.0, f64::INFINITY];
+        assert!( scale.ticks(10).is_empty() );
+    }
+
+    #[test]
+    fn builder_validates() {
+        assert!( SymlogScaleBuilder::<f64>::default().constant(0.0).build().is_err() );
+        assert!( SymlogScaleBuilder::<f64>::default().constant(f64::NAN).build().is_err() );
+        assert!( SymlogScaleBuilder::<f64>::default().domain([0.0, f64::INFINITY]).build().is_err() );
+    }
+}
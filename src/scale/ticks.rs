@@ -0,0 +1,124 @@
+//!
+//! Nice tick values for numeric intervals, ported from https://github.com/d3/d3-array
+//!
+
+const E10: f64 = 7.0710678118654755; // sqrt(50)
+const E5: f64 = 3.1622776601683795;  // sqrt(10)
+const E2: f64 = std::f64::consts::SQRT_2;
+
+/// Returns (i1, i2, inc) so that the ticks are i * inc (or i / -inc when inc is negative)
+fn tick_spec(start: f64, stop: f64, count: f64) -> (f64, f64, f64) {
+    let step = (stop - start) / count.max(0.0);
+    let power = step.log10().floor();
+    let error = step / 10_f64.powf(power);
+    let factor = if error >= E10 { 10.0 } else if error >= E5 { 5.0 } else if error >= E2 { 2.0 } else { 1.0 };
+    let (mut i1, mut i2, inc);
+    if power < 0.0 {
+        let pos_inc = 10_f64.powf(-power) / factor;
+        i1 = (start * pos_inc).round();
+        i2 = (stop * pos_inc).round();
+        if i1 / pos_inc < start { i1 += 1.0 };
+        if i2 / pos_inc > stop { i2 -= 1.0 };
+        inc = -pos_inc;
+    } else {
+        inc = 10_f64.powf(power) * factor;
+        i1 = (start / inc).round();
+        i2 = (stop / inc).round();
+        if i1 * inc < start { i1 += 1.0 };
+        if i2 * inc > stop { i2 -= 1.0 };
+    }
+    if i2 < i1 && (0.5..2.0).contains(&count) {
+        return tick_spec(start, stop, count * 2.0);
+    }
+    (i1, i2, inc)
+}
+
+/// Returns approximately `count` uniformly-spaced, nicely-rounded values between start and stop (inclusive)
+pub fn ticks(start: f64, stop: f64, count: usize) -> Vec<f64> {
+    if count == 0 || start.is_nan() || stop.is_nan() { return vec![] };
+    if start == stop { return vec![start] };
+    let reverse = stop < start;
+    let (i1, i2, inc) = if reverse {
+        tick_spec(stop, start, count as f64)
+    } else {
+        tick_spec(start, stop, count as f64)
+    };
+    if !(i1.is_finite() && i2.is_finite() && inc.is_finite()) || i2 < i1 { return vec![] };
+    let n = (i2 - i1) as usize + 1;
+    let tick = |i: f64| if inc < 0.0 { i / -inc } else { i * inc };
+    if reverse {
+        (0..n).map(|i| tick(i2 - i as f64)).collect()
+    } else {
+        (0..n).map(|i| tick(i1 + i as f64)).collect()
+    }
+}
+
+/// Like `tick_step`, but returns the inverse of the step (as a negative number) when the step is lower than 1
+pub fn tick_increment(start: f64, stop: f64, count: usize) -> f64 {
+    tick_spec(start, stop, count as f64).2
+}
+
+/// Returns the difference between adjacent tick values for the same arguments of `ticks`
+pub fn tick_step(start: f64, stop: f64, count: usize) -> f64 {
+    let reverse = stop < start;
+    let inc = if reverse { tick_increment(stop, start, count) } else { tick_increment(start, stop, count) };
+    let step = if inc < 0.0 { 1.0 / -inc } else { inc };
+    if reverse { -step } else { step }
+}
+
+/// Extends the interval so it starts and ends on round values, like the ticks for the same `count`
+pub fn nice(start: f64, stop: f64, count: usize) -> [f64; 2] {
+    let reverse = stop < start;
+    let (mut lo, mut hi) = if reverse { (stop, start) } else { (start, stop) };
+    let mut prestep = f64::NAN;
+    for _ in 0..10 {
+        let step = tick_increment(lo, hi, count);
+        if !step.is_finite() || step == prestep { break };
+        if step > 0.0 {
+            lo = (lo / step).floor() * step;
+            hi = (hi / step).ceil() * step;
+        } else if step < 0.0 {
+            lo = (lo * step).ceil() / step;
+            hi = (hi * step).floor() / step;
+        } else {
+            break;
+        }
+        prestep = step;
+    }
+    if reverse { [hi, lo] } else { [lo, hi] }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticks_works() {
+        assert_eq!(ticks(0.0, 1.0, 10), [0.0, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0]);
+        assert_eq!(ticks(0.0, 1.0, 5), [0.0, 0.2, 0.4, 0.6, 0.8, 1.0]);
+        assert_eq!(ticks(0.0, 10.0, 2), [0.0, 5.0, 10.0]);
+        assert_eq!(ticks(-10.0, 10.0, 4), [-10.0, -5.0, 0.0, 5.0, 10.0]);
+        assert_eq!(ticks(1.0, 0.0, 5), [1.0, 0.8, 0.6, 0.4, 0.2, 0.0]);
+        assert_eq!(ticks(1.0, 1.0, 5), [1.0]);
+        assert_eq!(ticks(0.0, 1.0, 0), Vec::<f64>::new());
+        assert_eq!(ticks(0.0, f64::NAN, 5), Vec::<f64>::new());
+    }
+
+    #[test]
+    fn tick_step_works() {
+        assert_eq!(tick_step(0.0, 1.0, 10), 0.1);
+        assert_eq!(tick_step(0.0, 1000.0, 10), 100.0);
+        assert_eq!(tick_step(0.0, 23.0, 5), 5.0);
+        assert_eq!(tick_step(10.0, 0.0, 5), -2.0);
+        assert_eq!(tick_increment(0.0, 1.0, 10), -10.0);
+    }
+
+    #[test]
+    fn nice_works() {
+        assert_eq!(nice(0.13, 0.91, 10), [0.1, 1.0]);
+        assert_eq!(nice(1.1, 10.9, 10), [1.0, 11.0]);
+        assert_eq!(nice(0.0, 97.0, 5), [0.0, 100.0]);
+        assert_eq!(nice(97.0, -3.0, 5), [100.0, -20.0]);
+        assert_eq!(nice(5.0, 5.0, 5), [5.0, 5.0]);
+    }
+}
@@ -1,5 +1,7 @@
 
-use crate::scale::interpolate;
+use crate::scale::{interpolate, interpolate_one, invert, invert_one, Scale, ContinuousScale};
+use crate::scale::time_ticks::time_ticks;
+use crate::interpolate::Interpolate;
 use chrono::{DateTime as ChDateTime, Utc, TimeZone};
 use crate::utils::Extend;
 
@@ -13,9 +15,32 @@ impl DateTime {
     }
 }
 
+/// Dates are stored as ISO-8601 strings in UTC, like "2020-01-31T12:00:00.000Z"
+#[cfg(feature = "serde")]
+impl serde::Serialize for DateTime {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use chrono::SecondsFormat;
+        let seconds = (self.0 / 1000.0).floor();
+        let nanos = ((self.0 - seconds * 1000.0) * 1e6).round() as u32;
+        let date = Utc.timestamp_opt(seconds as i64, nanos).single()
+            .filter(|_| self.0.is_finite())
+            .ok_or_else(|| serde::ser::Error::custom(format!("{} is not a valid date", self.0)))?;
+        serializer.serialize_str(&date.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DateTime {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        let date = ChDateTime::parse_from_rfc3339(&text).map_err(serde::de::Error::custom)?;
+        Ok(DateTime(date.timestamp() as f64 * 1000.0 + date.timestamp_subsec_nanos() as f64 / 1e6))
+    }
+}
+
 impl Default for DateTime {
     fn default() -> DateTime {
-        Utc.timestamp(0, 0).into()
+        Utc.timestamp_opt(0, 0).unwrap().into()
     }
 }
 
@@ -40,38 +65,98 @@ impl Extend<DateTime> for DateTime {
     }
 }
 
-#[derive(Debug, Builder, Default, PartialEq)]
+impl Interpolate for DateTime {
+    fn interpolate(a: &DateTime, b: &DateTime, t: f64) -> DateTime {
+        DateTime(f64::interpolate(&a.0, &b.0, t))
+    }
+
+    fn round(&self) -> DateTime {
+        DateTime(self.0.round())
+    }
+
+    fn unit_range() -> [DateTime; 2] {
+        [DateTime(0.0), DateTime(1000.0)]
+    }
+
+    fn undefined() -> DateTime { DateTime(f64::NAN) }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Builder, Clone, Default, PartialEq)]
 #[builder(setter(into))]
-pub struct TimeScale {
+pub struct TimeScale<R = f64> where R: Interpolate {
     #[builder(default = "[DateTime(0.0), DateTime(1000.0)]")]
     pub domain:[DateTime; 2],
-    #[builder(default = "[0.0, 1.0]")]
-    pub range: [f64; 2],
+    #[builder(default = "R::unit_range()")]
+    pub range: [R; 2],
     #[builder(default)]
     pub clamp: bool,
     #[builder(default)]
     pub round: bool
 }
 
-impl TimeScale {
+impl<R: Interpolate> TimeScale<R> {
 
-    pub fn call(&self, data: &[DateTime]) -> Vec<f64> {
+    pub fn call(&self, data: &[DateTime]) -> Vec<R> {
         let domain = [self.domain[0].0, self.domain[1].0];
         let data_millis: Vec<f64> = data.iter().map(|x| x.0).collect();
-        interpolate(&data_millis, &domain, &self.range, self.clamp, self.round, |x| {x}, |x| {x}, |x| {x})
+        interpolate(&data_millis, &domain, &self.range, self.clamp, self.round, |x| {x})
     }
+}
+
+impl TimeScale<f64> {
 
     pub fn invert(&self, data: &[f64]) -> Vec<DateTime> {
         let domain = [self.domain[0].0, self.domain[1].0];
-        let millis = interpolate(data, &self.range, &domain, self.clamp, self.round, |x| {x}, |x| {x}, |x| {x});
+        let millis = invert(data, &domain, &self.range, self.clamp, self.round, |x| {x}, |x| {x});
         millis.iter().map(|x| DateTime(*x)).collect()
     }
 }
 
+impl<R: Interpolate + 'static> Scale<DateTime, R> for TimeScale<R> {
+    fn call_one(&self, x: &DateTime) -> R {
+        let domain = [self.domain[0].0, self.domain[1].0];
+        interpolate_one(x.0, &domain, &self.range, self.clamp, self.round, &|x| {x})
+    }
+
+    fn domain(&self) -> Vec<DateTime> { self.domain.to_vec() }
+
+    fn range(&self) -> Vec<R> { self.range.to_vec() }
+
+    fn ticks(&self, count: usize) -> Vec<DateTime> {
+        time_ticks(&self.domain[0], &self.domain[1], count)
+    }
+
+    fn copy(&self) -> Box<dyn Scale<DateTime, R>> { Box::new(self.clone()) }
+}
+
+impl ContinuousScale<DateTime> for TimeScale<f64> {
+    fn invert_one(&self, y: f64) -> DateTime {
+        let domain = [self.domain[0].0, self.domain[1].0];
+        DateTime(invert_one(y, &domain, &self.range, self.clamp, self.round, &|x| {x}, &|x| {x}))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_works() {
+        let dates = vec![DateTime(0.0), DateTime(1_580_472_000_123.0), DateTime(-1.5)];
+        let json = serde_json::to_string(&dates).unwrap();
+        assert_eq!(json, r#"["1970-01-01T00:00:00Z","2020-01-31T12:00:00.123Z","1969-12-31T23:59:59.998500Z"]"#);
+        assert_eq!(serde_json::from_str::<Vec<DateTime>>(&json).unwrap(), dates);
+        assert!(serde_json::to_string(&DateTime(f64::NAN)).is_err());
+
+        let scale: TimeScale = TimeScaleBuilder::default()
+            .domain([DateTime(0.0), DateTime(1_580_472_000_000.0)])
+            .build().unwrap();
+        let json = serde_json::to_string(&scale).unwrap();
+        assert_eq!(serde_json::from_str::<TimeScale>(&json).unwrap(), scale);
+    }
+
     #[test]
     fn builder_works() {
         let scale: TimeScale = TimeScaleBuilder::default()
@@ -147,4 +232,32 @@ mod tests {
             [DateTime(0.0), DateTime(10.0), DateTime(100.0), DateTime(150.0)]
         );
     }
+
+    #[test]
+    fn scale_trait_works() {
+        let scale: TimeScale = TimeScaleBuilder::default()
+            .domain([DateTime(0.0), DateTime(10.0)])
+            .range([0.0, 100.0])
+            .build().unwrap();
+
+        let boxed: Box<dyn ContinuousScale<DateTime>> = Box::new(scale);
+        assert_eq!( boxed.call_one(&DateTime(5.0)), 50.0 );
+        assert_eq!( boxed.invert_one(50.0), DateTime(5.0) );
+        assert_eq!( boxed.ticks(2), [DateTime(0.0), DateTime(5.0), DateTime(10.0)] );
+    }
+
+    #[test]
+    fn ticks_out_of_range_are_empty() {
+        let scale: TimeScale = TimeScaleBuilder::default()
+            .domain([DateTime(0.0), DateTime(f64::INFINITY)])
+            .range([0.0, 100.0])
+            .build().unwrap();
+        assert!(scale.ticks(5).is_empty());
+
+        let scale: TimeScale = TimeScaleBuilder::default()
+            .domain([DateTime(1e17), DateTime(1.7e18)])
+            .range([0.0, 100.0])
+            .build().unwrap();
+        assert!(scale.ticks(5).is_empty());
+    }
 }
@@ -0,0 +1,167 @@
+//!
+//! Calendar aware ticks for time intervals (UTC), ported from https://github.com/d3/d3-time
+//!
+use std::convert::TryFrom;
+
+use chrono::{Datelike, TimeZone, Utc};
+
+use crate::scale::ticks::tick_step;
+use crate::scale::DateTime;
+
+pub const DURATION_SECOND: f64 = 1000.0;
+pub const DURATION_MINUTE: f64 = DURATION_SECOND * 60.0;
+pub const DURATION_HOUR: f64 = DURATION_MINUTE * 60.0;
+pub const DURATION_DAY: f64 = DURATION_HOUR * 24.0;
+pub const DURATION_WEEK: f64 = DURATION_DAY * 7.0;
+pub const DURATION_MONTH: f64 = DURATION_DAY * 30.0;
+pub const DURATION_YEAR: f64 = DURATION_DAY * 365.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeUnit { Millisecond, Second, Minute, Hour, Day, Week, Month, Year }
+
+const TICK_INTERVALS: [(TimeUnit, i64, f64); 18] = [
+    (TimeUnit::Second, 1, DURATION_SECOND),
+    (TimeUnit::Second, 5, 5.0 * DURATION_SECOND),
+    (TimeUnit::Second, 15, 15.0 * DURATION_SECOND),
+    (TimeUnit::Second, 30, 30.0 * DURATION_SECOND),
+    (TimeUnit::Minute, 1, DURATION_MINUTE),
+    (TimeUnit::Minute, 5, 5.0 * DURATION_MINUTE),
+    (TimeUnit::Minute, 15, 15.0 * DURATION_MINUTE),
+    (TimeUnit::Minute, 30, 30.0 * DURATION_MINUTE),
+    (TimeUnit::Hour, 1, DURATION_HOUR),
+    (TimeUnit::Hour, 3, 3.0 * DURATION_HOUR),
+    (TimeUnit::Hour, 6, 6.0 * DURATION_HOUR),
+    (TimeUnit::Hour, 12, 12.0 * DURATION_HOUR),
+    (TimeUnit::Day, 1, DURATION_DAY),
+    (TimeUnit::Day, 2, 2.0 * DURATION_DAY),
+    (TimeUnit::Week, 1, DURATION_WEEK),
+    (TimeUnit::Month, 1, DURATION_MONTH),
+    (TimeUnit::Month, 3, 3.0 * DURATION_MONTH),
+    (TimeUnit::Year, 1, DURATION_YEAR),
+];
+
+/// Chooses the interval and step whose ticks are closest to `count` for the given span of millis
+pub fn tick_interval(start: f64, stop: f64, count: usize) -> (TimeUnit, i64) {
+    let target = (stop - start).abs() / count as f64;
+    let i = TICK_INTERVALS.iter().position(|&(_, _, duration)| duration > target).unwrap_or(TICK_INTERVALS.len());
+    if i == TICK_INTERVALS.len() {
+        let step = tick_step(start / DURATION_YEAR, stop / DURATION_YEAR, count).abs();
+        (TimeUnit::Year, (step as i64).max(1))
+    } else if i == 0 {
+        let step = tick_step(start, stop, count).abs();
+        (TimeUnit::Millisecond, (step as i64).max(1))
+    } else {
+        let (before, after) = (TICK_INTERVALS[i - 1], TICK_INTERVALS[i]);
+        let (unit, step, _) = if target / before.2 < after.2 / target { before } else { after };
+        (unit, step)
+    }
+}
+
+/// Multiples of a fixed `size` (plus `offset`) between start and stop
+fn fixed(start: f64, stop: f64, size: f64, offset: f64) -> Vec<f64> {
+    let mut t = ((start - offset) / size).ceil() * size + offset;
+    let mut out = vec![];
+    while t <= stop {
+        out.push(t);
+        t += size;
+    }
+    out
+}
+
+/// None out of the range of chrono
+fn date(millis: f64) -> Option<chrono::DateTime<Utc>> {
+    Utc.timestamp_millis_opt(millis as i64).single()
+}
+
+fn first_of_month(months: i64) -> Option<f64> {
+    let year = i32::try_from(months.div_euclid(12)).ok()?;
+    let month = months.rem_euclid(12) as u32 + 1;
+    Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single().map(|d| d.timestamp_millis() as f64)
+}
+
+/// Boundaries of every `step` units between start and stop (inclusive)
+pub fn every(unit: TimeUnit, step: i64, start: f64, stop: f64) -> Vec<f64> {
+    let step_f = step as f64;
+    match unit {
+        TimeUnit::Millisecond => fixed(start, stop, step_f, 0.0),
+        TimeUnit::Second => fixed(start, stop, step_f * DURATION_SECOND, 0.0),
+        TimeUnit::Minute => fixed(start, stop, step_f * DURATION_MINUTE, 0.0),
+        TimeUnit::Hour => fixed(start, stop, step_f * DURATION_HOUR, 0.0),
+        TimeUnit::Day => fixed(start, stop, DURATION_DAY, 0.0).into_iter()
+            .filter(|&t| date(t).is_some_and(|d| (d.day0() as i64) % step == 0))
+            .collect(),
+        // Weeks start on Sunday, 1970-01-04 is the first one after the epoch
+        TimeUnit::Week => fixed(start, stop, step_f * DURATION_WEEK, 3.0 * DURATION_DAY),
+        TimeUnit::Month | TimeUnit::Year => {
+            let months_step = if unit == TimeUnit::Year { step * 12 } else { step };
+            let Some(d) = date(start) else { return vec![] };
+            let mut months = d.year() as i64 * 12 + d.month0() as i64;
+            if first_of_month(months).is_some_and(|t| t < start) { months += 1 };
+            months = (months + months_step - 1).div_euclid(months_step) * months_step;
+            let mut out = vec![];
+            while let Some(t) = first_of_month(months).filter(|&t| t <= stop) {
+                out.push(t);
+                months += months_step;
+            }
+            out
+        }
+    }
+}
+
+/// Returns approximately `count` calendar aligned dates between start and stop (inclusive).
+/// None for dates out of the range of chrono, like infinities or timestamps in nanoseconds
+pub fn time_ticks(start: &DateTime, stop: &DateTime, count: usize) -> Vec<DateTime> {
+    let valid = |x: f64| date(x).is_some() && x.is_finite();
+    if count == 0 || !valid(start.0) || !valid(stop.0) { return vec![] };
+    let reverse = stop.0 < start.0;
+    let (a, b) = if reverse { (stop.0, start.0) } else { (start.0, stop.0) };
+    let (unit, step) = tick_interval(a, b, count);
+    let mut out: Vec<DateTime> = every(unit, step, a, b).into_iter().map(DateTime).collect();
+    if reverse { out.reverse() };
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(y: i32, m: u32, d: u32, h: u32) -> DateTime {
+        Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap().into()
+    }
+
+    #[test]
+    fn time_ticks_works() {
+        assert_eq!(
+            time_ticks(&utc(2011, 1, 1, 12), &utc(2011, 1, 2, 12), 4),
+            [utc(2011, 1, 1, 12), utc(2011, 1, 1, 18), utc(2011, 1, 2, 0), utc(2011, 1, 2, 6), utc(2011, 1, 2, 12)]
+        );
+        assert_eq!(
+            time_ticks(&utc(2011, 1, 1, 0), &utc(2011, 12, 31, 0), 4),
+            [utc(2011, 1, 1, 0), utc(2011, 4, 1, 0), utc(2011, 7, 1, 0), utc(2011, 10, 1, 0)]
+        );
+        assert_eq!(
+            time_ticks(&utc(2000, 6, 1, 0), &utc(2020, 6, 1, 0), 4),
+            [utc(2005, 1, 1, 0), utc(2010, 1, 1, 0), utc(2015, 1, 1, 0), utc(2020, 1, 1, 0)]
+        );
+        assert_eq!(time_ticks(&DateTime(0.0), &DateTime(10.0), 5), [0.0, 2.0, 4.0, 6.0, 8.0, 10.0].iter().map(|&x| DateTime(x)).collect::<Vec<_>>());
+        assert_eq!(time_ticks(&DateTime(0.0), &DateTime(10.0), 0), []);
+    }
+
+    #[test]
+    fn invalid_dates_have_no_ticks() {
+        assert_eq!(time_ticks(&DateTime(0.0), &DateTime(f64::INFINITY), 5), []);
+        assert_eq!(time_ticks(&DateTime(f64::NEG_INFINITY), &DateTime(0.0), 5), []);
+        // Nanoseconds since the epoch, read as millis
+        assert_eq!(time_ticks(&DateTime(1e17), &DateTime(1.7e18), 5), []);
+        assert_eq!(time_ticks(&DateTime(0.0), &DateTime(1e17), 5), []);
+        // The extremes of chrono still work
+        let max = chrono::DateTime::<Utc>::MAX_UTC.timestamp_millis() as f64;
+        assert!(!time_ticks(&DateTime(max - 1e12), &DateTime(max), 5).is_empty());
+    }
+
+    #[test]
+    fn weeks_start_on_sunday() {
+        let ticks = time_ticks(&utc(2011, 1, 1, 0), &utc(2011, 2, 1, 0), 4);
+        assert_eq!(ticks, [utc(2011, 1, 2, 0), utc(2011, 1, 9, 0), utc(2011, 1, 16, 0), utc(2011, 1, 23, 0), utc(2011, 1, 30, 0)]);
+    }
+}
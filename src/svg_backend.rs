@@ -1,30 +1,148 @@
 
+use std::collections::HashMap;
+use std::fmt::Write;
+
 use svg;
 use svg::{Document, Node};
 use svg::node::element;
 
-use crate::core::{Scene, Glyph, Color, Style};
-use crate::mark::{Circle, Rect, Segment, Line, Text, TextAlign};
+use crate::core::{Scene, Glyph, Color, Style, LineCap, LineJoin};
+use crate::paint::{GradientUnits, Paint, Pattern, PatternKind};
+use crate::font::{FontStyle, FontWeight};
+use crate::mark::{Mark, Circle, Rect, Segment, Line, Area, Text, TextAlign, TextBaseline, Channel, CircleBatch, RectBatch, SymbolBatch, SymbolShape};
 
 pub struct SVGContext {
-    pub doc: Document
+    pub doc: Document,
+    /// Gradients and patterns used by the elements, each once. The i-th one has the id "paint{i}"
+    pub paints: Vec<Paint>,
 }
 
-fn set_style<T:Node>(elem: &mut T, style: & Style) {
-    elem.assign("fill", Color::rgba(&style.fill));
-    elem.assign("stroke", Color::rgba(&style.stroke));
+fn set_style<T:Node>(ctx: &mut SVGContext, elem: &mut T, style: & Style) {
+    elem.assign("fill", paint(ctx, &style.fill));
+    elem.assign("stroke", paint(ctx, &style.stroke));
     elem.assign("stroke-width", style.stroke_width);
+    // Only what differs from the SVG defaults
+    if !style.dash_array.is_empty() {
+        let dashes: Vec<String> = style.dash_array.iter().map(|d| d.to_string()).collect();
+        elem.assign("stroke-dasharray", dashes.join(","));
+    }
+    if style.dash_offset != 0.0 { elem.assign("stroke-dashoffset", style.dash_offset) };
+    match style.line_cap {
+        LineCap::Butt => {},
+        LineCap::Round => elem.assign("stroke-linecap", "round"),
+        LineCap::Square => elem.assign("stroke-linecap", "square"),
+    }
+    match style.line_join {
+        LineJoin::Miter => {},
+        LineJoin::Round => elem.assign("stroke-linejoin", "round"),
+        LineJoin::Bevel => elem.assign("stroke-linejoin", "bevel"),
+    }
+    if style.miter_limit != 4.0 { elem.assign("stroke-miterlimit", style.miter_limit) };
+    if style.fill_opacity != 1.0 { elem.assign("fill-opacity", style.fill_opacity) };
+    if style.stroke_opacity != 1.0 { elem.assign("stroke-opacity", style.stroke_opacity) };
+}
+
+/// Value of a fill or stroke attribute. Gradients and patterns are referenced by id, defined the first time they are used
+fn paint(ctx: &mut SVGContext, paint: &Option<Paint>) -> String {
+    match paint {
+        None => "none".into(),
+        Some(Paint::Solid(color)) => color.to_string(),
+        Some(paint) => {
+            let i = ctx.paints.iter().position(|p| p == paint).unwrap_or_else(|| {
+                ctx.paints.push(paint.clone());
+                ctx.paints.len() - 1
+            });
+            format!("url(#paint{})", i)
+        },
+    }
+}
+
+/// What both kinds of gradients share, their units and stops
+fn finish_gradient<T: Node>(mut gradient: T, stops: &[(f64, Color)], units: GradientUnits) -> T {
+    if units == GradientUnits::UserSpace { gradient.assign("gradientUnits", "userSpaceOnUse") };
+    for (offset, color) in stops {
+        gradient.append(element::Stop::new().set("offset", *offset).set("stop-color", color.to_string()));
+    }
+    gradient
+}
+
+/// The tile of a pattern, rotated around the origin
+fn pattern(pattern: &Pattern, id: String) -> element::Pattern {
+    let s = pattern.spacing;
+    let mut e = element::Pattern::new()
+        .set("id", id)
+        .set("patternUnits", "userSpaceOnUse")
+        .set("width", s)
+        .set("height", s);
+    if pattern.angle != 0.0 { e.assign("patternTransform", format!("rotate({})", pattern.angle)) };
+    if let Some(background) = pattern.background {
+        e.append(element::Rectangle::new().set("width", s).set("height", s).set("fill", background.to_string()));
+    }
+    let line = |x1: f64, y1: f64, x2: f64, y2: f64| element::Line::new()
+        .set("x1", x1).set("y1", y1).set("x2", x2).set("y2", y2)
+        .set("stroke", pattern.color.to_string())
+        .set("stroke-width", pattern.width);
+    match pattern.kind {
+        PatternKind::Hatch => e.append(line(0.0, s / 2.0, s, s / 2.0)),
+        PatternKind::CrossHatch => {
+            e.append(line(0.0, s / 2.0, s, s / 2.0));
+            e.append(line(s / 2.0, 0.0, s / 2.0, s));
+        },
+        PatternKind::Dots => e.append(element::Circle::new()
+            .set("cx", s / 2.0)
+            .set("cy", s / 2.0)
+            .set("r", pattern.width / 2.0)
+            .set("fill", pattern.color.to_string())),
+    }
+    e
+}
+
+/// `<defs>` with the gradients and patterns of `paints`
+fn definitions(paints: &[Paint]) -> element::Definitions {
+    let mut defs = element::Definitions::new();
+    for (i, paint) in paints.iter().enumerate() {
+        let id = format!("paint{}", i);
+        match paint {
+            Paint::Solid(_) => {},
+            Paint::LinearGradient(g) => {
+                let e = element::LinearGradient::new()
+                    .set("id", id)
+                    .set("x1", g.x1)
+                    .set("y1", g.y1)
+                    .set("x2", g.x2)
+                    .set("y2", g.y2);
+                defs.append(finish_gradient(e, &g.stops, g.units));
+            },
+            Paint::RadialGradient(g) => {
+                let e = element::RadialGradient::new()
+                    .set("id", id)
+                    .set("cx", g.cx)
+                    .set("cy", g.cy)
+                    .set("r", g.r);
+                defs.append(finish_gradient(e, &g.stops, g.units));
+            },
+            Paint::Pattern(p) => defs.append(pattern(p, id)),
+        }
+    }
+    defs
+}
+
+/// Text nodes are written as they are, so the markup characters are escaped first
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
 }
 
 impl Glyph for Circle {
     type Context =  SVGContext;
 
+    fn mark(&self) -> Option<Mark> { Some(Mark::Circle(self.clone())) }
+
     fn draw(& self, ctx: &mut Self::Context) {
         let mut e = element::Circle::new()
             .set("cx", self.x)
             .set("cy", self.y)
             .set("r", self.radius);
-        set_style(&mut e, &self.style);
+        set_style(ctx, &mut e, &self.style);
         ctx.doc.append(e);
     }
 }
@@ -33,13 +151,15 @@ impl Glyph for Circle {
 impl Glyph for Rect {
     type Context =  SVGContext;
 
+    fn mark(&self) -> Option<Mark> { Some(Mark::Rect(self.clone())) }
+
     fn draw(& self, ctx: &mut Self::Context) {
         let mut e = element::Rectangle::new()
             .set("x", self.x)
             .set("y", self.y)
             .set("width", self.width)
             .set("height", self.height);
-        set_style(&mut e, &self.style);
+        set_style(ctx, &mut e, &self.style);
         ctx.doc.append(e);
     }
 }
@@ -47,13 +167,15 @@ impl Glyph for Rect {
 impl Glyph for Segment {
     type Context =  SVGContext;
 
+    fn mark(&self) -> Option<Mark> { Some(Mark::Segment(self.clone())) }
+
     fn draw(& self, ctx: &mut Self::Context) {
         let mut e = element::Line::new()
             .set("x1", self.x)
             .set("y1", self.y)
             .set("x2", self.x2)
             .set("y2", self.y2);
-        set_style(&mut e, &self.style);
+        set_style(ctx, &mut e, &self.style);
         ctx.doc.append(e);
     }
 }
@@ -61,14 +183,38 @@ impl Glyph for Segment {
 impl Glyph for Line {
     type Context =  SVGContext;
 
+    fn mark(&self) -> Option<Mark> { Some(Mark::Line(self.clone())) }
+
     fn draw(& self, ctx: &mut Self::Context) {
-        let points: Vec<String> = izip!(&self.x, &self.y)
+        let points: Vec<String> = self.points().iter()
             .map(|(x, y)| { format!("{},{}", x, y) })
             .collect();
         
         let mut e = element::Polyline::new()
             .set("points", points.join(","));
-        set_style(&mut e, &self.style);
+        set_style(ctx, &mut e, &self.style);
+        ctx.doc.append(e);
+    }
+}
+
+impl Glyph for Area {
+    type Context =  SVGContext;
+
+    fn mark(&self) -> Option<Mark> { Some(Mark::Area(self.clone())) }
+
+    fn draw(& self, ctx: &mut Self::Context) {
+        let mut d = String::new();
+        for (i, (x, y)) in izip!(&self.x1, &self.y1).enumerate() {
+            write!(d, "{}{},{}", if i == 0 { "M" } else { "L" }, x, y).unwrap();
+        }
+        for (x, y) in izip!(&self.x0, &self.y0).rev() {
+            write!(d, "L{},{}", x, y).unwrap();
+        }
+        d.push('Z');
+
+        let mut e = element::Path::new()
+            .set("d", d);
+        set_style(ctx, &mut e, &self.style);
         ctx.doc.append(e);
     }
 }
@@ -76,7 +222,10 @@ impl Glyph for Line {
 impl Glyph for Text {
     type Context =  SVGContext;
 
+    fn mark(&self) -> Option<Mark> { Some(Mark::Text(self.clone())) }
+
     fn draw(& self, ctx: &mut Self::Context) {
+        let font = &self.font;
         let mut e = element::Text::new()
             .set("x", self.x)
             .set("y", self.y)
@@ -85,22 +234,374 @@ impl Glyph for Text {
                 TextAlign::Center => "middle",
                 TextAlign::End => "end",
             })
-            .add(svg::node::Text::new(self.text.clone()));
-        set_style(&mut e, &self.style);
+            .set("font-family", font.family.clone())
+            .set("font-size", font.size);
+        if font.weight != FontWeight::Normal { e.assign("font-weight", font.weight.value()) };
+        match font.style {
+            FontStyle::Normal => {},
+            FontStyle::Italic => e.assign("font-style", "italic"),
+            FontStyle::Oblique => e.assign("font-style", "oblique"),
+        }
+        match self.baseline {
+            TextBaseline::Alphabetic => {},
+            TextBaseline::Top => e.assign("dominant-baseline", "text-before-edge"),
+            TextBaseline::Middle => e.assign("dominant-baseline", "central"),
+            TextBaseline::Bottom => e.assign("dominant-baseline", "text-after-edge"),
+            TextBaseline::Hanging => e.assign("dominant-baseline", "hanging"),
+        }
+        if self.angle != 0.0 { e.assign("transform", format!("rotate({},{},{})", self.angle, self.x, self.y)) };
+        let lines = self.lines();
+        if let [line] = lines.as_slice() {
+            if self.dx != 0.0 { e.assign("dx", self.dx) };
+            if self.dy != 0.0 { e.assign("dy", self.dy) };
+            e.append(svg::node::Text::new(escape(line)));
+        } else {
+            // One tspan per line, the first one shifted so that the baseline aligns the whole block.
+            // They are written as a single node since whitespace between them would be rendered
+            let spacing = self.line_spacing();
+            let before = (lines.len() - 1) as f64 * spacing * match self.baseline {
+                TextBaseline::Middle => 0.5,
+                TextBaseline::Bottom => 1.0,
+                _ => 0.0,
+            };
+            let mut spans = String::new();
+            for (i, line) in lines.iter().enumerate() {
+                write!(spans, r#"<tspan x="{}" dy="{}""#, self.x, if i == 0 { self.dy - before } else { spacing }).unwrap();
+                if self.dx != 0.0 { write!(spans, r#" dx="{}""#, self.dx).unwrap() };
+                write!(spans, ">{}</tspan>", escape(line)).unwrap();
+            }
+            e.append(svg::node::Text::new(spans));
+        }
+        set_style(ctx, &mut e, &self.style);
         ctx.doc.append(e);
     }
 }
 
-pub fn save (path: &str, scene: & Scene<SVGContext>) {
+/// Appends one path per (fill, stroke) group, with the outline of every row of the group.
+/// Groups are painted in the order their first row appears.
+/// Rows with a tooltip get a path of their own holding it as a `<title>`
+fn draw_batch(
+    ctx: &mut SVGContext,
+    len: usize,
+    fill: &Channel<Option<Paint>>,
+    stroke: &Channel<Option<Paint>>,
+    stroke_width: f64,
+    tooltip: &Option<Vec<String>>,
+    outline: impl Fn(&mut String, usize)
+) {
+    if let Some(tooltip) = tooltip {
+        for (row, text) in tooltip.iter().enumerate().take(len) {
+            let mut d = String::new();
+            outline(&mut d, row);
+            let style = Style { fill: fill.get(row).clone(), stroke: stroke.get(row).clone(), stroke_width, ..Style::default() };
+            let mut e = element::Path::new()
+                .set("d", d)
+                .add(element::Title::new().add(svg::node::Text::new(escape(text))));
+            set_style(ctx, &mut e, &style);
+            ctx.doc.append(e);
+        }
+        return;
+    }
+
+    // Rows are grouped by the values of their fill and stroke attributes
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+    let mut groups: Vec<(&Option<Paint>, &Option<Paint>, String)> = vec![];
+    for row in 0..len {
+        let (row_fill, row_stroke) = (fill.get(row), stroke.get(row));
+        let key = (paint(ctx, row_fill), paint(ctx, row_stroke));
+        let i = *index.entry(key).or_insert_with(|| {
+            groups.push((row_fill, row_stroke, String::new()));
+            groups.len() - 1
+        });
+        outline(&mut groups[i].2, row);
+    }
+
+    for (fill, stroke, d) in groups {
+        let style = Style { fill: fill.clone(), stroke: stroke.clone(), stroke_width, ..Style::default() };
+        let mut e = element::Path::new()
+            .set("d", d);
+        set_style(ctx, &mut e, &style);
+        ctx.doc.append(e);
+    }
+}
+
+fn circle_outline(d: &mut String, x: f64, y: f64, r: f64) {
+    write!(d, "M{},{}a{},{} 0 1,0 {},0a{},{} 0 1,0 {},0", x - r, y, r, r, 2.0 * r, r, r, -2.0 * r).unwrap();
+}
+
+impl Glyph for CircleBatch {
+    type Context =  SVGContext;
+
+    fn mark(&self) -> Option<Mark> { Some(Mark::CircleBatch(self.clone())) }
+
+    fn draw(& self, ctx: &mut Self::Context) {
+        draw_batch(ctx, self.rows(), &self.fill, &self.stroke, self.stroke_width, &self.tooltip, |d, row| {
+            circle_outline(d, self.x[row], self.y[row], *self.radius.get(row));
+        });
+    }
+}
+
+impl Glyph for RectBatch {
+    type Context =  SVGContext;
+
+    fn mark(&self) -> Option<Mark> { Some(Mark::RectBatch(self.clone())) }
+
+    fn draw(& self, ctx: &mut Self::Context) {
+        draw_batch(ctx, self.rows(), &self.fill, &self.stroke, self.stroke_width, &self.tooltip, |d, row| {
+            let (w, h) = (*self.width.get(row), *self.height.get(row));
+            write!(d, "M{},{}h{}v{}h{}Z", self.x[row], self.y[row], w, h, -w).unwrap();
+        });
+    }
+}
+
+impl Glyph for SymbolBatch {
+    type Context =  SVGContext;
+
+    fn mark(&self) -> Option<Mark> { Some(Mark::SymbolBatch(self.clone())) }
+
+    fn draw(& self, ctx: &mut Self::Context) {
+        let unit = self.shape.vertices(1.0);
+        draw_batch(ctx, self.rows(), &self.fill, &self.stroke, self.stroke_width, &self.tooltip, |d, row| {
+            let (x, y, size) = (self.x[row], self.y[row], *self.size.get(row));
+            if self.shape == SymbolShape::Circle {
+                circle_outline(d, x, y, (size / std::f64::consts::PI).sqrt());
+                return;
+            }
+            // Every vertex grows with the square root of the area
+            let k = size.sqrt();
+            for (i, (vx, vy)) in unit.iter().enumerate() {
+                write!(d, "{}{},{}", if i == 0 { "M" } else { "L" }, x + vx * k, y + vy * k).unwrap();
+            }
+            d.push('Z');
+        });
+    }
+}
+
+impl Glyph for Mark {
+    type Context =  SVGContext;
+
+    fn mark(&self) -> Option<Mark> { Some(self.clone()) }
+
+    fn draw(& self, ctx: &mut Self::Context) {
+        match self {
+            Mark::Circle(mark) => mark.draw(ctx),
+            Mark::Rect(mark) => mark.draw(ctx),
+            Mark::Segment(mark) => mark.draw(ctx),
+            Mark::Line(mark) => mark.draw(ctx),
+            Mark::Area(mark) => mark.draw(ctx),
+            Mark::Text(mark) => mark.draw(ctx),
+            Mark::CircleBatch(mark) => mark.draw(ctx),
+            Mark::RectBatch(mark) => mark.draw(ctx),
+            Mark::SymbolBatch(mark) => mark.draw(ctx),
+        }
+    }
+}
+
+/// The SVG document of the scene
+pub fn document(scene: & Scene<SVGContext>) -> Document {
     let mut context = SVGContext{
         doc: Document::new()
                 .set("viewBox", (0, 0, scene.canvas.width, scene.canvas.height))
-                .set("style", format!("background: {};", scene.canvas.background.to_string()))
+                .set("style", format!("background: {};", scene.canvas.background)),
+        paints: vec![],
     };
 
     for glyph in & scene.glyphs {
         glyph.draw(&mut context);
     }
+    // After the elements, since they are only known once drawn
+    if !context.paints.is_empty() {
+        context.doc.append(definitions(&context.paints));
+    }
+    context.doc
+}
+
+pub fn save (path: &str, scene: & Scene<SVGContext>) {
+    svg::save(path, &document(scene)).unwrap();
+}
+
+/// Writes the SVG document to `target`, like stdout
+pub fn write<W: std::io::Write>(target: W, scene: & Scene<SVGContext>) -> std::io::Result<()> {
+    svg::write(target, &document(scene))
+}
+
+/// Longest side of the PNGs, in pixels
+#[cfg(feature = "png")]
+pub const MAX_PNG_SIDE: u32 = 16384;
 
-    svg::save(path, &context.doc).unwrap();
-}
\ No newline at end of file
+/// Encodes the scene as a PNG of `scale` pixels per unit of the canvas, drawing the text with the system fonts.
+/// Fails when a side would be longer than `MAX_PNG_SIDE`
+#[cfg(feature = "png")]
+pub fn to_png(scene: & Scene<SVGContext>, scale: f32) -> Result<Vec<u8>, String> {
+    use resvg::{tiny_skia, usvg};
+    use resvg::usvg::fontdb;
+
+    let mut options = usvg::Options::default();
+    let fonts = options.fontdb_mut();
+    fonts.load_system_fonts();
+    // Generic families fall back to an installed font when Arial, Times New Roman or Courier New are missing
+    let families: Vec<String> = fonts.faces().flat_map(|face| face.families.iter().map(|(name, _)| name.clone())).collect();
+    let find = |matches: &dyn Fn(&str) -> bool| families.iter().find(|name| matches(name)).or_else(|| families.first()).cloned();
+    let installed = |fonts: &fontdb::Database, family: fontdb::Family| fonts.query(&fontdb::Query { families: &[family], ..Default::default() }).is_some();
+    if !installed(fonts, fontdb::Family::SansSerif) {
+        if let Some(family) = find(&|name| name.contains("Sans") && !name.contains("Mono")) { fonts.set_sans_serif_family(family) };
+    }
+    if !installed(fonts, fontdb::Family::Serif) {
+        if let Some(family) = find(&|name| name.contains("Serif") && !name.contains("Sans")) { fonts.set_serif_family(family) };
+    }
+    if !installed(fonts, fontdb::Family::Monospace) {
+        if let Some(family) = find(&|name| name.contains("Mono")) { fonts.set_monospace_family(family) };
+    }
+    let tree = usvg::Tree::from_str(&document(scene).to_string(), &options).map_err(|e| e.to_string())?;
+
+    let size = tree.size().to_int_size().scale_by(scale).ok_or("the canvas is empty")?;
+    if size.width() > MAX_PNG_SIDE || size.height() > MAX_PNG_SIDE {
+        return Err(format!("the PNG would be {}x{} pixels, more than {} on a side", size.width(), size.height(), MAX_PNG_SIDE));
+    }
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| format!("there is not enough memory for a PNG of {}x{} pixels", size.width(), size.height()))?;
+    // The background is a CSS property, which is not part of the drawing
+    let background = &scene.canvas.background;
+    let alpha = (background.a * 255.0).round() as u8;
+    pixmap.fill(tiny_skia::Color::from_rgba8(background.r, background.g, background.b, alpha));
+    resvg::render(&tree, tiny_skia::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::color::{BLUE, RED, WHITE};
+    use crate::core::Canvas;
+    use crate::font::Font;
+    use crate::mark::CircleBatchBuilder;
+    use crate::paint::{LinearGradient, RadialGradient, stops};
+
+    fn context() -> SVGContext {
+        SVGContext { doc: Document::new(), paints: vec![] }
+    }
+
+    #[test]
+    fn style_works() {
+        let mut e = element::Path::new();
+        set_style(&mut context(), &mut e, &Style::default());
+        let plain = e.to_string();
+        assert!(!plain.contains("dash") && !plain.contains("line") && !plain.contains("opacity"), "{}", plain);
+
+        let style = Style {
+            dash_array: vec![4.0, 2.5],
+            dash_offset: 1.0,
+            line_cap: LineCap::Round,
+            line_join: LineJoin::Bevel,
+            miter_limit: 2.0,
+            fill_opacity: 0.5,
+            stroke_opacity: 0.25,
+            ..Style::default()
+        };
+        let mut e = element::Path::new();
+        set_style(&mut context(), &mut e, &style);
+        let svg = e.to_string();
+        for attribute in &[r#"stroke-dasharray="4,2.5""#, r#"stroke-dashoffset="1""#, r#"stroke-linecap="round""#, r#"stroke-linejoin="bevel""#,
+                           r#"stroke-miterlimit="2""#, r#"fill-opacity="0.5""#, r#"stroke-opacity="0.25""#] {
+            assert!(svg.contains(attribute), "{} not in {}", attribute, svg);
+        }
+    }
+
+    #[test]
+    fn paint_works() {
+        let gradient: Paint = LinearGradient::vertical(stops(&[RED, BLUE])).into();
+        let hatch: Paint = Pattern { kind: PatternKind::CrossHatch, background: Some(WHITE), ..Pattern::default() }.into();
+        let mut scene = Scene::new(Canvas::default());
+        for (fill, stroke) in [(gradient.clone(), None), (hatch, Some(gradient)), (RED.into(), None)] {
+            let style = Rc::new(Style { fill: Some(fill), stroke, ..Style::default() });
+            scene.add(Box::new(Rect { x: 0.0, y: 0.0, width: 10.0, height: 10.0, style }));
+        }
+        let svg = document(&scene).to_string();
+        // Each paint is defined once
+        assert_eq!(svg.matches("<linearGradient").count(), 1, "{}", svg);
+        assert_eq!(svg.matches("<pattern").count(), 1, "{}", svg);
+        assert_eq!(svg.matches("<stop").count(), 2, "{}", svg);
+        assert_eq!(svg.matches("url(#paint0)").count(), 2, "{}", svg);
+        assert!(svg.contains(r#"fill="url(#paint1)""#), "{}", svg);
+        assert!(svg.contains(r#"patternTransform="rotate(45)""#), "{}", svg);
+        assert_eq!(svg.matches("<line ").count(), 2, "{}", svg);
+        assert!(svg.contains(r#"fill="rgba(255,0,0,1)""#), "{}", svg);
+
+        let mut scene = Scene::new(Canvas::default());
+        scene.add(Box::new(Rect { x: 0.0, y: 0.0, width: 10.0, height: 10.0, style: Rc::new(Style::default()) }));
+        assert!(!document(&scene).to_string().contains("<defs"));
+    }
+
+    #[test]
+    fn gradient_units_work() {
+        // A horizontal segment has no height, only a gradient in pixels paints it
+        let mut scene = Scene::new(Canvas::default());
+        for stroke in [LinearGradient::horizontal(stops(&[RED, BLUE])), LinearGradient::between(0.0, 5.0, 100.0, 5.0, stops(&[RED, BLUE]))] {
+            let style = Rc::new(Style { stroke: Some(stroke.into()), ..Style::default() });
+            scene.add(Box::new(Segment { x: 0.0, y: 5.0, x2: 100.0, y2: 5.0, style }));
+        }
+        let svg = document(&scene).to_string();
+        assert_eq!(svg.matches(r#"gradientUnits="userSpaceOnUse""#).count(), 1, "{}", svg);
+    }
+
+    #[test]
+    fn batch_paint_works() {
+        let gradient: Paint = RadialGradient::centered(stops(&[WHITE, RED])).into();
+        let mut scene = Scene::new(Canvas::default());
+        scene.add(Box::new(CircleBatchBuilder::default()
+            .x(vec![1.0, 2.0, 3.0])
+            .y(vec![1.0, 2.0, 3.0])
+            .radius(1.0)
+            .fill(vec![Some(gradient.clone()), Some(RED.into()), Some(gradient)])
+            .build().unwrap()));
+        let svg = document(&scene).to_string();
+        // The rows with the gradient share a path
+        assert_eq!(svg.matches("<path").count(), 2, "{}", svg);
+        assert_eq!(svg.matches("<radialGradient").count(), 1, "{}", svg);
+        assert!(svg.contains(r#"fill="url(#paint0)""#), "{}", svg);
+    }
+
+    #[test]
+    fn text_works() {
+        let mut scene = Scene::new(Canvas::default());
+        scene.add(Box::new(Text {
+            x: 10.0,
+            y: 20.0,
+            text: "a < b".into(),
+            baseline: TextBaseline::Middle,
+            font: Font { family: "serif".into(), size: 12.0, weight: FontWeight::Bold, style: FontStyle::Italic },
+            angle: -90.0,
+            dy: 2.0,
+            ..Text::default()
+        }));
+        let svg = document(&scene).to_string();
+        for attribute in &[r#"dominant-baseline="central""#, r#"font-family="serif""#, r#"font-size="12""#, r#"font-weight="700""#,
+                           r#"font-style="italic""#, r#"transform="rotate(-90,10,20)""#, r#"dy="2""#, "a &lt; b"] {
+            assert!(svg.contains(attribute), "{} not in {}", attribute, svg);
+        }
+        assert!(!svg.contains("dx="));
+
+        let mut scene = Scene::new(Canvas::default());
+        scene.add(Box::new(Text {
+            x: 10.0,
+            y: 20.0,
+            text: "first line\nsecond".into(),
+            baseline: TextBaseline::Bottom,
+            max_width: Some(30.0),
+            ..Text::default()
+        }));
+        let svg = document(&scene).to_string();
+        assert_eq!(svg.matches("<tspan").count(), 3, "{}", svg);
+        assert!(svg.contains(r#"<tspan x="10" dy="-24">"#), "{}", svg);
+        assert!(svg.contains(r#"<tspan x="10" dy="12">"#), "{}", svg);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn png_works() {
+        let scene = Scene::new(Canvas { width: 20, height: 10, background: WHITE });
+        assert!(to_png(&scene, 2.0).unwrap().starts_with(b"\x89PNG"));
+        assert_eq!(to_png(&scene, 1000.0).unwrap_err(), "the PNG would be 20000x10000 pixels, more than 16384 on a side");
+    }
+}
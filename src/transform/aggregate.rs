@@ -0,0 +1,270 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::data::{Column, DataError, DataType, Table};
+
+/// Reductions of the values of a column in each group
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AggregateOp {
+    /// Number of rows, nulls included
+    Count,
+    Sum,
+    Mean,
+    Median,
+    Min,
+    Max,
+    /// Quantile for p in [0, 1], interpolated like R-7 and d3
+    Quantile(f64),
+    /// Sample standard deviation
+    Stdev,
+    /// Number of distinct non-null values
+    Distinct,
+    First,
+    Last,
+}
+
+/// An output column of `GroupBy::aggregate`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aggregate {
+    pub op: AggregateOp,
+    pub column: String,
+    /// Name of the output column
+    pub name: String,
+}
+
+impl Aggregate {
+    /// The output column is named after the op and the column, like `mean_price`
+    pub fn new(op: AggregateOp, column: &str) -> Aggregate {
+        let prefix = match op {
+            AggregateOp::Count => "count".to_string(),
+            AggregateOp::Sum => "sum".to_string(),
+            AggregateOp::Mean => "mean".to_string(),
+            AggregateOp::Median => "median".to_string(),
+            AggregateOp::Min => "min".to_string(),
+            AggregateOp::Max => "max".to_string(),
+            AggregateOp::Quantile(p) => format!("q{}", p),
+            AggregateOp::Stdev => "stdev".to_string(),
+            AggregateOp::Distinct => "distinct".to_string(),
+            AggregateOp::First => "first".to_string(),
+            AggregateOp::Last => "last".to_string(),
+        };
+        let name = if column.is_empty() { prefix } else { format!("{}_{}", prefix, column) };
+        Aggregate { op, column: column.to_string(), name }
+    }
+
+    /// Number of rows in each group, in a column named `count`
+    pub fn count() -> Aggregate { Aggregate::new(AggregateOp::Count, "") }
+    pub fn sum(column: &str) -> Aggregate { Aggregate::new(AggregateOp::Sum, column) }
+    pub fn mean(column: &str) -> Aggregate { Aggregate::new(AggregateOp::Mean, column) }
+    pub fn median(column: &str) -> Aggregate { Aggregate::new(AggregateOp::Median, column) }
+    pub fn min(column: &str) -> Aggregate { Aggregate::new(AggregateOp::Min, column) }
+    pub fn max(column: &str) -> Aggregate { Aggregate::new(AggregateOp::Max, column) }
+    pub fn quantile(column: &str, p: f64) -> Aggregate { Aggregate::new(AggregateOp::Quantile(p), column) }
+    pub fn stdev(column: &str) -> Aggregate { Aggregate::new(AggregateOp::Stdev, column) }
+    pub fn distinct(column: &str) -> Aggregate { Aggregate::new(AggregateOp::Distinct, column) }
+    pub fn first(column: &str) -> Aggregate { Aggregate::new(AggregateOp::First, column) }
+    pub fn last(column: &str) -> Aggregate { Aggregate::new(AggregateOp::Last, column) }
+
+    /// Renames the output column
+    pub fn alias(mut self, name: &str) -> Aggregate {
+        self.name = name.to_string();
+        self
+    }
+}
+
+/// Quantile of sorted values, None when empty
+pub fn quantile_sorted(values: &[f64], p: f64) -> Option<f64> {
+    if values.is_empty() || p.is_nan() { return None };
+    let h = (values.len() - 1) as f64 * p.clamp(0.0, 1.0);
+    let i = h.floor() as usize;
+    let lower = values[i];
+    match values.get(i + 1) {
+        Some(upper) => Some(lower + (upper - lower) * (h - i as f64)),
+        None => Some(lower),
+    }
+}
+
+/// Numeric reduction of the non-null values. Counting ops are handled by the caller
+fn reduce(op: AggregateOp, values: &mut [f64]) -> Option<f64> {
+    let n = values.len() as f64;
+    match op {
+        AggregateOp::Sum => Some(values.iter().sum()),
+        AggregateOp::Mean if values.is_empty() => None,
+        AggregateOp::Mean => Some(values.iter().sum::<f64>() / n),
+        AggregateOp::Min => values.iter().cloned().fold(None, |m, x| Some(m.map_or(x, |m: f64| m.min(x)))),
+        AggregateOp::Max => values.iter().cloned().fold(None, |m, x| Some(m.map_or(x, |m: f64| m.max(x)))),
+        AggregateOp::Median | AggregateOp::Quantile(_) => {
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let p = if let AggregateOp::Quantile(p) = op { p } else { 0.5 };
+            quantile_sorted(values, p)
+        },
+        AggregateOp::Stdev if values.len() < 2 => None,
+        AggregateOp::Stdev => {
+            let mean = values.iter().sum::<f64>() / n;
+            let squares: f64 = values.iter().map(|x| (x - mean).powi(2)).sum();
+            Some((squares / (n - 1.0)).sqrt())
+        },
+        AggregateOp::Count | AggregateOp::Distinct | AggregateOp::First | AggregateOp::Last => None,
+    }
+}
+
+/// Rows of a table split by the values of some key columns. Groups are in first-seen order
+#[derive(Debug, Clone)]
+pub struct GroupBy<'a> {
+    table: &'a Table,
+    keys: Vec<String>,
+    groups: Vec<Vec<usize>>,
+}
+
+impl<'a> GroupBy<'a> {
+    pub fn new(table: &'a Table, keys: &[&str]) -> Result<GroupBy<'a>, DataError> {
+        let columns = keys.iter().map(|k| table.column(k)).collect::<Result<Vec<&Column>, DataError>>()?;
+        let values: Vec<Vec<String>> = columns.iter().map(|c| c.to_strings()).collect();
+
+        let mut index: HashMap<Vec<Option<&str>>, usize> = HashMap::new();
+        let mut groups: Vec<Vec<usize>> = vec![];
+        for row in 0..table.len() {
+            let key: Vec<Option<&str>> = columns.iter().zip(&values)
+                .map(|(c, v)| if c.is_null(row) { None } else { Some(v[row].as_str()) })
+                .collect();
+            let len = index.len();
+            let group = *index.entry(key).or_insert(len);
+            if group == groups.len() { groups.push(vec![]) };
+            groups[group].push(row);
+        }
+
+        Ok(GroupBy { table, keys: keys.iter().map(|k| k.to_string()).collect(), groups })
+    }
+
+    /// Rows of each group, in table order
+    pub fn groups(&self) -> &[Vec<usize>] {
+        &self.groups
+    }
+
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// A table for each group
+    pub fn tables(&self) -> Vec<Table> {
+        self.groups.iter().map(|rows| self.table.take(rows)).collect()
+    }
+
+    /// New table with a row per group: the key columns followed by the aggregates
+    pub fn aggregate(&self, aggregates: &[Aggregate]) -> Result<Table, DataError> {
+        let firsts: Vec<usize> = self.groups.iter().map(|g| g[0]).collect();
+        let lasts: Vec<usize> = self.groups.iter().map(|g| g[g.len() - 1]).collect();
+
+        let mut out = Table::new();
+        for key in &self.keys {
+            out.add_column(key, self.table.column(key)?.take(&firsts))?;
+        }
+        for aggregate in aggregates {
+            let column: Column = match aggregate.op {
+                AggregateOp::Count => self.groups.iter().map(|g| g.len() as i64).collect::<Vec<i64>>().into(),
+                AggregateOp::First => self.table.column(&aggregate.column)?.take(&firsts),
+                AggregateOp::Last => self.table.column(&aggregate.column)?.take(&lasts),
+                AggregateOp::Distinct => {
+                    let source = self.table.column(&aggregate.column)?;
+                    let values = source.to_strings();
+                    self.groups.iter()
+                        .map(|g| g.iter().filter(|&&r| !source.is_null(r)).map(|&r| &values[r]).collect::<HashSet<_>>().len() as i64)
+                        .collect::<Vec<i64>>()
+                        .into()
+                },
+                op => {
+                    let source = self.table.column(&aggregate.column)?;
+                    if let found @ (DataType::Str | DataType::Categorical) = source.data_type() {
+                        return Err(DataError::TypeMismatch { column: aggregate.column.clone(), expected: DataType::Float, found });
+                    }
+                    let values = source.to_f64();
+                    self.groups.iter()
+                        .map(|g| {
+                            let mut group: Vec<f64> = g.iter().map(|&r| values[r]).filter(|x| !x.is_nan()).collect();
+                            reduce(op, &mut group)
+                        })
+                        .collect::<Vec<Option<f64>>>()
+                        .into()
+                },
+            };
+            out.add_column(&aggregate.name, column)?;
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Value;
+
+    fn stocks() -> Table {
+        Table::new()
+            .with_column("symbol", Column::categorical(&["MSFT", "IBM", "MSFT", "IBM", "MSFT"])).unwrap()
+            .with_column("year", vec![2000_i64, 2000, 2001, 2001, 2002]).unwrap()
+            .with_column("price", vec![Some(10.0), Some(4.0), Some(20.0), None, Some(60.0)]).unwrap()
+    }
+
+    #[test]
+    fn group_by_works() {
+        let table = stocks();
+        let groups = table.group_by(&["symbol"]).unwrap();
+        assert_eq!(groups.groups(), [vec![0, 2, 4], vec![1, 3]]);
+        assert_eq!(groups.tables()[1].column("year").unwrap().as_i64().unwrap(), [2000, 2001]);
+        assert_eq!(table.group_by(&["symbol", "year"]).unwrap().len(), 5);
+        assert!(table.group_by(&["name"]).is_err());
+    }
+
+    #[test]
+    fn aggregate_works() {
+        let table = stocks().group_by(&["symbol"]).unwrap()
+            .aggregate(&[
+                Aggregate::count(),
+                Aggregate::sum("price"),
+                Aggregate::mean("price"),
+                Aggregate::median("price").alias("typical"),
+                Aggregate::min("price"),
+                Aggregate::max("price"),
+                Aggregate::quantile("price", 0.25),
+                Aggregate::stdev("price"),
+                Aggregate::distinct("price"),
+                Aggregate::first("year"),
+                Aggregate::last("year"),
+            ]).unwrap();
+        assert_eq!(table.names(), ["symbol", "count", "sum_price", "mean_price", "typical", "min_price", "max_price",
+                                   "q0.25_price", "stdev_price", "distinct_price", "first_year", "last_year"]);
+        assert_eq!(table.row(0).get("symbol"), Value::Str("MSFT".into()));
+        assert_eq!(table.column("count").unwrap().as_i64().unwrap(), [3, 2]);
+        assert_eq!(table.column("sum_price").unwrap().as_f64().unwrap(), [90.0, 4.0]);
+        assert_eq!(table.column("mean_price").unwrap().as_f64().unwrap(), [30.0, 4.0]);
+        assert_eq!(table.column("typical").unwrap().as_f64().unwrap(), [20.0, 4.0]);
+        assert_eq!(table.column("min_price").unwrap().as_f64().unwrap(), [10.0, 4.0]);
+        assert_eq!(table.column("max_price").unwrap().as_f64().unwrap(), [60.0, 4.0]);
+        assert_eq!(table.column("q0.25_price").unwrap().as_f64().unwrap(), [15.0, 4.0]);
+        assert_eq!(table.row(0).f64("stdev_price"), Some(26.457513110645905));
+        assert_eq!(table.row(1).get("stdev_price"), Value::Null);
+        assert_eq!(table.column("distinct_price").unwrap().as_i64().unwrap(), [3, 1]);
+        assert_eq!(table.column("first_year").unwrap().as_i64().unwrap(), [2000, 2000]);
+        assert_eq!(table.column("last_year").unwrap().as_i64().unwrap(), [2002, 2001]);
+    }
+
+    #[test]
+    fn aggregate_checks_types() {
+        let groups = stocks();
+        let groups = groups.group_by(&["year"]).unwrap();
+        assert_eq!(groups.aggregate(&[Aggregate::mean("symbol")]).unwrap_err(),
+                   DataError::TypeMismatch { column: "symbol".into(), expected: DataType::Float, found: DataType::Categorical });
+        // Only the numeric reductions need numbers
+        assert!(groups.aggregate(&[Aggregate::first("symbol"), Aggregate::distinct("symbol")]).is_ok());
+    }
+
+    #[test]
+    fn quantile_works() {
+        assert_eq!(quantile_sorted(&[1.0, 2.0, 3.0, 4.0], 0.5), Some(2.5));
+        assert_eq!(quantile_sorted(&[1.0, 2.0, 3.0, 4.0], 1.0), Some(4.0));
+        assert_eq!(quantile_sorted(&[], 0.5), None);
+    }
+}
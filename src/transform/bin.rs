@@ -0,0 +1,241 @@
+use crate::scale::ticks::tick_increment;
+use crate::utils::Extend;
+
+/// Most bins made by counts and steps, larger counts or smaller steps are capped to it
+pub const MAX_BINS: usize = 10_000;
+
+/// How the interval of the data is split into bins
+#[derive(Debug, Clone, PartialEq)]
+pub enum Thresholds {
+    /// Nice thresholds, like the ticks of a scale, with at most this number of bins (and `MAX_BINS`)
+    Count(usize),
+    /// Bins of the same width, aligned to multiples of the step. Nice bins when there would be more than `MAX_BINS`
+    Step(f64),
+    /// Explicit thresholds, values out of the domain are ignored
+    Values(Vec<f64>),
+}
+
+#[derive(Debug, Builder, Clone, PartialEq)]
+#[builder(setter(into))]
+pub struct BinParams {
+    #[builder(default = "Thresholds::Count(10)")]
+    pub thresholds: Thresholds,
+    /// Interval to bin, the extent of the data otherwise. Values out of it are ignored
+    #[builder(default)]
+    pub domain: Option<[f64; 2]>,
+}
+
+impl Default for BinParams {
+    fn default() -> BinParams {
+        BinParams { thresholds: Thresholds::Count(10), domain: None }
+    }
+}
+
+/// Values in [start, end), the last bin also includes its end
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bin {
+    pub start: f64,
+    pub end: f64,
+    pub count: usize,
+}
+
+/// A cell of a 2-D grid of bins
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bin2d {
+    pub x_start: f64,
+    pub x_end: f64,
+    pub y_start: f64,
+    pub y_end: f64,
+    pub count: usize,
+}
+
+/// Edges of `step` width covering [lo, hi], built from the increment of the ticks to avoid rounding errors.
+/// None when there would be more than `MAX_BINS`
+fn nice_edges(lo: f64, hi: f64, inc: f64) -> Option<Vec<f64>> {
+    let edge = |i: f64| if inc < 0.0 { i / -inc } else { i * inc };
+    let (i0, mut i1) = if inc < 0.0 {
+        ((lo * -inc).floor(), (hi * -inc).ceil())
+    } else {
+        ((lo / inc).floor(), (hi / inc).ceil())
+    };
+    if i1 == i0 { i1 += 1.0 };
+    if (i1 - i0).is_nan() || i1 - i0 > MAX_BINS as f64 { return None };
+    Some((0..=(i1 - i0) as usize).map(|i| edge(i0 + i as f64)).collect())
+}
+
+/// Nice edges with at most `max_bins` bins
+fn count_edges(lo: f64, hi: f64, max_bins: usize) -> Vec<f64> {
+    if lo == hi { return vec![lo, hi] };
+    let max_bins = max_bins.clamp(1, MAX_BINS);
+    (1..=max_bins).rev()
+        .filter_map(|count| nice_edges(lo, hi, tick_increment(lo, hi, count)))
+        .find(|edges| edges.len() - 1 <= max_bins)
+        .unwrap_or_else(|| vec![lo, hi])
+}
+
+/// None when there would be more than `MAX_BINS`
+fn step_edges(lo: f64, hi: f64, step: f64) -> Option<Vec<f64>> {
+    let i0 = (lo / step).floor();
+    let n = ((hi / step).ceil() - i0).max(1.0);
+    if n.is_nan() || n > MAX_BINS as f64 { return None };
+    Some((0..=n as usize).map(|i| (i0 + i as f64) * step).collect())
+}
+
+/// Keeps the edges inside (lo, hi) and closes them with lo and hi
+fn clip_edges(edges: &[f64], lo: f64, hi: f64) -> Vec<f64> {
+    let mut clipped = vec![lo];
+    clipped.extend(edges.iter().filter(|&&e| lo < e && e < hi));
+    clipped.push(hi);
+    clipped
+}
+
+/// Edges of the bins for the data: n + 1 sorted values for n bins. Empty when there is nothing to bin
+pub fn edges(data: &[f64], params: &BinParams) -> Vec<f64> {
+    let values: Vec<f64> = data.iter().cloned().filter(|x| !x.is_nan()).collect();
+    let [lo, hi] = match params.domain {
+        Some(domain) => domain,
+        None if values.is_empty() => return vec![],
+        None => f64::extend(&values),
+    };
+    if !(lo.is_finite() && hi.is_finite()) || hi < lo { return vec![] };
+
+    let edges = match &params.thresholds {
+        Thresholds::Count(count) => count_edges(lo, hi, *count),
+        Thresholds::Step(step) if *step > 0.0 && step.is_finite() => {
+            step_edges(lo, hi, *step).unwrap_or_else(|| count_edges(lo, hi, MAX_BINS))
+        },
+        Thresholds::Step(_) => vec![lo, hi],
+        Thresholds::Values(values) => {
+            let mut values = values.clone();
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            clip_edges(&values, lo, hi)
+        },
+    };
+    if params.domain.is_some() { clip_edges(&edges, lo, hi) } else { edges }
+}
+
+/// Index of the bin containing x, if any
+pub fn locate(edges: &[f64], x: f64) -> Option<usize> {
+    let n = edges.len().checked_sub(1)?;
+    if n == 0 || !(x >= edges[0] && x <= edges[n]) { return None };
+    Some((edges.partition_point(|&e| e <= x) - 1).min(n - 1))
+}
+
+/// Groups the data in contiguous bins and counts the values in each one. NaNs are ignored
+pub fn bin(data: &[f64], params: &BinParams) -> Vec<Bin> {
+    let edges = edges(data, params);
+    let mut counts = vec![0; edges.len().saturating_sub(1)];
+    for &x in data {
+        if let Some(i) = locate(&edges, x) { counts[i] += 1 };
+    }
+    counts.into_iter().enumerate()
+        .map(|(i, count)| Bin { start: edges[i], end: edges[i + 1], count })
+        .collect()
+}
+
+/// Bins the points (x, y) in a grid, for density plots. Cells are returned row by row,
+/// each row has all the x bins for a y bin
+pub fn bin2d(x: &[f64], y: &[f64], x_params: &BinParams, y_params: &BinParams) -> Vec<Bin2d> {
+    let x_edges = edges(x, x_params);
+    let y_edges = edges(y, y_params);
+    let columns = x_edges.len().saturating_sub(1);
+    let rows = y_edges.len().saturating_sub(1);
+    let mut counts = vec![0; columns * rows];
+    for (&xv, &yv) in x.iter().zip(y) {
+        if let (Some(i), Some(j)) = (locate(&x_edges, xv), locate(&y_edges, yv)) {
+            counts[j * columns + i] += 1;
+        }
+    }
+    counts.into_iter().enumerate()
+        .map(|(k, count)| {
+            let (i, j) = (k % columns, k / columns);
+            Bin2d { x_start: x_edges[i], x_end: x_edges[i + 1], y_start: y_edges[j], y_end: y_edges[j + 1], count }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(bins: &[Bin]) -> Vec<usize> {
+        bins.iter().map(|b| b.count).collect()
+    }
+
+    #[test]
+    fn count_works() {
+        let data = [0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 3.1, 4.0, 9.9];
+        let bins = bin(&data, &BinParams::default());
+        assert_eq!(bins.len(), 10);
+        assert_eq!(bins[0], Bin { start: 0.0, end: 1.0, count: 2 });
+        assert_eq!(bins[9], Bin { start: 9.0, end: 10.0, count: 1 });
+
+        let params = BinParamsBuilder::default().thresholds(Thresholds::Count(3)).build().unwrap();
+        let bins = bin(&data, &params);
+        assert!(bins.len() <= 3);
+        assert_eq!(bins.iter().map(|b| b.start).collect::<Vec<_>>(), [0.0, 5.0]);
+        assert_eq!(counts(&bins), [9, 1]);
+
+        let bins = bin(&[0.1, 0.2, 0.3], &BinParams::default());
+        assert_eq!(bins.first().unwrap().start, 0.1);
+        assert_eq!(bins.last().unwrap().end, 0.3);
+        assert_eq!(bins.iter().map(|b| b.count).sum::<usize>(), 3);
+
+        assert_eq!(bin(&[], &BinParams::default()), []);
+        assert_eq!(bin(&[2.0, 2.0], &BinParams::default()), [Bin { start: 2.0, end: 2.0, count: 2 }]);
+    }
+
+    #[test]
+    fn step_and_values_work() {
+        let data = [1.0, 2.0, 3.0, 4.0, 5.0, f64::NAN];
+        let params = BinParamsBuilder::default().thresholds(Thresholds::Step(2.0)).build().unwrap();
+        let bins = bin(&data, &params);
+        assert_eq!(bins.iter().map(|b| (b.start, b.end)).collect::<Vec<_>>(), [(0.0, 2.0), (2.0, 4.0), (4.0, 6.0)]);
+        assert_eq!(counts(&bins), [1, 2, 2]);
+
+        let params = BinParamsBuilder::default()
+            .thresholds(Thresholds::Values(vec![4.0, 2.5, 10.0]))
+            .build().unwrap();
+        let bins = bin(&data, &params);
+        assert_eq!(bins.iter().map(|b| (b.start, b.end)).collect::<Vec<_>>(), [(1.0, 2.5), (2.5, 4.0), (4.0, 5.0)]);
+        assert_eq!(counts(&bins), [2, 1, 2]);
+
+        let params = BinParamsBuilder::default()
+            .thresholds(Thresholds::Step(1.0))
+            .domain(Some([1.5, 3.5]))
+            .build().unwrap();
+        let bins = bin(&data, &params);
+        assert_eq!(bins.iter().map(|b| (b.start, b.end)).collect::<Vec<_>>(), [(1.5, 2.0), (2.0, 3.0), (3.0, 3.5)]);
+        assert_eq!(counts(&bins), [0, 1, 1]);
+    }
+
+    #[test]
+    fn bins_are_capped() {
+        let data = [0.0, 1.0];
+        let params = BinParamsBuilder::default().thresholds(Thresholds::Step(1e-12)).build().unwrap();
+        let bins = bin(&data, &params);
+        assert!(bins.len() <= MAX_BINS);
+        assert_eq!(bins.iter().map(|b| b.count).sum::<usize>(), 2);
+
+        let params = BinParamsBuilder::default().thresholds(Thresholds::Count(usize::MAX)).build().unwrap();
+        assert!(bin(&data, &params).len() <= MAX_BINS);
+        let params = BinParamsBuilder::default().thresholds(Thresholds::Count(0)).build().unwrap();
+        assert_eq!(bin(&data, &params).len(), 1);
+
+        let params = BinParamsBuilder::default().thresholds(Thresholds::Step(f64::INFINITY)).build().unwrap();
+        assert_eq!(counts(&bin(&data, &params)), [2]);
+        let bins = bin(&[-1e308, 1e308], &BinParams::default());
+        assert!(!bins.is_empty() && bins.len() <= 10);
+    }
+
+    #[test]
+    fn bin2d_works() {
+        let x = [0.0, 0.5, 1.5, 2.0];
+        let y = [0.0, 0.0, 1.0, 2.0];
+        let params = BinParamsBuilder::default().thresholds(Thresholds::Step(1.0)).build().unwrap();
+        let cells = bin2d(&x, &y, &params, &params);
+        assert_eq!(cells.len(), 4);
+        assert_eq!(cells[0], Bin2d { x_start: 0.0, x_end: 1.0, y_start: 0.0, y_end: 1.0, count: 2 });
+        assert_eq!(cells.iter().map(|c| c.count).collect::<Vec<_>>(), [2, 0, 0, 2]);
+    }
+}
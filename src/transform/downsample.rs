@@ -0,0 +1,176 @@
+//!
+//! Reduction of the points of long series that keeps their visual shape.
+//! Every function returns the indices of the points to keep, in order
+//!
+
+/// Downsampling of a line whose points are already in pixels, so the number of points
+/// depends on the width of the line instead of the number of rows
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Downsample {
+    /// LTTB with two points per pixel of width
+    Lttb,
+    /// M4 with a column per pixel of width
+    MinMax,
+    /// RDP with the given tolerance in pixels
+    Rdp(f64),
+}
+
+impl Downsample {
+    /// Indices of the points to draw on `width` pixels, like the length of the range of the x scale.
+    /// Every point when the width isn't positive and finite
+    pub fn apply(&self, x: &[f64], y: &[f64], width: f64) -> Vec<usize> {
+        let n = x.len().min(y.len());
+        if !(width.is_finite() && width > 0.0) { return (0..n).collect() };
+        // Never more buckets than points
+        let width = width.ceil().min(n as f64) as usize;
+        match self {
+            Downsample::Lttb => lttb(x, y, width * 2),
+            Downsample::MinMax => min_max(x, y, width),
+            Downsample::Rdp(epsilon) => rdp(x, y, *epsilon),
+        }
+    }
+}
+
+/// Keeps `threshold` points with Largest-Triangle-Three-Buckets, see
+/// "Downsampling Time Series for Visual Representation" by Steinarsson
+pub fn lttb(x: &[f64], y: &[f64], threshold: usize) -> Vec<usize> {
+    let n = x.len().min(y.len());
+    if threshold >= n || threshold < 3 { return (0..n).collect() };
+
+    let every = (n - 2) as f64 / (threshold - 2) as f64;
+    let mut sampled = Vec::with_capacity(threshold);
+    let mut a = 0;
+    sampled.push(a);
+    for i in 0..threshold - 2 {
+        // Average of the next bucket
+        let start = ((i + 1) as f64 * every) as usize + 1;
+        let end = (((i + 2) as f64 * every) as usize + 1).min(n);
+        let len = (end - start) as f64;
+        let avg_x = x[start..end].iter().sum::<f64>() / len;
+        let avg_y = y[start..end].iter().sum::<f64>() / len;
+
+        // Point of the current bucket with the largest triangle
+        let from = (i as f64 * every) as usize + 1;
+        let to = ((i + 1) as f64 * every) as usize + 1;
+        let (ax, ay) = (x[a], y[a]);
+        let mut max_area = -1.0;
+        for j in from..to {
+            let area = ((ax - avg_x) * (y[j] - ay) - (ax - x[j]) * (avg_y - ay)).abs();
+            if area > max_area {
+                max_area = area;
+                a = j;
+            }
+        }
+        sampled.push(a);
+    }
+    sampled.push(n - 1);
+    sampled
+}
+
+/// Keeps the first, last, minimum and maximum points of each of `columns` columns of equal width
+/// along x (M4 aggregation), drawn as a line it is identical to the original at one pixel per column
+pub fn min_max(x: &[f64], y: &[f64], columns: usize) -> Vec<usize> {
+    let n = x.len().min(y.len());
+    if columns == 0 || n <= columns * 4 { return (0..n).collect() };
+
+    let (x0, x1) = x[..n].iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+    let width = x1 - x0;
+    // first, last, min, max of each column
+    let mut buckets: Vec<Option<[usize; 4]>> = vec![None; columns];
+    for i in 0..n {
+        if x[i].is_nan() || y[i].is_nan() { continue };
+        let column = if width > 0.0 { (((x[i] - x0) / width * columns as f64) as usize).min(columns - 1) } else { 0 };
+        match &mut buckets[column] {
+            None => buckets[column] = Some([i, i, i, i]),
+            Some(b) => {
+                b[1] = i;
+                if y[i] < y[b[2]] { b[2] = i };
+                if y[i] > y[b[3]] { b[3] = i };
+            }
+        }
+    }
+    let mut sampled: Vec<usize> = buckets.into_iter().flatten().flatten().collect();
+    sampled.sort_unstable();
+    sampled.dedup();
+    sampled
+}
+
+/// Distance from p to the segment a-b
+fn segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let t = if length > 0.0 { (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length).clamp(0.0, 1.0) } else { 0.0 };
+    ((p.0 - a.0 - t * dx).powi(2) + (p.1 - a.1 - t * dy).powi(2)).sqrt()
+}
+
+/// Ramer–Douglas–Peucker simplification, the removed points are closer than `epsilon` to the line
+pub fn rdp(x: &[f64], y: &[f64], epsilon: f64) -> Vec<usize> {
+    let n = x.len().min(y.len());
+    if n < 3 { return (0..n).collect() };
+
+    let mut keep = vec![false; n];
+    keep[0] = true;
+    keep[n - 1] = true;
+    let mut stack = vec![(0, n - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let (a, b) = ((x[first], y[first]), (x[last], y[last]));
+        let mut max_distance = 0.0;
+        let mut index = first;
+        for i in first + 1..last {
+            let distance = segment_distance((x[i], y[i]), a, b);
+            if distance > max_distance {
+                max_distance = distance;
+                index = i;
+            }
+        }
+        if max_distance > epsilon {
+            keep[index] = true;
+            stack.push((first, index));
+            stack.push((index, last));
+        }
+    }
+    (0..n).filter(|&i| keep[i]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lttb_works() {
+        let x: Vec<f64> = (0..10).map(|i| i as f64).collect();
+        let y = [0.0, 1.0, 0.0, 0.0, 5.0, 0.0, 0.0, -3.0, 0.0, 0.0];
+        assert_eq!(lttb(&x, &y, 5), [0, 2, 4, 7, 9]);
+        assert_eq!(lttb(&x, &y, 20).len(), 10);
+        assert_eq!(lttb(&x, &y, 2).len(), 10);
+    }
+
+    #[test]
+    fn min_max_works() {
+        let x: Vec<f64> = (0..12).map(|i| i as f64).collect();
+        let y = [0.0, 3.0, -1.0, 2.0, 1.0, 1.0, 9.0, 1.0, 0.0, 0.0, 5.0, 0.0];
+        // Columns [0, 5.5) and [5.5, 11]
+        assert_eq!(min_max(&x, &y, 2), [0, 1, 2, 5, 6, 8, 11]);
+        assert_eq!(min_max(&x, &y, 3).len(), 12);
+    }
+
+    #[test]
+    fn apply_works() {
+        let x: Vec<f64> = (0..100).map(|i| i as f64 * 1e300).collect();
+        let y: Vec<f64> = x.iter().map(|_| 0.0).collect();
+        assert_eq!(Downsample::Lttb.apply(&x, &y, 10.0).len(), 20);
+        assert_eq!(Downsample::Lttb.apply(&x, &y, f64::INFINITY).len(), 100);
+        assert_eq!(Downsample::MinMax.apply(&x, &y, f64::NAN).len(), 100);
+        assert_eq!(Downsample::Lttb.apply(&x, &y, 1e20).len(), 100);
+    }
+
+    #[test]
+    fn rdp_works() {
+        let x = [0.0, 1.0, 2.0, 3.0, 4.0];
+        let y = [0.0, 0.1, 0.0, 2.0, 0.0];
+        assert_eq!(rdp(&x, &y, 0.5), [0, 2, 3, 4]);
+        assert_eq!(rdp(&x, &y, 5.0), [0, 4]);
+        assert_eq!(rdp(&x, &y, 0.0), [0, 1, 2, 3, 4]);
+    }
+}
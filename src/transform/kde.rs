@@ -0,0 +1,137 @@
+use crate::transform::aggregate::quantile_sorted;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kernel { Gaussian, Epanechnikov }
+
+impl Kernel {
+    pub fn eval(&self, u: f64) -> f64 {
+        match self {
+            Kernel::Gaussian => (-0.5 * u * u).exp() / (2.0 * std::f64::consts::PI).sqrt(),
+            Kernel::Epanechnikov if u.abs() <= 1.0 => 0.75 * (1.0 - u * u),
+            Kernel::Epanechnikov => 0.0,
+        }
+    }
+}
+
+/// Width of the kernel, the rules of thumb assume normally distributed data
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bandwidth {
+    /// 1.06 σ n^(-1/5)
+    Scott,
+    /// 0.9 min(σ, IQR / 1.34) n^(-1/5)
+    Silverman,
+    Fixed(f64),
+}
+
+#[derive(Debug, Builder, Clone, PartialEq)]
+#[builder(setter(into))]
+pub struct KdeParams {
+    #[builder(default = "Kernel::Gaussian")]
+    pub kernel: Kernel,
+    #[builder(default = "Bandwidth::Scott")]
+    pub bandwidth: Bandwidth,
+    /// Interval where the density is evaluated. By default the extent of the data
+    /// extended by 3 bandwidths, so the tails reach zero
+    #[builder(default)]
+    pub extent: Option<[f64; 2]>,
+    /// Number of points of the grid
+    #[builder(default = "100")]
+    pub steps: usize,
+}
+
+impl Default for KdeParams {
+    fn default() -> KdeParams {
+        KdeParams { kernel: Kernel::Gaussian, bandwidth: Bandwidth::Scott, extent: None, steps: 100 }
+    }
+}
+
+/// Estimated probability density `y` at the points `x`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Density {
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+}
+
+/// Bandwidth for the data, 1 when the data has no spread
+pub fn bandwidth(data: &[f64], rule: Bandwidth) -> f64 {
+    let mut values: Vec<f64> = data.iter().cloned().filter(|x| !x.is_nan()).collect();
+    let n = values.len() as f64;
+    if let Bandwidth::Fixed(h) = rule { return h };
+    if values.len() < 2 { return 1.0 };
+
+    let mean = values.iter().sum::<f64>() / n;
+    let sigma = (values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+    let h = match rule {
+        Bandwidth::Silverman => {
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let iqr = quantile_sorted(&values, 0.75).unwrap() - quantile_sorted(&values, 0.25).unwrap();
+            let spread = if iqr > 0.0 { sigma.min(iqr / 1.34) } else { sigma };
+            0.9 * spread * n.powf(-0.2)
+        },
+        _ => 1.06 * sigma * n.powf(-0.2),
+    };
+    if h > 0.0 { h } else { 1.0 }
+}
+
+/// Kernel density estimate of the data evaluated on a uniform grid. NaNs are ignored
+pub fn kde(data: &[f64], params: &KdeParams) -> Density {
+    let values: Vec<f64> = data.iter().cloned().filter(|x| !x.is_nan()).collect();
+    if values.is_empty() || params.steps == 0 { return Density::default() };
+
+    let h = bandwidth(&values, params.bandwidth);
+    let [lo, hi] = params.extent.unwrap_or_else(|| {
+        let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        [min - 3.0 * h, max + 3.0 * h]
+    });
+    let x: Vec<f64> = if params.steps == 1 {
+        vec![(lo + hi) / 2.0]
+    } else {
+        (0..params.steps).map(|i| lo + (hi - lo) * i as f64 / (params.steps - 1) as f64).collect()
+    };
+    let k = 1.0 / (values.len() as f64 * h);
+    let y = x.iter()
+        .map(|xi| k * values.iter().map(|v| params.kernel.eval((xi - v) / h)).sum::<f64>())
+        .collect();
+    Density { x, y }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::InDelta;
+
+    #[test]
+    fn bandwidth_works() {
+        let data = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let sigma = 2.5_f64.sqrt();
+        assert!(bandwidth(&data, Bandwidth::Scott).in_delta(1.06 * sigma * 5_f64.powf(-0.2)));
+        assert!(bandwidth(&data, Bandwidth::Silverman).in_delta(0.9 * (2.0 / 1.34) * 5_f64.powf(-0.2)));
+        assert_eq!(bandwidth(&data, Bandwidth::Fixed(0.3)), 0.3);
+        assert_eq!(bandwidth(&[2.0, 2.0], Bandwidth::Scott), 1.0);
+    }
+
+    #[test]
+    fn kde_works() {
+        let params = KdeParamsBuilder::default()
+            .bandwidth(Bandwidth::Fixed(1.0))
+            .extent(Some([-1.0, 1.0]))
+            .steps(3_usize)
+            .build().unwrap();
+        let density = kde(&[0.0], &params);
+        assert_eq!(density.x, [-1.0, 0.0, 1.0]);
+        assert!(density.y[1].in_delta(0.3989422804014327));
+        assert_eq!(density.y[0], density.y[2]);
+
+        let params = KdeParamsBuilder::default()
+            .kernel(Kernel::Epanechnikov)
+            .bandwidth(Bandwidth::Fixed(2.0))
+            .steps(201_usize)
+            .build().unwrap();
+        let density = kde(&[0.0, 1.0], &params);
+        assert_eq!(density.x[0], -6.0);
+        assert_eq!(density.y[0], 0.0);
+        let area: f64 = density.y.iter().sum::<f64>() * (density.x[1] - density.x[0]);
+        assert!((area - 1.0).abs() < 1e-3);
+    }
+}
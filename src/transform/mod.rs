@@ -0,0 +1,21 @@
+//!
+//! Transformations from data to the values that are drawn
+//!
+
+pub mod bin;
+pub use self::bin::{Bin, Bin2d, BinParams, BinParamsBuilder, MAX_BINS, Thresholds, bin, bin2d};
+
+pub mod stack;
+pub use self::stack::{StackOffset, StackOrder, StackParams, StackParamsBuilder, stack, stack_series};
+
+pub mod aggregate;
+pub use self::aggregate::{Aggregate, AggregateOp, GroupBy};
+
+pub mod kde;
+pub use self::kde::{Bandwidth, Density, KdeParams, KdeParamsBuilder, Kernel, kde};
+
+pub mod regression;
+pub use self::regression::{ConfidenceBand, Fit, RegressionMethod, RegressionParams, RegressionParamsBuilder, regression};
+
+pub mod downsample;
+pub use self::downsample::{Downsample, lttb, min_max, rdp};
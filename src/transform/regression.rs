@@ -0,0 +1,377 @@
+use std::rc::Rc;
+
+use crate::core::Style;
+use crate::mark::{Area, Line};
+use crate::scale::Scale;
+
+/// Model fitted to the points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RegressionMethod {
+    /// y = a + b x
+    Linear,
+    /// y = c0 + c1 x + ... + cn x^n
+    Polynomial(usize),
+    /// y = a e^(b x), for positive y
+    Exponential,
+    /// y = a + b ln(x), for positive x
+    Log,
+    /// y = a x^b, for positive x and y
+    Power,
+    /// Locally weighted regression, the value is the fraction of the points used for each fit
+    Loess(f64),
+}
+
+#[derive(Debug, Builder, Clone, PartialEq)]
+#[builder(setter(into))]
+pub struct RegressionParams {
+    #[builder(default = "RegressionMethod::Linear")]
+    pub method: RegressionMethod,
+    /// Interval of x where the model is evaluated, the extent of the data by default. Ignored by LOESS
+    #[builder(default)]
+    pub extent: Option<[f64; 2]>,
+    /// Number of points where the model is evaluated. Ignored by LOESS, evaluated at the x of the data
+    #[builder(default = "100")]
+    pub steps: usize,
+    /// Confidence level of the band around the fit, like 0.95. Ignored by LOESS
+    #[builder(default)]
+    pub confidence: Option<f64>,
+}
+
+impl Default for RegressionParams {
+    fn default() -> RegressionParams {
+        RegressionParams { method: RegressionMethod::Linear, extent: None, steps: 100, confidence: None }
+    }
+}
+
+/// Confidence band of the mean around a fit
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfidenceBand {
+    pub y0: Vec<f64>,
+    pub y1: Vec<f64>,
+}
+
+/// Fitted points of a model
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fit {
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    /// Coefficients in the order of the formula of the method, empty for LOESS
+    pub coefficients: Vec<f64>,
+    /// Coefficient of determination of the fit on the data
+    pub r_squared: f64,
+    pub band: Option<ConfidenceBand>,
+}
+
+impl Fit {
+    /// Trend line in pixels
+    pub fn line(&self, x_scale: &dyn Scale<f64, f64>, y_scale: &dyn Scale<f64, f64>, style: Rc<Style>) -> Line {
+        Line { x: x_scale.call(&self.x), y: y_scale.call(&self.y), style, downsample: None }
+    }
+
+    /// Confidence band in pixels
+    pub fn area(&self, x_scale: &dyn Scale<f64, f64>, y_scale: &dyn Scale<f64, f64>, style: Rc<Style>) -> Option<Area> {
+        self.band.as_ref()
+            .map(|band| Area::horizontal(x_scale.call(&self.x), y_scale.call(&band.y0), y_scale.call(&band.y1), style))
+    }
+}
+
+/// Inverse of a square matrix by Gauss-Jordan elimination, None when singular or not finite
+fn inverse(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    if !matrix.iter().flatten().all(|v| v.is_finite()) { return None };
+    let n = matrix.len();
+    let mut a: Vec<Vec<f64>> = matrix.iter().enumerate()
+        .map(|(i, row)| row.iter().cloned().chain((0..n).map(|j| if i == j { 1.0 } else { 0.0 })).collect())
+        .collect();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 { return None };
+        a.swap(col, pivot);
+        let p = a[col][col];
+        a[col].iter_mut().for_each(|v| *v /= p);
+        for row in 0..n {
+            if row == col { continue };
+            let factor = a[row][col];
+            if factor == 0.0 { continue };
+            let pivot_row = a[col].clone();
+            a[row].iter_mut().zip(&pivot_row).for_each(|(v, p)| *v -= factor * p);
+        }
+    }
+    Some(a.into_iter().map(|row| row[n..].to_vec()).collect())
+}
+
+/// Quantile of the standard normal distribution, by Acklam's rational approximation
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [-3.969683028665376e1, 2.209460984245205e2, -2.759285104469687e2,
+                         1.38357751867269e2, -3.066479806614716e1, 2.506628277459239];
+    const B: [f64; 5] = [-5.447609879822406e1, 1.615858368580409e2, -1.556989798598866e2,
+                         6.680131188771972e1, -1.328068155288572e1];
+    const C: [f64; 6] = [-7.784894002430293e-3, -3.223964580411365e-1, -2.400758277161838,
+                         -2.549732539343734, 4.374664141464968, 2.938163982698783];
+    const D: [f64; 4] = [7.784695709041462e-3, 3.224671290700398e-1, 2.445134137142996, 3.754408661907416];
+    let tail = |q: f64| (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+        / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0);
+    if p < 0.02425 {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - 0.02425 {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// Quantile of the Student's t distribution, by the Cornish-Fisher expansion of the normal quantile
+fn t_quantile(p: f64, df: f64) -> f64 {
+    let z = normal_quantile(p);
+    let g1 = (z.powi(3) + z) / 4.0;
+    let g2 = (5.0 * z.powi(5) + 16.0 * z.powi(3) + 3.0 * z) / 96.0;
+    let g3 = (3.0 * z.powi(7) + 19.0 * z.powi(5) + 17.0 * z.powi(3) - 15.0 * z) / 384.0;
+    let g4 = (79.0 * z.powi(9) + 776.0 * z.powi(7) + 1482.0 * z.powi(5) - 1920.0 * z.powi(3) - 945.0 * z) / 92160.0;
+    z + g1 / df + g2 / df.powi(2) + g3 / df.powi(3) + g4 / df.powi(4)
+}
+
+fn r_squared(y: &[f64], predicted: &[f64]) -> f64 {
+    let mean = y.iter().sum::<f64>() / y.len() as f64;
+    let total: f64 = y.iter().map(|y| (y - mean).powi(2)).sum();
+    let residual: f64 = y.iter().zip(predicted).map(|(y, p)| (y - p).powi(2)).sum();
+    if total == 0.0 { 1.0 } else { 1.0 - residual / total }
+}
+
+/// Features of x that make the model linear in its parameters
+fn features(method: RegressionMethod, x: f64) -> Vec<f64> {
+    match method {
+        RegressionMethod::Polynomial(degree) => (0..=degree).map(|i| x.powi(i as i32)).collect(),
+        RegressionMethod::Log | RegressionMethod::Power => vec![1.0, x.ln()],
+        _ => vec![1.0, x],
+    }
+}
+
+/// Models fitted to the logarithm of y
+fn log_y(method: RegressionMethod) -> bool {
+    method == RegressionMethod::Exponential || method == RegressionMethod::Power
+}
+
+fn least_squares(x: &[f64], y: &[f64], params: &RegressionParams) -> Option<Fit> {
+    let method = params.method;
+    let features = |x: f64| features(method, x);
+    let transform = |y: f64| if log_y(method) { y.ln() } else { y };
+    let inverse_transform = |y: f64| if log_y(method) { y.exp() } else { y };
+    let points: Vec<(f64, f64)> = x.iter().cloned().zip(y.iter().cloned())
+        .filter(|&(x, y)| x.is_finite() && y.is_finite())
+        .filter(|&(x, y)| match method {
+            RegressionMethod::Exponential => y > 0.0,
+            RegressionMethod::Log => x > 0.0,
+            RegressionMethod::Power => x > 0.0 && y > 0.0,
+            _ => true,
+        })
+        .collect();
+
+    let rows: Vec<Vec<f64>> = points.iter().map(|&(x, _)| features(x)).collect();
+    let target: Vec<f64> = points.iter().map(|&(_, y)| transform(y)).collect();
+    let (n, p) = (rows.len(), rows.first().map(|r| r.len()).unwrap_or(0));
+    if n < p || n == 0 { return None };
+
+    let xtx: Vec<Vec<f64>> = (0..p)
+        .map(|i| (0..p).map(|j| rows.iter().map(|r| r[i] * r[j]).sum()).collect())
+        .collect();
+    let xty: Vec<f64> = (0..p).map(|i| rows.iter().zip(&target).map(|(r, t)| r[i] * t).sum()).collect();
+    let xtx_inv = inverse(&xtx)?;
+    let beta: Vec<f64> = xtx_inv.iter().map(|row| row.iter().zip(&xty).map(|(a, b)| a * b).sum()).collect();
+    let predict = |f: &[f64]| f.iter().zip(&beta).map(|(a, b)| a * b).sum::<f64>();
+
+    let observed: Vec<f64> = points.iter().map(|&(_, y)| y).collect();
+    let predicted: Vec<f64> = rows.iter().map(|r| inverse_transform(predict(r))).collect();
+
+    let [lo, hi] = params.extent.unwrap_or_else(|| {
+        let xs = points.iter().map(|&(x, _)| x);
+        [xs.clone().fold(f64::INFINITY, f64::min), xs.fold(f64::NEG_INFINITY, f64::max)]
+    });
+    let steps = params.steps.max(2);
+    let grid: Vec<f64> = (0..steps).map(|i| lo + (hi - lo) * i as f64 / (steps - 1) as f64).collect();
+    let fitted: Vec<f64> = grid.iter().map(|&x| inverse_transform(predict(&features(x)))).collect();
+
+    // Band of the mean in the linearized space: t * s * sqrt(f' (X'X)^-1 f)
+    let band = params.confidence.filter(|_| n > p).map(|level| {
+        let sse: f64 = rows.iter().zip(&target).map(|(r, t)| (t - predict(r)).powi(2)).sum();
+        let s = (sse / (n - p) as f64).sqrt();
+        let t = t_quantile(0.5 + level / 2.0, (n - p) as f64);
+        let (y0, y1) = grid.iter()
+            .map(|&x| {
+                let f = features(x);
+                let leverage: f64 = (0..p).map(|i| (0..p).map(|j| f[i] * xtx_inv[i][j] * f[j]).sum::<f64>()).sum();
+                let margin = t * s * leverage.max(0.0).sqrt();
+                let y = predict(&f);
+                (inverse_transform(y - margin), inverse_transform(y + margin))
+            })
+            .unzip();
+        ConfidenceBand { y0, y1 }
+    });
+
+    let coefficients = match method {
+        RegressionMethod::Exponential | RegressionMethod::Power => vec![beta[0].exp(), beta[1]],
+        _ => beta.clone(),
+    };
+    Some(Fit { x: grid, y: fitted, coefficients, r_squared: r_squared(&observed, &predicted), band })
+}
+
+/// Weighted linear fit evaluated at x0
+fn weighted_fit(points: &[(f64, f64)], weights: &[f64], x0: f64) -> f64 {
+    let (mut sw, mut sx, mut sy, mut sxx, mut sxy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for (&(x, y), &w) in points.iter().zip(weights) {
+        sw += w;
+        sx += w * x;
+        sy += w * y;
+        sxx += w * x * x;
+        sxy += w * x * y;
+    }
+    if sw == 0.0 { return f64::NAN };
+    let (mx, my) = (sx / sw, sy / sw);
+    let var = sxx / sw - mx * mx;
+    let slope = if var.abs() < 1e-12 { 0.0 } else { (sxy / sw - mx * my) / var };
+    my + slope * (x0 - mx)
+}
+
+/// LOESS with tricube weights and two robustness iterations, evaluated at the x of the data
+fn loess(x: &[f64], y: &[f64], bandwidth: f64) -> Option<Fit> {
+    let mut points: Vec<(f64, f64)> = x.iter().cloned().zip(y.iter().cloned())
+        .filter(|&(x, y)| x.is_finite() && y.is_finite())
+        .collect();
+    points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    let n = points.len();
+    if n < 2 { return None };
+
+    let span = ((bandwidth * n as f64).floor() as usize).clamp(2, n);
+    let mut robustness = vec![1.0; n];
+    let mut fitted = vec![0.0; n];
+    for iteration in 0..3 {
+        let (mut left, mut right) = (0, span - 1);
+        for i in 0..n {
+            let xi = points[i].0;
+            // Slides the window of the `span` nearest neighbours
+            while right + 1 < n && points[right + 1].0 - xi < xi - points[left].0 {
+                left += 1;
+                right += 1;
+            }
+            let max_distance = (xi - points[left].0).max(points[right].0 - xi);
+            let tricube: Vec<f64> = (left..=right)
+                .map(|j| {
+                    let d = if max_distance > 0.0 { (points[j].0 - xi).abs() / max_distance } else { 0.0 };
+                    if d < 1.0 { (1.0 - d.powi(3)).powi(3) } else { 0.0 }
+                })
+                .collect();
+            let mut weights: Vec<f64> = tricube.iter().zip(&robustness[left..=right]).map(|(t, r)| t * r).collect();
+            // Every neighbour is an outlier, they are all kept instead
+            if weights.iter().all(|&w| w == 0.0) { weights = tricube };
+            fitted[i] = weighted_fit(&points[left..=right], &weights, xi);
+        }
+        if iteration == 2 { break };
+
+        // Bisquare weights of the residuals, scaled by 6 times their median
+        let mut residuals: Vec<f64> = points.iter().zip(&fitted).map(|(p, f)| (p.1 - f).abs()).collect();
+        let mut sorted = residuals.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let median = sorted[n / 2];
+        if median < 1e-12 { break };
+        residuals.iter_mut().zip(robustness.iter_mut()).for_each(|(r, w)| {
+            let u = *r / (6.0 * median);
+            *w = if u < 1.0 { (1.0 - u * u).powi(2) } else { 0.0 };
+        });
+    }
+
+    let (xs, ys): (Vec<f64>, Vec<f64>) = points.into_iter().unzip();
+    let r_squared = r_squared(&ys, &fitted);
+    Some(Fit { x: xs, y: fitted, coefficients: vec![], r_squared, band: None })
+}
+
+/// Fits a model to the points (x, y). Points that are not finite or out of the domain of the model are
+/// ignored. None when there are not enough points
+pub fn regression(x: &[f64], y: &[f64], params: &RegressionParams) -> Option<Fit> {
+    match params.method {
+        RegressionMethod::Loess(bandwidth) => loess(x, y, bandwidth),
+        _ => least_squares(x, y, params),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(method: RegressionMethod) -> RegressionParams {
+        RegressionParamsBuilder::default().method(method).steps(3_usize).build().unwrap()
+    }
+
+    fn close(a: &[f64], b: &[f64]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-6)
+    }
+
+    #[test]
+    fn linear_works() {
+        let fit = regression(&[0.0, 1.0, 2.0, 3.0], &[1.0, 3.0, 5.0, 7.0], &params(RegressionMethod::Linear)).unwrap();
+        assert!(close(&fit.coefficients, &[1.0, 2.0]));
+        assert_eq!(fit.x, [0.0, 1.5, 3.0]);
+        assert!(close(&fit.y, &[1.0, 4.0, 7.0]));
+        assert!((fit.r_squared - 1.0).abs() < 1e-12);
+        assert_eq!(fit.band, None);
+
+        let fit = regression(&[0.0, 1.0, 2.0, 3.0], &[1.0, 2.0, 2.0, 4.0], &params(RegressionMethod::Linear)).unwrap();
+        assert!(close(&fit.coefficients, &[0.9, 0.9]));
+        assert!((fit.r_squared - (1.0 - 0.7 / 4.75)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn nonlinear_works() {
+        let x = [1.0, 2.0, 3.0, 4.0];
+        let quadratic: Vec<f64> = x.iter().map(|x| 2.0 - x + 0.5 * x * x).collect();
+        let fit = regression(&x, &quadratic, &params(RegressionMethod::Polynomial(2))).unwrap();
+        assert!(close(&fit.coefficients, &[2.0, -1.0, 0.5]));
+
+        let exponential: Vec<f64> = x.iter().map(|x| 3.0 * (0.5 * x).exp()).collect();
+        let fit = regression(&x, &exponential, &params(RegressionMethod::Exponential)).unwrap();
+        assert!(close(&fit.coefficients, &[3.0, 0.5]));
+
+        let log: Vec<f64> = x.iter().map(|x| 1.0 + 2.0 * x.ln()).collect();
+        let fit = regression(&x, &log, &params(RegressionMethod::Log)).unwrap();
+        assert!(close(&fit.coefficients, &[1.0, 2.0]));
+
+        let power: Vec<f64> = x.iter().map(|x| 2.0 * x.powf(1.5)).collect();
+        let fit = regression(&x, &power, &params(RegressionMethod::Power)).unwrap();
+        assert!(close(&fit.coefficients, &[2.0, 1.5]));
+        assert!(close(&fit.y, &[2.0, 2.0 * 2.5_f64.powf(1.5), 16.0]));
+
+        assert_eq!(regression(&[1.0], &[1.0], &params(RegressionMethod::Polynomial(2))), None);
+        // The squares overflow
+        assert_eq!(regression(&[1e200, 2e200, 3e200], &[1.0, 2.0, 3.0], &params(RegressionMethod::Polynomial(2))), None);
+        assert_eq!(inverse(&[vec![f64::NAN]]), None);
+    }
+
+    #[test]
+    fn confidence_works() {
+        let x = [0.0, 1.0, 2.0, 3.0, 4.0];
+        let y = [0.1, 0.9, 2.2, 2.8, 4.1];
+        let params = RegressionParamsBuilder::default().steps(5_usize).confidence(Some(0.95)).build().unwrap();
+        let fit = regression(&x, &y, &params).unwrap();
+        let band = fit.band.unwrap();
+        assert!(band.y0.iter().zip(&fit.y).zip(&band.y1).all(|((y0, y), y1)| y0 < y && y < y1));
+        // Narrowest at the mean of x
+        let width: Vec<f64> = band.y1.iter().zip(&band.y0).map(|(y1, y0)| y1 - y0).collect();
+        assert!(width[2] < width[0] && width[2] < width[4]);
+        assert!((width[0] - width[4]).abs() < 1e-9);
+        assert!((t_quantile(0.975, 30.0) - 2.0423).abs() < 1e-3);
+        assert!((normal_quantile(0.975) - 1.959964).abs() < 1e-6);
+    }
+
+    #[test]
+    fn loess_works() {
+        let x: Vec<f64> = (0..20).map(|i| i as f64).collect();
+        let mut y: Vec<f64> = x.iter().enumerate().map(|(i, x)| 2.0 * x + 1.0 + if i % 2 == 0 { 0.1 } else { -0.1 }).collect();
+        y[10] = 100.0;
+        let fit = regression(&x, &y, &params(RegressionMethod::Loess(1.0))).unwrap();
+        assert_eq!(fit.x, x);
+        // The outlier is down-weighted by the robustness iterations
+        assert!((fit.y[10] - 21.0).abs() < 0.2);
+        assert!((fit.y[0] - 1.0).abs() < 0.2);
+        assert!(fit.coefficients.is_empty());
+    }
+}
@@ -0,0 +1,260 @@
+//!
+//! Stacking of series, ported from https://github.com/d3/d3-shape
+//!
+
+use std::collections::HashMap;
+
+use crate::data::{DataError, Table};
+
+/// How the baseline of the stack is computed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StackOffset {
+    /// Zero baseline
+    None,
+    /// Normalizes each point so the stack goes from 0 to 1
+    Expand,
+    /// Positive values are stacked above zero and negative values below
+    Diverging,
+    /// Centers the stack around zero
+    Silhouette,
+    /// Minimizes the weighted wiggle of the layers, for streamgraphs
+    Wiggle,
+}
+
+/// Order of the series from the baseline up
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StackOrder {
+    Input,
+    /// The series with the smallest sum at the bottom
+    Ascending,
+    /// The series with the largest sum at the bottom
+    Descending,
+    /// Earliest peaks in the middle and later peaks outside, for streamgraphs
+    InsideOut,
+}
+
+#[derive(Debug, Builder, Clone, PartialEq)]
+#[builder(setter(into))]
+pub struct StackParams {
+    /// Column with the position of each stack, like the band of a bar
+    pub x: String,
+    /// Column with the key of the series
+    pub series: String,
+    /// Column with the values to stack
+    pub value: String,
+    #[builder(default = "StackOrder::Input")]
+    pub order: StackOrder,
+    #[builder(default = "StackOffset::None")]
+    pub offset: StackOffset,
+}
+
+fn sum(values: &[f64]) -> f64 {
+    values.iter().filter(|v| !v.is_nan()).sum()
+}
+
+/// Index of the first maximum
+fn peak(values: &[f64]) -> usize {
+    let mut max = f64::NEG_INFINITY;
+    let mut index = 0;
+    for (i, &v) in values.iter().enumerate() {
+        if v > max { max = v; index = i; }
+    }
+    index
+}
+
+/// Indices of the series from the bottom of the stack up
+pub fn stack_order(values: &[Vec<f64>], order: StackOrder) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..values.len()).collect();
+    let sums: Vec<f64> = values.iter().map(|s| sum(s)).collect();
+    let by_sum = |a: &usize, b: &usize| sums[*a].partial_cmp(&sums[*b]).unwrap_or(std::cmp::Ordering::Equal);
+    match order {
+        StackOrder::Input => {},
+        StackOrder::Ascending => indices.sort_by(by_sum),
+        StackOrder::Descending => { indices.sort_by(by_sum); indices.reverse(); },
+        StackOrder::InsideOut => {
+            let peaks: Vec<usize> = values.iter().map(|s| peak(s)).collect();
+            indices.sort_by_key(|&i| peaks[i]);
+            let (mut top, mut bottom) = (0.0, 0.0);
+            let (mut tops, mut bottoms) = (vec![], vec![]);
+            for i in indices {
+                if top < bottom { top += sums[i]; tops.push(i); } else { bottom += sums[i]; bottoms.push(i); }
+            }
+            bottoms.reverse();
+            bottoms.extend(tops);
+            indices = bottoms;
+        },
+    }
+    indices
+}
+
+/// Baseline of each point for the offsets that stack every series on top of the previous one
+fn baseline(values: &[Vec<f64>], order: &[usize], offset: StackOffset, m: usize) -> Vec<f64> {
+    let value = |i: usize, j: usize| { let v = values[i][j]; if v.is_nan() { 0.0 } else { v } };
+    match offset {
+        StackOffset::Silhouette => (0..m).map(|j| -order.iter().map(|&i| value(i, j)).sum::<f64>() / 2.0).collect(),
+        StackOffset::Wiggle => {
+            let mut base = vec![0.0; m];
+            let mut y = 0.0;
+            for (j, b) in base.iter_mut().enumerate().skip(1) {
+                let (mut s1, mut s2) = (0.0, 0.0);
+                for (k, &i) in order.iter().enumerate() {
+                    let vij = value(i, j);
+                    let mut s3 = (vij - value(i, j - 1)) / 2.0;
+                    for &l in &order[..k] {
+                        s3 += value(l, j) - value(l, j - 1);
+                    }
+                    s1 += vij;
+                    s2 += s3 * vij;
+                }
+                if s1 != 0.0 { y -= s2 / s1 };
+                *b = y;
+            }
+            base
+        },
+        _ => vec![0.0; m],
+    }
+}
+
+/// Stacks `values`, a matrix with a row per series and a column per point. Returns the [y0, y1]
+/// interval of each value, indexed like `values`. Missing values (NaN) count as zero
+pub fn stack_series(values: &[Vec<f64>], order: StackOrder, offset: StackOffset) -> Vec<Vec<[f64; 2]>> {
+    let m = values.iter().map(|s| s.len()).max().unwrap_or(0);
+    let mut values: Vec<Vec<f64>> = values.iter()
+        .map(|s| (0..m).map(|j| s.get(j).cloned().unwrap_or(f64::NAN)).collect())
+        .collect();
+    let order = stack_order(&values, order);
+    let mut out = vec![vec![[0.0, 0.0]; m]; values.len()];
+
+    if offset == StackOffset::Expand {
+        for j in 0..m {
+            let total: f64 = values.iter().map(|s| if s[j].is_nan() { 0.0 } else { s[j] }).sum();
+            if total != 0.0 {
+                values.iter_mut().for_each(|s| s[j] /= total);
+            }
+        }
+    }
+
+    if offset == StackOffset::Diverging {
+        for j in 0..m {
+            let (mut positive, mut negative) = (0.0, 0.0);
+            for &i in &order {
+                let v = values[i][j];
+                out[i][j] = if v > 0.0 {
+                    positive += v;
+                    [positive - v, positive]
+                } else if v < 0.0 {
+                    negative += v;
+                    [negative, negative - v]
+                } else {
+                    [0.0, 0.0]
+                };
+            }
+        }
+        return out;
+    }
+
+    let mut top = baseline(&values, &order, offset, m);
+    for &i in &order {
+        for j in 0..m {
+            let v = values[i][j];
+            let y0 = top[j];
+            top[j] += if v.is_nan() { 0.0 } else { v };
+            out[i][j] = [y0, top[j]];
+        }
+    }
+    out
+}
+
+/// Stacks the values of a table in long format, one row per x and series, and returns it
+/// with the new columns `y0` and `y1`. Rows with the same x and series share their interval
+pub fn stack(table: &Table, params: &StackParams) -> Result<Table, DataError> {
+    let xs = table.column(&params.x)?.to_strings();
+    let keys = table.column(&params.series)?.to_strings();
+    let values = table.column(&params.value)?.to_f64();
+
+    let mut x_index: HashMap<&str, usize> = HashMap::new();
+    let mut key_index: HashMap<&str, usize> = HashMap::new();
+    let cells: Vec<(usize, usize)> = xs.iter().zip(&keys)
+        .map(|(x, key)| {
+            let len = x_index.len();
+            let j = *x_index.entry(x).or_insert(len);
+            let len = key_index.len();
+            let i = *key_index.entry(key).or_insert(len);
+            (i, j)
+        })
+        .collect();
+
+    let mut matrix = vec![vec![f64::NAN; x_index.len()]; key_index.len()];
+    for (&(i, j), &v) in cells.iter().zip(&values) {
+        if v.is_nan() { continue };
+        matrix[i][j] = if matrix[i][j].is_nan() { v } else { matrix[i][j] + v };
+    }
+
+    let stacked = stack_series(&matrix, params.order, params.offset);
+    let y0: Vec<f64> = cells.iter().map(|&(i, j)| stacked[i][j][0]).collect();
+    let y1: Vec<f64> = cells.iter().map(|&(i, j)| stacked[i][j][1]).collect();
+    table.clone()
+        .with_column("y0", y0)?
+        .with_column("y1", y1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series() -> Vec<Vec<f64>> {
+        vec![vec![1.0, 2.0, 1.0], vec![3.0, 4.0, 2.0], vec![1.0, 0.0, 5.0]]
+    }
+
+    #[test]
+    fn offset_works() {
+        let none = stack_series(&series(), StackOrder::Input, StackOffset::None);
+        assert_eq!(none[0], [[0.0, 1.0], [0.0, 2.0], [0.0, 1.0]]);
+        assert_eq!(none[2], [[4.0, 5.0], [6.0, 6.0], [3.0, 8.0]]);
+
+        let expand = stack_series(&series(), StackOrder::Input, StackOffset::Expand);
+        assert_eq!(expand[1][0], [0.2, 0.8]);
+        assert_eq!(expand[2][1], [1.0, 1.0]);
+
+        let silhouette = stack_series(&series(), StackOrder::Input, StackOffset::Silhouette);
+        assert_eq!(silhouette[0][0], [-2.5, -1.5]);
+        assert_eq!(silhouette[2][2], [-1.0, 4.0]);
+
+        let diverging = stack_series(&[vec![1.0, -1.0], vec![-2.0, 3.0], vec![4.0, -5.0]], StackOrder::Input, StackOffset::Diverging);
+        assert_eq!(diverging, [[[0.0, 1.0], [-1.0, 0.0]], [[-2.0, 0.0], [0.0, 3.0]], [[1.0, 5.0], [-6.0, -1.0]]]);
+
+        let wiggle = stack_series(&[vec![1.0, 1.0], vec![1.0, 3.0]], StackOrder::Input, StackOffset::Wiggle);
+        assert_eq!(wiggle, [[[0.0, 1.0], [-0.75, 0.25]], [[1.0, 2.0], [0.25, 3.25]]]);
+    }
+
+    #[test]
+    fn order_works() {
+        assert_eq!(stack_order(&series(), StackOrder::Input), [0, 1, 2]);
+        assert_eq!(stack_order(&series(), StackOrder::Ascending), [0, 2, 1]);
+        assert_eq!(stack_order(&series(), StackOrder::Descending), [1, 2, 0]);
+        assert_eq!(stack_order(&series(), StackOrder::InsideOut), [2, 0, 1]);
+
+        let ascending = stack_series(&series(), StackOrder::Ascending, StackOffset::None);
+        assert_eq!(ascending[2][0], [1.0, 2.0]);
+        assert_eq!(ascending[1][0], [2.0, 5.0]);
+    }
+
+    #[test]
+    fn stack_works() {
+        let table = Table::new()
+            .with_column("month", vec!["jan", "jan", "feb", "feb", "feb"]).unwrap()
+            .with_column("fruit", vec!["apple", "pear", "apple", "pear", "pear"]).unwrap()
+            .with_column("sold", vec![Some(3.0), Some(2.0), None, Some(1.0), Some(4.0)]).unwrap();
+        let params = StackParamsBuilder::default()
+            .x("month")
+            .series("fruit")
+            .value("sold")
+            .build().unwrap();
+        let stacked = stack(&table, &params).unwrap();
+        assert_eq!(stacked.column("y0").unwrap().as_f64().unwrap(), [0.0, 3.0, 0.0, 0.0, 0.0]);
+        assert_eq!(stacked.column("y1").unwrap().as_f64().unwrap(), [3.0, 5.0, 0.0, 5.0, 5.0]);
+
+        let params = StackParamsBuilder::default().x("month").series("fruit").value("price").build().unwrap();
+        assert_eq!(stack(&table, &params), Err(DataError::ColumnNotFound("price".into())));
+    }
+}
@@ -19,6 +19,15 @@ impl Extend<f64> for f64 {
     }
 }
 
+impl Extend<i64> for i64 {
+    /// Computes the min and max of an array
+    fn extend(vec: &[i64]) -> [i64; 2] {
+        let min = vec.iter().cloned().min().unwrap_or(i64::MAX);
+        let max = vec.iter().cloned().max().unwrap_or(i64::MIN);
+        [min, max]
+    }
+}
+
 pub trait InDelta<T> {
     fn in_delta(&self, other: T) -> bool;
 }